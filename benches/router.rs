@@ -0,0 +1,53 @@
+//! Routing benchmark, recognizes the last registered route of
+//! applications with different number of routes.
+#[macro_use]
+extern crate criterion;
+extern crate actix_web;
+
+use actix_web::server::HttpHandler;
+use actix_web::test::TestRequest;
+use actix_web::{App, HttpResponse};
+use criterion::Criterion;
+
+const ROUTES: [usize; 4] = [10, 100, 400, 1000];
+
+fn app(routes: usize) -> impl HttpHandler {
+    let mut app = App::new();
+    for idx in 0..routes {
+        app = app
+            .resource(&format!("/static{}/index.html", idx), |r| {
+                r.f(|_| HttpResponse::Ok())
+            })
+            .resource(&format!("/user{}/{{id}}/profile", idx), |r| {
+                r.f(|_| HttpResponse::Ok())
+            });
+    }
+    app.finish()
+}
+
+fn bench_static(c: &mut Criterion) {
+    c.bench_function_over_inputs(
+        "route static",
+        |b, &&routes| {
+            let app = app(routes);
+            let path = format!("/static{}/index.html", routes - 1);
+            b.iter(|| app.handle(TestRequest::with_uri(&path).request()).ok())
+        },
+        &ROUTES,
+    );
+}
+
+fn bench_param(c: &mut Criterion) {
+    c.bench_function_over_inputs(
+        "route param",
+        |b, &&routes| {
+            let app = app(routes);
+            let path = format!("/user{}/12345/profile", routes - 1);
+            b.iter(|| app.handle(TestRequest::with_uri(&path).request()).ok())
+        },
+        &ROUTES,
+    );
+}
+
+criterion_group!(benches, bench_static, bench_param);
+criterion_main!(benches);
@@ -5,6 +5,9 @@ fn main() {
         Some((true, _)) => println!("cargo:rustc-cfg=actix_impl_trait"),
         _ => (),
     };
+    if let Some((true, _)) = version_check::is_min_version("1.38.0") {
+        println!("cargo:rustc-cfg=actix_type_name");
+    }
     match version_check::is_nightly() {
         Some(true) => {
             println!("cargo:rustc-cfg=actix_nightly");
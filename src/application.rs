@@ -1,4 +1,10 @@
 use std::rc::Rc;
+use std::sync::Arc;
+
+#[cfg(feature = "openapi")]
+use bytes::Bytes;
+#[cfg(feature = "openapi")]
+use serde_json;
 
 use handler::{AsyncResult, FromRequest, Handler, Responder, WrapHandler};
 use header::ContentEncoding;
@@ -6,12 +12,18 @@ use http::Method;
 use httprequest::HttpRequest;
 use httpresponse::HttpResponse;
 use middleware::Middleware;
+#[cfg(feature = "openapi")]
+use openapi::OpenApi;
+use param::{Converters, ParamConverter};
 use pipeline::{Pipeline, PipelineHandler};
-use pred::Predicate;
+use pred::{self, Predicate};
 use resource::Resource;
-use router::{ResourceDef, Router};
+use router::{ResourceDef, RouteEntry, RouteTable, Router};
 use scope::Scope;
-use server::{HttpHandler, HttpHandlerTask, IntoHttpHandler, Request};
+use server::input::DecodeLimits;
+use server::{
+    CompressionPolicy, HttpHandler, HttpHandlerTask, IntoHttpHandler, Request,
+};
 use with::WithFactory;
 
 /// Application
@@ -27,7 +39,10 @@ pub struct HttpApplication<S = ()> {
 #[doc(hidden)]
 pub struct Inner<S> {
     router: Router<S>,
+    converters: Converters,
     encoding: ContentEncoding,
+    compression: Rc<CompressionPolicy>,
+    decode_limits: DecodeLimits,
 }
 
 impl<S: 'static> PipelineHandler<S> for Inner<S> {
@@ -36,12 +51,32 @@ impl<S: 'static> PipelineHandler<S> for Inner<S> {
         self.encoding
     }
 
+    #[inline]
+    fn compression(&self) -> &Rc<CompressionPolicy> {
+        &self.compression
+    }
+
     fn handle(&self, req: &HttpRequest<S>) -> AsyncResult<HttpResponse> {
         self.router.handle(req)
     }
 }
 
 impl<S: 'static> HttpApplication<S> {
+    /// Flattened route table of the application.
+    ///
+    /// See `App::routes()`.
+    pub fn routes(&self) -> RouteTable {
+        let preds = self.filters.as_ref().map(|f| pred::names(f));
+        let base = RouteEntry::new(
+            &self.prefix,
+            self.middlewares.len(),
+            preds.unwrap_or_default(),
+        );
+        let mut table = Vec::new();
+        self.inner.router.route_table(&base, &mut table);
+        RouteTable::new(table, self.inner.converters.clone())
+    }
+
     #[cfg(test)]
     pub(crate) fn run(&self, req: Request) -> AsyncResult<HttpResponse> {
         let info = self
@@ -77,6 +112,10 @@ impl<S: 'static> HttpHandler for HttpApplication<S> {
                 }
             }
 
+            if let Some(payload) = msg.inner.payload.borrow().as_ref() {
+                payload.set_decode_limits(self.inner.decode_limits);
+            }
+
             let info = self
                 .inner
                 .router
@@ -95,9 +134,14 @@ struct ApplicationParts<S> {
     state: S,
     prefix: String,
     router: Router<S>,
+    converters: Converters,
     encoding: ContentEncoding,
+    compression: CompressionPolicy,
+    decode_limits: DecodeLimits,
     middlewares: Vec<Box<Middleware<S>>>,
     filters: Vec<Box<Predicate<S>>>,
+    #[cfg(feature = "openapi")]
+    openapi: Option<(String, OpenApi)>,
 }
 
 /// Structure that follows the builder pattern for building application
@@ -142,9 +186,14 @@ where
                 state,
                 prefix: "".to_owned(),
                 router: Router::new(ResourceDef::prefix("")),
+                converters: Converters::default(),
                 middlewares: Vec::new(),
                 filters: Vec::new(),
+                #[cfg(feature = "openapi")]
+                openapi: None,
                 encoding: ContentEncoding::Auto,
+                compression: CompressionPolicy::default(),
+                decode_limits: DecodeLimits::default(),
             }),
         }
     }
@@ -314,7 +363,11 @@ where
     ///
     /// By default, each segment matches the regular expression `[^{}/]+`.
     ///
-    /// You can also specify a custom regex in the form `{identifier:regex}`:
+    /// You can also specify a custom regex in the form `{identifier:regex}`.
+    ///
+    /// Typed segments are specified in the form `{identifier:converter}`,
+    /// i.e. `{id:u64}`, `{id:uuid}` or `{tail:*}`, matched values are
+    /// checked by converter, see `ParamConverter` for details.
     ///
     /// For instance, to route `GET`-requests on any route matching
     /// `/users/{userid}/{friend}` and store `userid` and `friend` in
@@ -386,6 +439,66 @@ where
         self
     }
 
+    /// Set response compression policy.
+    ///
+    /// Policy defines compression levels, minimal size and content types
+    /// of compressed responses, and server preference of encodings.
+    /// See [`CompressionPolicy`](server/struct.CompressionPolicy.html)
+    /// for default values.
+    pub fn compression(mut self, policy: CompressionPolicy) -> App<S> {
+        {
+            let parts = self.parts.as_mut().expect("Use after finish");
+            parts.compression = policy;
+        }
+        self
+    }
+
+    /// Set maximum size of decoded request payload.
+    ///
+    /// Compressed request payload is decoded while it is read, reading
+    /// fails with `PayloadError::Overflow` error as soon as decoded data
+    /// exceeds the limit. By default size is not limited.
+    pub fn max_decoded_size(mut self, size: usize) -> App<S> {
+        {
+            let parts = self.parts.as_mut().expect("Use after finish");
+            parts.decode_limits.max_size = Some(size);
+        }
+        self
+    }
+
+    /// Set maximum compression ratio of request payload.
+    ///
+    /// Reading compressed request payload fails with `PayloadError::Overflow`
+    /// error if size of decoded data exceeds size of encoded data more than
+    /// `ratio` times. Ratio is not checked until 64Kb of data is decoded.
+    /// By default ratio is not limited.
+    pub fn max_compression_ratio(mut self, ratio: usize) -> App<S> {
+        {
+            let parts = self.parts.as_mut().expect("Use after finish");
+            parts.decode_limits.max_ratio = Some(ratio);
+        }
+        self
+    }
+
+    /// Register typed path parameter converter.
+    ///
+    /// Converter is used by `{name:converter}` parameters of application
+    /// resources, scopes and external resources. Patterns are resolved
+    /// when application configuration is finished, so converter could be
+    /// registered after resources which use it. Converter registered with
+    /// the same name is replaced. See `ParamConverter` for example.
+    ///
+    /// Panics if name is a name of built-in converter or if converter
+    /// regex is wrong.
+    pub fn converter<C: ParamConverter>(mut self, name: &str, converter: C) -> App<S> {
+        self.parts
+            .as_mut()
+            .expect("Use after finish")
+            .converters
+            .register(name, Arc::new(converter));
+        self
+    }
+
     /// Register an external resource.
     ///
     /// External resources are useful for URL generation purposes only
@@ -504,15 +617,86 @@ where
         cfg(self)
     }
 
+    /// Flattened route table of the application.
+    ///
+    /// Table contains full patterns of all routes, including application
+    /// prefix and scope paths, resource names, matched methods, predicates,
+    /// number of middlewares and handler type names. Routes are listed in
+    /// registration order, see `RouteTable` for example.
+    pub fn routes(&self) -> RouteTable {
+        let parts = self.parts.as_ref().expect("Use after finish");
+        let prefix = parts.prefix.trim().trim_right_matches('/');
+        let base = RouteEntry::new(
+            prefix,
+            parts.middlewares.len(),
+            pred::names(&parts.filters),
+        );
+        let mut table = Vec::new();
+        parts.router.route_table(&base, &mut table);
+        RouteTable::new(table, parts.converters.clone())
+    }
+
+    /// Serve OpenAPI document of the application routes.
+    ///
+    /// Document is generated from the route table when application
+    /// configuration is finished, and served as json with *GET* method
+    /// at the specified path. See `openapi` module for details.
+    ///
+    /// ```rust
+    /// # extern crate actix_web;
+    /// use actix_web::openapi::OpenApi;
+    /// use actix_web::{App, HttpResponse};
+    ///
+    /// fn main() {
+    ///     let app = App::new()
+    ///         .resource("/index.html", |r| r.get().f(|_| HttpResponse::Ok()))
+    ///         .openapi("/openapi.json", OpenApi::new("Index", "1.0.0"))
+    ///         .finish();
+    /// }
+    /// ```
+    #[cfg(feature = "openapi")]
+    pub fn openapi(mut self, path: &str, api: OpenApi) -> App<S> {
+        self.parts.as_mut().expect("Use after finish").openapi =
+            Some((path.to_owned(), api));
+        self
+    }
+
+    /// Register resource that serves OpenAPI document
+    #[cfg(feature = "openapi")]
+    fn register_openapi(&mut self) {
+        let openapi = self.parts.as_mut().and_then(|parts| parts.openapi.take());
+        if let Some((path, api)) = openapi {
+            let doc = api.document(&self.routes());
+            let body = Bytes::from(serde_json::to_vec(&doc).unwrap());
+            let mut resource = Resource::new(ResourceDef::new(&path));
+            resource
+                .get()
+                .openapi(|op| {
+                    op.hidden();
+                }).f(move |_| {
+                    HttpResponse::Ok()
+                        .content_type("application/json")
+                        .body(body.clone())
+                });
+            self.register_resource(resource);
+        }
+    }
+
     /// Finish application configuration and create `HttpHandler` object.
     pub fn finish(&mut self) -> HttpApplication<S> {
+        #[cfg(feature = "openapi")]
+        self.register_openapi();
+
         let mut parts = self.parts.take().expect("Use after finish");
         let prefix = parts.prefix.trim().trim_right_matches('/');
-        parts.router.finish();
+        parts.router.finish(&parts.converters);
 
         let inner = Rc::new(Inner {
             router: parts.router,
+            converters: parts.converters,
             encoding: parts.encoding,
+            compression: Rc::new(parts.compression),
+            decode_limits: parts.decode_limits,
         });
         let filters = if parts.filters.is_empty() {
             None
@@ -616,7 +800,7 @@ impl<S: 'static> Iterator for App<S> {
 mod tests {
     use super::*;
     use body::{Binary, Body};
-    use http::StatusCode;
+    use http::{header, StatusCode};
     use httprequest::HttpRequest;
     use httpresponse::HttpResponse;
     use pred;
@@ -770,6 +954,76 @@ mod tests {
         assert_eq!(resp.as_msg().status(), StatusCode::NOT_FOUND);
     }
 
+    #[test]
+    fn test_converter() {
+        struct Even;
+
+        impl ParamConverter for Even {
+            fn regex(&self) -> &str {
+                "[0-9]+"
+            }
+
+            fn is_valid(&self, value: &str) -> bool {
+                value.parse::<u64>().map(|v| v % 2 == 0).unwrap_or(false)
+            }
+        }
+
+        let app = App::new()
+            .resource("/num/{n:even}", |r| {
+                r.name("even");
+                r.f(|req| match req.url_for("even", &["3"]) {
+                    Ok(_) => HttpResponse::Conflict(),
+                    Err(_) => HttpResponse::Ok(),
+                })
+            }).scope("/scope", |s| {
+                s.resource("/{n:even}", |r| r.f(|_| HttpResponse::Created()))
+            }).converter("even", Even)
+            .finish();
+
+        let req = TestRequest::with_uri("/num/4").request();
+        let resp = app.run(req);
+        assert_eq!(resp.as_msg().status(), StatusCode::OK);
+
+        let req = TestRequest::with_uri("/num/5").request();
+        let resp = app.run(req);
+        assert_eq!(resp.as_msg().status(), StatusCode::NOT_FOUND);
+
+        let req = TestRequest::with_uri("/scope/6").request();
+        let resp = app.run(req);
+        assert_eq!(resp.as_msg().status(), StatusCode::CREATED);
+
+        let req = TestRequest::with_uri("/scope/7").request();
+        let resp = app.run(req);
+        assert_eq!(resp.as_msg().status(), StatusCode::NOT_FOUND);
+
+        // converters are not shared between applications
+        let app = App::new()
+            .resource("/num/{n:even}", |r| r.f(|_| HttpResponse::Ok()))
+            .finish();
+
+        let req = TestRequest::with_uri("/num/4").request();
+        let resp = app.run(req);
+        assert_eq!(resp.as_msg().status(), StatusCode::NOT_FOUND);
+
+        let req = TestRequest::with_uri("/num/even").request();
+        let resp = app.run(req);
+        assert_eq!(resp.as_msg().status(), StatusCode::OK);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_builtin_converter() {
+        struct Any;
+
+        impl ParamConverter for Any {
+            fn regex(&self) -> &str {
+                ".*"
+            }
+        }
+
+        App::new().converter("u64", Any);
+    }
+
     #[test]
     fn test_route() {
         let app = App::new()
@@ -792,6 +1046,191 @@ mod tests {
             .method(Method::HEAD)
             .request();
         let resp = app.run(req);
+        assert_eq!(resp.as_msg().status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(
+            resp.as_msg().headers().get(header::ALLOW).unwrap(),
+            "GET, POST, OPTIONS"
+        );
+
+        let req = TestRequest::with_uri("/test")
+            .method(Method::OPTIONS)
+            .request();
+        let resp = app.run(req);
+        assert_eq!(resp.as_msg().status(), StatusCode::OK);
+        assert_eq!(
+            resp.as_msg().headers().get(header::ALLOW).unwrap(),
+            "GET, POST, OPTIONS"
+        );
+    }
+
+    #[test]
+    fn test_routes() {
+        use middleware::DefaultHeaders;
+
+        fn index(_: &HttpRequest) -> HttpResponse {
+            HttpResponse::Ok().finish()
+        }
+
+        struct State;
+
+        let mut app = App::new()
+            .prefix("/app")
+            .middleware(DefaultHeaders::new())
+            .resource("/index.html", |r| {
+                r.name("index");
+                r.get().f(index);
+                r.route()
+                    .filter(pred::Any(pred::Post()).or(pred::Put()))
+                    .filter(pred::Header("content-type", "text/plain"))
+                    .f(index);
+            }).scope("/api/", |scope| {
+                scope
+                    .middleware(DefaultHeaders::new())
+                    .filter(pred::Host("localhost"))
+                    .nested("/v1", |scope| scope.resource("/users", |r| r.f(index)))
+                    .with_state("/state", State, |scope| {
+                        scope.filter(pred::Header("x-state", "1")).route(
+                            "/test",
+                            Method::DELETE,
+                            |_: HttpRequest<State>| HttpResponse::Ok(),
+                        )
+                    })
+            }).handler("/static", |_: &_| HttpResponse::Ok());
+
+        let routes = app.routes();
+        let routes: Vec<_> = routes.iter().collect();
+        assert_eq!(routes.len(), 5);
+
+        assert_eq!(routes[0].pattern(), "/app/index.html");
+        assert_eq!(routes[0].name(), "index");
+        assert_eq!(routes[0].methods(), Some(&[Method::GET][..]));
+        assert!(routes[0].predicates().is_empty());
+        assert_eq!(routes[0].middlewares(), 1);
+
+        assert_eq!(routes[1].methods(), Some(&[Method::POST, Method::PUT][..]));
+
+        assert_eq!(routes[2].pattern(), "/app/api/v1/users");
+        assert_eq!(routes[2].name(), "");
+        assert_eq!(routes[2].methods(), None);
+        assert_eq!(routes[2].middlewares(), 2);
+
+        assert_eq!(routes[3].pattern(), "/app/api/state/test");
+        assert_eq!(routes[3].methods(), Some(&[Method::DELETE][..]));
+
+        assert_eq!(routes[4].pattern(), "/app/static");
+        assert_eq!(routes[4].methods(), None);
+        assert_eq!(routes[4].middlewares(), 1);
+
+        // type names are available with rustc 1.38 or later
+        #[cfg(actix_type_name)]
+        {
+            assert!(routes[0].handler().ends_with("index"));
+            assert_eq!(routes[1].predicates().len(), 2);
+            assert!(routes[1].predicates()[1].contains("HeaderPredicate"));
+            assert!(routes[2].predicates()[0].contains("HostPredicate"));
+            assert_eq!(routes[3].predicates().len(), 2);
+            assert!(routes[3].predicates()[1].contains("HeaderPredicate"));
+            assert!(routes[3].handler().contains("closure"));
+            assert!(routes[4].handler().contains("closure"));
+        }
+
+        let app = app.finish();
+        assert_eq!(app.routes().len(), 5);
+
+        let text = app.routes().to_string();
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert!(lines[0].starts_with("METHODS"));
+        assert!(lines[1].starts_with("GET       /app/index.html"));
+        #[cfg(actix_type_name)]
+        assert!(lines[2].contains("AnyPredicate,HeaderPredicate"));
+        assert!(lines[3].starts_with("*         /app/api/v1/users"));
+
+        let json = ::serde_json::to_value(&app.routes()).unwrap();
+        assert_eq!(json[0]["pattern"], "/app/index.html");
+        assert_eq!(json[0]["methods"][0], "GET");
+        assert_eq!(json[2]["methods"], ::serde_json::Value::Null);
+        assert_eq!(json[2]["middlewares"], 2);
+    }
+
+    #[test]
+    fn test_typed_params() {
+        use extractor::Path;
+
+        let app = App::new()
+            .resource("/{id:u32}", |r| {
+                r.with(|id: Path<u32>| format!("{}", id.into_inner()))
+            }).finish();
+
+        let req = TestRequest::with_uri("/42").request();
+        let resp = app.run(req);
+        assert_eq!(resp.as_msg().status(), StatusCode::OK);
+
+        for uri in &["/-1", "/abc", "/4294967296"] {
+            let req = TestRequest::with_uri(uri).request();
+            let resp = app.run(req);
+            assert_eq!(resp.as_msg().status(), StatusCode::NOT_FOUND);
+        }
+    }
+
+    #[cfg(feature = "openapi")]
+    #[test]
+    fn test_openapi() {
+        let app = App::new()
+            .prefix("/app")
+            .resource("/test", |r| r.get().f(|_| HttpResponse::Ok()))
+            .openapi("/openapi.json", OpenApi::new("Test", "1.0"))
+            .finish();
+        assert_eq!(app.routes().len(), 2);
+
+        let req = TestRequest::with_uri("/app/openapi.json").request();
+        let resp = app.run(req);
+        assert_eq!(resp.as_msg().status(), StatusCode::OK);
+        assert_eq!(
+            resp.as_msg().headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/json"
+        );
+        let doc: ::serde_json::Value = match *resp.as_msg().body() {
+            Body::Binary(ref b) => ::serde_json::from_slice(b.as_ref()).unwrap(),
+            _ => panic!(),
+        };
+        assert_eq!(doc["info"]["title"], "Test");
+        assert_eq!(doc["paths"].as_object().unwrap().len(), 1);
+        assert!(doc["paths"]["/app/test"]["get"].is_object());
+    }
+
+    #[test]
+    fn test_method_not_allowed() {
+        let app = App::new()
+            .resource("/test", |r| {
+                r.get().f(|_| HttpResponse::Ok());
+                r.method(Method::OPTIONS).f(|_| HttpResponse::NoContent());
+            }).resource("/any", |r| {
+                r.route()
+                    .filter(pred::Header("x-test", "1"))
+                    .f(|_| HttpResponse::Ok())
+            }).finish();
+
+        let req = TestRequest::with_uri("/test")
+            .method(Method::PUT)
+            .request();
+        let resp = app.run(req);
+        assert_eq!(resp.as_msg().status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(
+            resp.as_msg().headers().get(header::ALLOW).unwrap(),
+            "GET, OPTIONS"
+        );
+
+        let req = TestRequest::with_uri("/test")
+            .method(Method::OPTIONS)
+            .request();
+        let resp = app.run(req);
+        assert_eq!(resp.as_msg().status(), StatusCode::NO_CONTENT);
+
+        let req = TestRequest::with_uri("/any")
+            .method(Method::PUT)
+            .request();
+        let resp = app.run(req);
         assert_eq!(resp.as_msg().status(), StatusCode::NOT_FOUND);
     }
 
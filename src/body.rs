@@ -1,5 +1,6 @@
 use bytes::{Bytes, BytesMut};
 use futures::Stream;
+use std::fs::File;
 use std::sync::Arc;
 use std::{fmt, mem};
 
@@ -25,6 +26,15 @@ pub enum Body {
     Actor(Box<ActorHttpContext>),
 }
 
+/// Region of a file, which can be sent directly to the peer
+/// instead of the streaming body of the response.
+#[derive(Debug)]
+pub(crate) struct FileBody {
+    pub file: File,
+    pub offset: u64,
+    pub size: u64,
+}
+
 /// Represents various types of binary body.
 /// `Content-Length` header is set to length of the body.
 #[derive(Debug, PartialEq)]
@@ -1,5 +1,7 @@
 use std::collections::{HashMap, VecDeque};
 use std::net::Shutdown;
+#[cfg(all(unix, feature = "uds"))]
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
 use std::{fmt, io, mem, time};
 
@@ -16,6 +18,11 @@ use http::{Error as HttpError, HttpTryFrom, Uri};
 use tokio_io::{AsyncRead, AsyncWrite};
 use tokio_timer::Delay;
 
+#[cfg(all(unix, feature = "uds"))]
+use tokio_timer::Timeout;
+#[cfg(all(unix, feature = "uds"))]
+use tokio_uds::UnixStream;
+
 #[cfg(feature = "alpn")]
 use openssl::ssl::{Error as OpensslError, SslConnector, SslMethod};
 #[cfg(feature = "alpn")]
@@ -261,6 +268,8 @@ pub struct ClientConnector {
     acq_rx: Option<mpsc::UnboundedReceiver<AcquiredConnOperation>>,
 
     resolver: Option<Addr<Resolver>>,
+    #[cfg(all(unix, feature = "uds"))]
+    uds: HashMap<String, PathBuf>,
     conn_lifetime: Duration,
     conn_keep_alive: Duration,
     limit: usize,
@@ -308,6 +317,8 @@ impl Default for ClientConnector {
                 acq_tx: tx,
                 acq_rx: Some(rx),
                 resolver: None,
+                #[cfg(all(unix, feature = "uds"))]
+                uds: HashMap::new(),
                 connector: builder.build().unwrap().into(),
                 conn_lifetime: Duration::from_secs(75),
                 conn_keep_alive: Duration::from_secs(15),
@@ -345,6 +356,8 @@ impl Default for ClientConnector {
                 acq_tx: tx,
                 acq_rx: Some(rx),
                 resolver: None,
+                #[cfg(all(unix, feature = "uds"))]
+                uds: HashMap::new(),
                 conn_lifetime: Duration::from_secs(75),
                 conn_keep_alive: Duration::from_secs(15),
                 limit: 100,
@@ -411,6 +424,8 @@ impl ClientConnector {
             acq_tx: tx,
             acq_rx: Some(rx),
             resolver: None,
+            #[cfg(all(unix, feature = "uds"))]
+            uds: HashMap::new(),
             conn_lifetime: Duration::from_secs(75),
             conn_keep_alive: Duration::from_secs(15),
             limit: 100,
@@ -485,6 +500,8 @@ impl ClientConnector {
             acq_tx: tx,
             acq_rx: Some(rx),
             resolver: None,
+            #[cfg(all(unix, feature = "uds"))]
+            uds: HashMap::new(),
             conn_lifetime: Duration::from_secs(75),
             conn_keep_alive: Duration::from_secs(15),
             limit: 100,
@@ -550,6 +567,20 @@ impl ClientConnector {
         self
     }
 
+    #[cfg(all(unix, feature = "uds"))]
+    /// Connect to the host over unix domain socket.
+    ///
+    /// Plain http connections to the `host` are established to the socket at
+    /// `path` instead of resolving host name, port of the url is ignored.
+    pub fn uds_host<H, P>(mut self, host: H, path: P) -> Self
+    where
+        H: Into<String>,
+        P: Into<PathBuf>,
+    {
+        self.uds.insert(host.into(), path.into());
+        self
+    }
+
     fn acquire(&mut self, key: &Key) -> Acquire {
         // check limits
         if self.limit > 0 {
@@ -755,7 +786,47 @@ impl ClientConnector {
         self.waiters = Some(act_waiters);
     }
 
+    #[cfg(all(unix, feature = "uds"))]
+    fn connect_uds(
+        &mut self, key: Key, path: PathBuf, waiter: Waiter, ctx: &mut Context<Self>,
+    ) {
+        let conn = AcquiredConn(key.clone(), Some(self.acq_tx.clone()));
+
+        fut::WrapFuture::<ClientConnector>::actfuture(Timeout::new(
+            UnixStream::connect(path),
+            waiter.conn_timeout,
+        )).then(move |res, act, _| {
+            match res {
+                Ok(stream) => {
+                    act.stats.opened += 1;
+                    let _ = waiter.tx.send(Ok(Connection::new(
+                        conn.0.clone(),
+                        Some(conn),
+                        Box::new(stream),
+                    )));
+                }
+                Err(err) => {
+                    let err = match err.into_inner() {
+                        Some(err) => ClientConnectorError::IoError(err),
+                        None => ClientConnectorError::Timeout,
+                    };
+                    let _ = waiter.tx.send(Err(err));
+                }
+            }
+            fut::ok(())
+        }).spawn(ctx);
+    }
+
     fn connect_waiter(&mut self, key: &Key, waiter: Waiter, ctx: &mut Context<Self>) {
+        #[cfg(all(unix, feature = "uds"))]
+        {
+            if !key.ssl {
+                if let Some(path) = self.uds.get(&key.host).cloned() {
+                    return self.connect_uds(key.clone(), path, waiter, ctx);
+                }
+            }
+        }
+
         let key = key.clone();
         let conn = AcquiredConn(key.clone(), Some(self.acq_tx.clone()));
 
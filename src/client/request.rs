@@ -22,6 +22,7 @@ use http::header::{self, HeaderName, HeaderValue};
 use http::{uri, Error as HttpError, HeaderMap, HttpTryFrom, Method, Uri, Version};
 use httpmessage::HttpMessage;
 use httprequest::HttpRequest;
+use middleware::request_id::RequestId;
 
 /// An HTTP Client Request
 ///
@@ -416,6 +417,18 @@ impl ClientRequestBuilder {
         self
     }
 
+    /// Forward identifier of the server request.
+    ///
+    /// Identifier assigned by `RequestIdService` middleware is set to
+    /// the same header as it was received with. Nothing is set if request
+    /// has no identifier.
+    pub fn forward_request_id<S>(&mut self, req: &HttpRequest<S>) -> &mut Self {
+        if let Some(id) = req.extensions().get::<RequestId>() {
+            self.set_header(id.header().clone(), id.as_str());
+        }
+        self
+    }
+
     /// Set a header only if it is not yet set.
     pub fn set_header_if_none<K, V>(&mut self, key: K, value: V) -> &mut Self
     where
@@ -619,11 +632,19 @@ impl ClientRequestBuilder {
                 true
             };
 
-            if https {
-                self.set_header_if_none(header::ACCEPT_ENCODING, "br, gzip, deflate");
+            #[cfg(not(feature = "zstd"))]
+            let encodings = if https {
+                "br, gzip, deflate"
             } else {
-                self.set_header_if_none(header::ACCEPT_ENCODING, "gzip, deflate");
-            }
+                "gzip, deflate"
+            };
+            #[cfg(feature = "zstd")]
+            let encodings = if https {
+                "br, zstd, gzip, deflate"
+            } else {
+                "zstd, gzip, deflate"
+            };
+            self.set_header_if_none(header::ACCEPT_ENCODING, encodings);
 
             // set request host header
             if let Some(parts) = parts(&mut self.request, &self.err) {
@@ -224,7 +224,7 @@ fn content_encoder(buf: BytesMut, req: &mut ClientRequest) -> Output {
             return Output::Empty(buf);
         }
         Body::Binary(ref mut bytes) => {
-            #[cfg(any(feature = "flate2", feature = "brotli"))]
+            #[cfg(any(feature = "flate2", feature = "brotli", feature = "zstd"))]
             {
                 if encoding.is_compression() {
                     let mut tmp = BytesMut::new();
@@ -243,6 +243,8 @@ fn content_encoder(buf: BytesMut, req: &mut ClientRequest) -> Output {
                         ContentEncoding::Br => {
                             ContentEncoder::Br(BrotliEncoder::new(transfer, 5))
                         }
+                        #[cfg(feature = "zstd")]
+                        ContentEncoding::Zstd => ContentEncoder::zstd(transfer, 3),
                         ContentEncoding::Auto | ContentEncoding::Identity => {
                             unreachable!()
                         }
@@ -264,7 +266,7 @@ fn content_encoder(buf: BytesMut, req: &mut ClientRequest) -> Output {
                     .insert(CONTENT_LENGTH, HeaderValue::try_from(b.freeze()).unwrap());
                 TransferEncoding::eof(buf)
             }
-            #[cfg(not(any(feature = "flate2", feature = "brotli")))]
+            #[cfg(not(any(feature = "flate2", feature = "brotli", feature = "zstd")))]
             {
                 let mut b = BytesMut::new();
                 let _ = write!(b, "{}", bytes.len());
@@ -311,6 +313,8 @@ fn content_encoder(buf: BytesMut, req: &mut ClientRequest) -> Output {
         }
         #[cfg(feature = "brotli")]
         ContentEncoding::Br => ContentEncoder::Br(BrotliEncoder::new(transfer, 5)),
+        #[cfg(feature = "zstd")]
+        ContentEncoding::Zstd => ContentEncoder::zstd(transfer, 3),
         ContentEncoding::Identity | ContentEncoding::Auto => return Output::TE(transfer),
     };
     Output::Encoder(enc)
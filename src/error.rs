@@ -501,7 +501,10 @@ impl ResponseError for UrlencodedError {
 
 impl From<PayloadError> for UrlencodedError {
     fn from(err: PayloadError) -> UrlencodedError {
-        UrlencodedError::Payload(err)
+        match err {
+            PayloadError::Overflow => UrlencodedError::Overflow,
+            err => UrlencodedError::Payload(err),
+        }
     }
 }
 
@@ -536,7 +539,10 @@ impl ResponseError for JsonPayloadError {
 
 impl From<PayloadError> for JsonPayloadError {
     fn from(err: PayloadError) -> JsonPayloadError {
-        JsonPayloadError::Payload(err)
+        match err {
+            PayloadError::Overflow => JsonPayloadError::Overflow,
+            err => JsonPayloadError::Payload(err),
+        }
     }
 }
 
@@ -601,6 +607,9 @@ pub enum UrlGenerationError {
     /// Not all path pattern covered
     #[fail(display = "Not all path pattern covered")]
     NotEnoughElements,
+    /// Value does not match typed path parameter
+    #[fail(display = "Invalid value of path parameter: {}", _0)]
+    InvalidParam(String),
     /// URL parse error
     #[fail(display = "{}", _0)]
     ParseError(#[cause] UrlParseError),
@@ -624,6 +633,12 @@ pub enum StaticFileError {
     /// Cannot render directory
     #[fail(display = "Unable to render directory without index file")]
     IsDirectory,
+    /// Path is a hidden file or directory
+    #[fail(display = "Unable to serve hidden file")]
+    IsHidden,
+    /// Path resolves to a location outside of the base directory
+    #[fail(display = "Path is outside of the base directory")]
+    IsOutsideOfDirectory,
 }
 
 /// Return `NotFound` for `StaticFileError`
@@ -16,6 +16,8 @@ use error::{Error, ErrorBadRequest, ErrorNotFound, UrlencodedError};
 use handler::{AsyncResult, FromRequest};
 use httpmessage::{HttpMessage, MessageBody, UrlEncoded};
 use httprequest::HttpRequest;
+#[cfg(feature = "openapi")]
+use openapi::{schema_of, Operation, Schema};
 
 #[derive(PartialEq, Eq, PartialOrd, Ord)]
 /// Extract typed information from the request's path.
@@ -121,6 +123,11 @@ where
             .map_err(ErrorNotFound)
             .map(|inner| Path { inner })
     }
+
+    #[cfg(feature = "openapi")]
+    fn describe_api(op: &mut Operation) {
+        op.path_schema(schema_of::<T>());
+    }
 }
 
 impl<T: fmt::Debug> fmt::Debug for Path<T> {
@@ -209,6 +216,11 @@ where
             .map_err(|e| e.into())
             .map(Query)
     }
+
+    #[cfg(feature = "openapi")]
+    fn describe_api(op: &mut Operation) {
+        op.query_schema(&schema_of::<T>());
+    }
 }
 
 impl<T: fmt::Debug> fmt::Debug for Query<T> {
@@ -294,6 +306,11 @@ where
                 .map(Form),
         )
     }
+
+    #[cfg(feature = "openapi")]
+    fn describe_api(op: &mut Operation) {
+        op.request_body("application/x-www-form-urlencoded", schema_of::<T>());
+    }
 }
 
 impl<T: fmt::Debug> fmt::Debug for Form<T> {
@@ -403,6 +420,11 @@ impl<S: 'static> FromRequest<S> for Bytes {
 
         Ok(Box::new(MessageBody::new(req).limit(cfg.limit).from_err()))
     }
+
+    #[cfg(feature = "openapi")]
+    fn describe_api(op: &mut Operation) {
+        op.request_body("application/octet-stream", Bytes::schema());
+    }
 }
 
 /// Extract text information from the request's body.
@@ -462,6 +484,11 @@ impl<S: 'static> FromRequest<S> for String {
                 }),
         ))
     }
+
+    #[cfg(feature = "openapi")]
+    fn describe_api(op: &mut Operation) {
+        op.request_body("text/plain", String::schema());
+    }
 }
 
 /// Optionally extract a field from the request
@@ -524,6 +551,11 @@ where
             Err(_) => future::ok(None),
         }))
     }
+
+    #[cfg(feature = "openapi")]
+    fn describe_api(op: &mut Operation) {
+        T::describe_api(op)
+    }
 }
 
 /// Optionally extract a field from the request or extract the Error if unsuccessful
@@ -582,6 +614,11 @@ where
     fn from_request(req: &HttpRequest<S>, cfg: &Self::Config) -> Self::Result {
         Box::new(T::from_request(req, cfg).into().then(future::ok))
     }
+
+    #[cfg(feature = "openapi")]
+    fn describe_api(op: &mut Operation) {
+        T::describe_api(op)
+    }
 }
 
 /// Payload configuration for request's payload.
@@ -651,6 +688,11 @@ macro_rules! tuple_from_req ({$fut_type:ident, $(($n:tt, $T:ident)),+} => {
                 futs: ($(Some($T::from_request(req, &cfg.$n).into()),)+),
             })
         }
+
+        #[cfg(feature = "openapi")]
+        fn describe_api(op: &mut Operation) {
+            $($T::describe_api(op);)+
+        }
     }
 
     struct $fut_type<S, $($T: FromRequest<S>),+>
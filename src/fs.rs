@@ -1,11 +1,12 @@
 //! Static files support
 use std::fmt::Write;
+use std::ffi::OsStr;
 use std::fs::{DirEntry, File, Metadata};
 use std::io::{Read, Seek};
 use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
 use std::path::{Path, PathBuf};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use std::{cmp, io};
 
 #[cfg(unix)]
@@ -18,7 +19,11 @@ use htmlescape::encode_minimal as escape_html_entity;
 use mime;
 use mime_guess::{get_mime_type, guess_mime_type};
 use percent_encoding::{utf8_percent_encode, DEFAULT_ENCODE_SET};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde_json;
+use time;
 
+use body::FileBody;
 use error::{Error, StaticFileError};
 use handler::{AsyncResult, Handler, Responder, RouteHandler, WrapHandler};
 use header;
@@ -88,6 +93,55 @@ pub trait StaticFileConfig: Default {
     fn is_method_allowed(_method: &Method) -> bool {
         true
     }
+
+    ///Describes whether Actix should serve precompressed variants of a file.
+    ///
+    ///If enabled, a `.br` or `.gz` sibling of the requested file
+    ///(e.g. `app.js.br`) is served as-is when the client's `Accept-Encoding`
+    ///allows it. Content type is still derived from the original file.
+    ///
+    ///Defaults to `false`
+    fn is_use_precompressed() -> bool {
+        false
+    }
+
+    ///Describes `Cache-Control` directives for a file.
+    ///
+    ///Receives path and content type of the file, so fingerprinted assets
+    ///can be marked as `immutable` and HTML pages as `no-cache`.
+    ///`Expires` header is derived from the `max-age` directive, or set to
+    ///a date in the past for `no-cache` and `no-store`.
+    ///
+    ///By default no caching headers are set.
+    ///
+    ///```rust
+    /// # extern crate mime;
+    /// # extern crate actix_web;
+    /// use std::path::Path;
+    /// use actix_web::http::header::CacheDirective;
+    /// use actix_web::fs::StaticFileConfig;
+    ///
+    /// #[derive(Default)]
+    /// struct MyConfig;
+    ///
+    /// impl StaticFileConfig for MyConfig {
+    ///     fn cache_control(_: &Path, typ: &mime::Mime) -> Vec<CacheDirective> {
+    ///         if typ.subtype() == mime::HTML {
+    ///             vec![CacheDirective::NoCache]
+    ///         } else {
+    ///             vec![
+    ///                 CacheDirective::Public,
+    ///                 CacheDirective::MaxAge(31_536_000),
+    ///                 CacheDirective::Extension("immutable".to_owned(), None),
+    ///             ]
+    ///         }
+    ///     }
+    /// }
+    /// # fn main() {}
+    ///```
+    fn cache_control(_path: &Path, _typ: &mime::Mime) -> Vec<header::CacheDirective> {
+        Vec::new()
+    }
 }
 
 ///Default content disposition as described in
@@ -115,6 +169,7 @@ pub struct NamedFile<C = DefaultConfig> {
     modified: Option<SystemTime>,
     cpu_pool: Option<CpuPool>,
     encoding: Option<ContentEncoding>,
+    precompressed: Option<&'static str>,
     status_code: StatusCode,
     _cd_map: PhantomData<C>,
 }
@@ -183,6 +238,7 @@ impl<C: StaticFileConfig> NamedFile<C> {
             modified,
             cpu_pool,
             encoding,
+            precompressed: None,
             status_code: StatusCode::OK,
             _cd_map: PhantomData,
         })
@@ -272,11 +328,13 @@ impl<C: StaticFileConfig> NamedFile<C> {
                 .duration_since(UNIX_EPOCH)
                 .expect("modification time must be after epoch");
             header::EntityTag::strong(format!(
-                "{:x}:{:x}:{:x}:{:x}",
+                "{:x}:{:x}:{:x}:{:x}{}",
                 ino,
                 self.md.len(),
                 dur.as_secs(),
-                dur.subsec_nanos()
+                dur.subsec_nanos(),
+                // every encoded variant is a separate representation
+                self.precompressed.map_or(String::new(), |enc| format!("-{}", enc))
             ))
         })
     }
@@ -284,6 +342,56 @@ impl<C: StaticFileConfig> NamedFile<C> {
     fn last_modified(&self) -> Option<header::HttpDate> {
         self.modified.map(|mtime| mtime.into())
     }
+
+    /// Switch to the precompressed sibling of this file that is most
+    /// preferred by the client, if there is any.
+    fn use_precompressed<S>(&mut self, req: &HttpRequest<S>) {
+        let items = match req.get_header::<header::AcceptEncoding>() {
+            Some(header::AcceptEncoding(items)) => items,
+            None => return,
+        };
+        // `*` applies to encodings which are not listed explicitly
+        let any = header::Encoding::EncodingExt("*".to_owned());
+        let quality = |enc: &header::Encoding| {
+            items
+                .iter()
+                .find(|item| item.item == *enc)
+                .or_else(|| items.iter().find(|item| item.item == any))
+                .map(|item| item.quality)
+        };
+        let mut candidates = Vec::new();
+        for &(ref enc, ext, name) in &[
+            (header::Encoding::Brotli, ".br", "br"),
+            (header::Encoding::Gzip, ".gz", "gzip"),
+        ] {
+            match quality(enc) {
+                Some(q) if q != header::q(0) => candidates.push((q, ext, name)),
+                _ => (),
+            }
+        }
+        // stable sort, brotli wins over gzip when client qualities tie
+        candidates.sort_by(|a, b| b.0.cmp(&a.0));
+
+        for (_, ext, enc) in candidates {
+            let mut path = self.path.clone().into_os_string();
+            path.push(ext);
+
+            let file = match File::open(&path) {
+                Ok(file) => file,
+                Err(_) => continue,
+            };
+            match file.metadata() {
+                Ok(md) => if md.is_file() {
+                    self.modified = md.modified().ok();
+                    self.md = md;
+                    self.file = file;
+                    self.precompressed = Some(enc);
+                    return;
+                },
+                Err(_) => continue,
+            }
+        }
+    }
 }
 
 impl<C> Deref for NamedFile<C> {
@@ -300,6 +408,23 @@ impl<C> DerefMut for NamedFile<C> {
     }
 }
 
+/// Returns `Expires` value matching `Cache-Control` directives.
+fn expires(directives: &[header::CacheDirective]) -> Option<header::HttpDate> {
+    for directive in directives {
+        if let header::CacheDirective::MaxAge(secs) = *directive {
+            let exp = SystemTime::now() + Duration::from_secs(u64::from(secs));
+            return Some(exp.into());
+        }
+    }
+    if directives.iter().any(|d| {
+        *d == header::CacheDirective::NoCache || *d == header::CacheDirective::NoStore
+    }) {
+        Some(UNIX_EPOCH.into())
+    } else {
+        None
+    }
+}
+
 /// Returns true if `req` has no `If-Match` header or one which matches `etag`.
 fn any_match<S>(etag: Option<&header::EntityTag>, req: &HttpRequest<S>) -> bool {
     match req.get_header::<header::IfMatch>() {
@@ -339,7 +464,12 @@ impl<C: StaticFileConfig> Responder for NamedFile<C> {
     type Item = HttpResponse;
     type Error = io::Error;
 
-    fn respond_to<S>(self, req: &HttpRequest<S>) -> Result<HttpResponse, io::Error> {
+    fn respond_to<S>(mut self, req: &HttpRequest<S>) -> Result<HttpResponse, io::Error> {
+        let precompressed = C::is_use_precompressed() && self.encoding.is_none();
+        if precompressed {
+            self.use_precompressed(req);
+        }
+
         if self.status_code != StatusCode::OK {
             let mut resp = HttpResponse::build(self.status_code);
             resp.set(header::ContentType(self.content_type.clone()))
@@ -351,6 +481,13 @@ impl<C: StaticFileConfig> Responder for NamedFile<C> {
             if let Some(current_encoding) = self.encoding {
                 resp.content_encoding(current_encoding);
             }
+            if let Some(enc) = self.precompressed {
+                resp.content_encoding(ContentEncoding::Identity)
+                    .header(header::CONTENT_ENCODING, enc);
+            }
+            if precompressed {
+                resp.header(header::VARY, "Accept-Encoding");
+            }
             let reader = ChunkedReadFile {
                 size: self.md.len(),
                 offset: 0,
@@ -408,6 +545,13 @@ impl<C: StaticFileConfig> Responder for NamedFile<C> {
         if let Some(current_encoding) = self.encoding {
             resp.content_encoding(current_encoding);
         }
+        if let Some(enc) = self.precompressed {
+            resp.content_encoding(ContentEncoding::Identity)
+                .header(header::CONTENT_ENCODING, enc);
+        }
+        if precompressed {
+            resp.header(header::VARY, "Accept-Encoding");
+        }
 
         resp.if_some(last_modified, |lm, resp| {
             resp.set(header::LastModified(lm));
@@ -415,6 +559,13 @@ impl<C: StaticFileConfig> Responder for NamedFile<C> {
             resp.set(header::ETag(etag));
         });
 
+        let directives = C::cache_control(&self.path, &self.content_type);
+        if !directives.is_empty() {
+            resp.if_some(expires(&directives), |exp, resp| {
+                resp.set(header::Expires(exp));
+            }).set(header::CacheControl(directives));
+        }
+
         resp.header(header::ACCEPT_RANGES, "bytes");
 
         let mut length = self.md.len();
@@ -456,6 +607,13 @@ impl<C: StaticFileConfig> Responder for NamedFile<C> {
         if *req.method() == Method::HEAD {
             Ok(resp.finish())
         } else {
+            // server can send file region directly, if it does not need
+            // to encode response body
+            let file = self.file.try_clone().ok().map(|file| FileBody {
+                file,
+                offset,
+                size: length,
+            });
             let reader = ChunkedReadFile {
                 offset,
                 size: length,
@@ -465,9 +623,13 @@ impl<C: StaticFileConfig> Responder for NamedFile<C> {
                 counter: 0,
             };
             if offset != 0 || length != self.md.len() {
-                return Ok(resp.status(StatusCode::PARTIAL_CONTENT).streaming(reader));
+                resp.status(StatusCode::PARTIAL_CONTENT);
             };
-            Ok(resp.streaming(reader))
+            let mut resp = resp.streaming(reader);
+            if let Some(file) = file {
+                resp.set_file_body(file);
+            }
+            Ok(resp)
         }
     }
 }
@@ -561,12 +723,61 @@ impl Directory {
     }
 }
 
-fn directory_listing<S>(
+/// Is this a hidden file or directory name?
+fn is_hidden(name: &OsStr) -> bool {
+    name.to_string_lossy().starts_with('.')
+}
+
+/// Is this error caused by missing file?
+fn is_not_found(err: &Error) -> bool {
+    err.as_fail()
+        .downcast_ref::<io::Error>()
+        .map_or(false, |err| err.kind() == io::ErrorKind::NotFound)
+}
+
+/// Visible entry of a directory listing
+struct ListingEntry {
+    name: String,
+    url: String,
+    is_dir: bool,
+    size: u64,
+    modified: Option<SystemTime>,
+}
+
+impl ListingEntry {
+    /// Modification time in RFC 3339 format
+    fn modified(&self) -> Option<String> {
+        self.modified
+            .and_then(|mtime| mtime.duration_since(UNIX_EPOCH).ok())
+            .map(|dur| {
+                let ts = time::Timespec::new(dur.as_secs() as i64, 0);
+                time::at_utc(ts).rfc3339().to_string()
+            })
+    }
+}
+
+impl Serialize for ListingEntry {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("ListingEntry", 5)?;
+        s.serialize_field("name", &self.name)?;
+        s.serialize_field("url", &self.url)?;
+        s.serialize_field("is_dir", &self.is_dir)?;
+        s.serialize_field("size", &self.size)?;
+        s.serialize_field("modified", &self.modified())?;
+        s.end()
+    }
+}
+
+/// Collect visible entries of the directory.
+///
+/// Entries are sorted and paginated according to `sort` (`name`, `size` or
+/// `modified`), `order` (`asc` or `desc`), `page` and `per_page` query
+/// parameters. Returns requested page and total number of entries.
+fn listing_entries<S>(
     dir: &Directory, req: &HttpRequest<S>,
-) -> Result<HttpResponse, io::Error> {
-    let index_of = format!("Index of {}", req.path());
-    let mut body = String::new();
+) -> Result<(Vec<ListingEntry>, usize), io::Error> {
     let base = Path::new(req.path());
+    let mut entries = Vec::new();
 
     for entry in dir.path.read_dir()? {
         if dir.is_visible(&entry) {
@@ -575,30 +786,112 @@ fn directory_listing<S>(
                 Ok(p) => base.join(p),
                 Err(_) => continue,
             };
-            // show file url as relative to static path
-            let file_url = utf8_percent_encode(&p.to_string_lossy(), DEFAULT_ENCODE_SET)
-                .to_string();
-            // " -- &quot;  & -- &amp;  ' -- &#x27;  < -- &lt;  > -- &gt;
-            let file_name = escape_html_entity(&entry.file_name().to_string_lossy());
-
-            // if file is a directory, add '/' to the end of the name
-            if let Ok(metadata) = entry.metadata() {
-                if metadata.is_dir() {
-                    let _ = write!(
-                        body,
-                        "<li><a href=\"{}\">{}/</a></li>",
-                        file_url, file_name
-                    );
-                } else {
-                    let _ = write!(
-                        body,
-                        "<li><a href=\"{}\">{}</a></li>",
-                        file_url, file_name
-                    );
-                }
-            } else {
-                continue;
-            }
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+            entries.push(ListingEntry {
+                name: entry.file_name().to_string_lossy().into_owned(),
+                // show file url as relative to static path
+                url: utf8_percent_encode(&p.to_string_lossy(), DEFAULT_ENCODE_SET)
+                    .to_string(),
+                is_dir: metadata.is_dir(),
+                size: if metadata.is_dir() { 0 } else { metadata.len() },
+                modified: metadata.modified().ok(),
+            });
+        }
+    }
+
+    let query = req.query();
+    match query.get("sort").map(|s| s.as_str()) {
+        Some("size") => entries.sort_by(|a, b| a.size.cmp(&b.size)),
+        Some("modified") => entries.sort_by(|a, b| a.modified.cmp(&b.modified)),
+        _ => entries.sort_by(|a, b| a.name.cmp(&b.name)),
+    }
+    if query.get("order").map(|s| s.as_str()) == Some("desc") {
+        entries.reverse();
+    }
+
+    let total = entries.len();
+    let per_page = query
+        .get("per_page")
+        .and_then(|s| s.parse::<usize>().ok())
+        .unwrap_or(0);
+    if per_page > 0 {
+        let page = query
+            .get("page")
+            .and_then(|s| s.parse::<usize>().ok())
+            .unwrap_or(1)
+            .max(1);
+        entries = entries
+            .into_iter()
+            .skip((page - 1).saturating_mul(per_page))
+            .take(per_page)
+            .collect();
+    }
+    Ok((entries, total))
+}
+
+/// Returns true if client prefers `application/json` over `text/html`.
+fn prefers_json<S>(req: &HttpRequest<S>) -> bool {
+    let mut items = match req.get_header::<header::Accept>() {
+        Some(header::Accept(items)) => items,
+        None => return false,
+    };
+    items.sort_by(|a, b| b.quality.cmp(&a.quality));
+
+    for item in items {
+        if item.quality == header::q(0) {
+            continue;
+        }
+        match (item.item.type_(), item.item.subtype()) {
+            (mime::APPLICATION, mime::JSON) => return true,
+            (mime::TEXT, mime::HTML) | (mime::TEXT, mime::STAR)
+            | (mime::STAR, mime::STAR) => return false,
+            _ => (),
+        }
+    }
+    false
+}
+
+fn directory_listing<S>(
+    dir: &Directory, req: &HttpRequest<S>,
+) -> Result<HttpResponse, io::Error> {
+    let (entries, total) = listing_entries(dir, req)?;
+
+    if prefers_json(req) {
+        let mut listing = serde_json::Map::new();
+        listing.insert("path".to_owned(), req.path().into());
+        listing.insert("total".to_owned(), total.into());
+        listing.insert("entries".to_owned(), serde_json::to_value(&entries)?);
+
+        return Ok(HttpResponse::Ok()
+            .content_type("application/json")
+            .header(header::VARY, "Accept")
+            .body(serde_json::Value::Object(listing).to_string()));
+    }
+
+    let index_of = format!("Index of {}", req.path());
+    let mut body = String::new();
+
+    for entry in entries {
+        // " -- &quot;  & -- &amp;  ' -- &#x27;  < -- &lt;  > -- &gt;
+        let file_name = escape_html_entity(&entry.name);
+        let modified = entry.modified().unwrap_or_default();
+
+        // if file is a directory, add '/' to the end of the name
+        if entry.is_dir {
+            let _ = write!(
+                body,
+                "<tr><td><a href=\"{}\">{}/</a></td><td>-</td><td>{}</td></tr>",
+                entry.url, file_name, modified
+            );
+        } else {
+            let _ = write!(
+                body,
+                "<tr><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td></tr>",
+                entry.url, file_name, entry.size, modified
+            );
         }
     }
 
@@ -606,13 +899,16 @@ fn directory_listing<S>(
         "<html>\
          <head><title>{}</title></head>\
          <body><h1>{}</h1>\
-         <ul>\
+         <table>\
+         <tr><th>Name</th><th>Size</th><th>Modified</th></tr>\
          {}\
-         </ul></body>\n</html>",
+         </table></body>\n</html>",
         index_of, index_of, body
     );
+    // representation depends on `Accept` header
     Ok(HttpResponse::Ok()
         .content_type("text/html; charset=utf-8")
+        .header(header::VARY, "Accept")
         .body(html))
 }
 
@@ -634,12 +930,14 @@ fn directory_listing<S>(
 pub struct StaticFiles<S, C = DefaultConfig> {
     directory: PathBuf,
     index: Option<String>,
+    fallback: Option<String>,
     show_index: bool,
     cpu_pool: CpuPool,
     default: Box<RouteHandler<S>>,
     renderer: Box<DirectoryRenderer<S>>,
     _chunk_size: usize,
-    _follow_symlinks: bool,
+    follow_symlinks: bool,
+    hidden_files: bool,
     _cd_map: PhantomData<C>,
 }
 
@@ -689,6 +987,7 @@ impl<S: 'static, C: StaticFileConfig> StaticFiles<S, C> {
         Ok(StaticFiles {
             directory: dir,
             index: None,
+            fallback: None,
             show_index: false,
             cpu_pool: pool,
             default: Box::new(WrapHandler::new(|_: &_| {
@@ -696,13 +995,19 @@ impl<S: 'static, C: StaticFileConfig> StaticFiles<S, C> {
             })),
             renderer: Box::new(directory_listing),
             _chunk_size: 0,
-            _follow_symlinks: false,
+            follow_symlinks: false,
+            hidden_files: false,
             _cd_map: PhantomData,
         })
     }
 
     /// Show files listing for directories.
     ///
+    /// Listing is rendered as HTML, or as JSON if client prefers
+    /// `application/json`. Entries can be sorted and paginated with `sort`
+    /// (`name`, `size` or `modified`), `order` (`asc` or `desc`), `page` and
+    /// `per_page` query parameters.
+    ///
     /// By default show files listing is disabled.
     pub fn show_files_listing(mut self) -> Self {
         self.show_index = true;
@@ -729,12 +1034,62 @@ impl<S: 'static, C: StaticFileConfig> StaticFiles<S, C> {
         self
     }
 
+    /// Set fallback file
+    ///
+    /// Serves specific file for unknown paths without file extension, i.e.
+    /// `index.html` of a single page application with client side routing.
+    /// Missing files with extension are still passed to the default handler.
+    pub fn fallback_file<T: Into<String>>(mut self, file: T) -> StaticFiles<S, C> {
+        self.fallback = Some(file.into());
+        self
+    }
+
+    /// Allow symlinks pointing outside of the base directory.
+    ///
+    /// By default only symlinks resolving to a path inside of the base
+    /// directory are served. Targets with hidden components anywhere in
+    /// their canonical path are rejected unless hidden files are served.
+    pub fn follow_symlinks(mut self) -> StaticFiles<S, C> {
+        self.follow_symlinks = true;
+        self
+    }
+
+    /// Serve hidden files and directories (dotfiles).
+    ///
+    /// By default files whose resolved path has any component starting
+    /// with `.`, i.e. symlinks to `.env` or `.git/config`, are not served.
+    /// Request paths with segments starting with `.` are always rejected.
+    pub fn serve_hidden_files(mut self) -> StaticFiles<S, C> {
+        self.hidden_files = true;
+        self
+    }
+
     /// Sets default handler which is used when no matched file could be found.
     pub fn default_handler<H: Handler<S>>(mut self, handler: H) -> StaticFiles<S, C> {
         self.default = Box::new(WrapHandler::new(handler));
         self
     }
 
+    /// Resolve path relative to the base directory, hidden files and
+    /// symlinks leading outside of the base directory are rejected.
+    fn resolve(&self, relpath: &Path) -> Result<PathBuf, Error> {
+        let path = self.directory.join(relpath).canonicalize()?;
+
+        let hidden = match path.strip_prefix(&self.directory) {
+            Ok(resolved) => resolved.iter().any(is_hidden),
+            Err(_) => {
+                if !self.follow_symlinks {
+                    return Err(StaticFileError::IsOutsideOfDirectory.into());
+                }
+                path.iter().any(is_hidden)
+            }
+        };
+        if hidden && !self.hidden_files {
+            return Err(StaticFileError::IsHidden.into());
+        }
+        Ok(path)
+    }
+
     fn try_handle(
         &self, req: &HttpRequest<S>,
     ) -> Result<AsyncResult<HttpResponse>, Error> {
@@ -742,7 +1097,17 @@ impl<S: 'static, C: StaticFileConfig> StaticFiles<S, C> {
         let relpath = PathBuf::from_param(tail.trim_left_matches('/'))?;
 
         // full filepath
-        let path = self.directory.join(&relpath).canonicalize()?;
+        let path = match self.resolve(&relpath) {
+            Ok(path) => path,
+            Err(err) => match self.fallback {
+                Some(ref fallback)
+                    if relpath.extension().is_none() && is_not_found(&err) =>
+                {
+                    self.resolve(Path::new(fallback))?
+                }
+                _ => return Err(err),
+            },
+        };
 
         if path.is_dir() {
             if let Some(ref redir_index) = self.index {
@@ -888,6 +1253,29 @@ mod tests {
     use http::{header, Method, StatusCode};
     use test::{self, TestRequest};
 
+    /// Temporary directory of a test, removed on drop
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> TempDir {
+            let dir = ::std::env::temp_dir()
+                .join(format!("actix-web-{}-{}", name, ::std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+
+        fn path(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
     #[test]
     fn test_file_extension_to_mime() {
         let m = file_extension_to_mime("jpg");
@@ -1280,6 +1668,44 @@ mod tests {
         assert_eq!(bytes, data);
     }
 
+    #[test]
+    fn test_named_file_large() {
+        let tmp = TempDir::new("fs-large");
+        let dir = tmp.path();
+        let data: Vec<u8> = (0..1_048_576u32).map(|i| (i % 251) as u8).collect();
+        fs::write(dir.join("large.bin"), &data).unwrap();
+
+        let root = dir.to_owned();
+        let mut srv = test::TestServer::with_factory(move || {
+            App::new().handler("test", StaticFiles::new(&root).unwrap())
+        });
+
+        // whole file
+        let request = srv
+            .get()
+            .uri(srv.url("/test/large.bin"))
+            .no_default_headers()
+            .finish()
+            .unwrap();
+        let response = srv.execute(request.send()).unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = srv.execute(response.body().limit(2_097_152)).unwrap();
+        assert_eq!(bytes, Bytes::from(data.clone()));
+
+        // range
+        let request = srv
+            .get()
+            .uri(srv.url("/test/large.bin"))
+            .no_default_headers()
+            .header(header::RANGE, "bytes=1000-500999")
+            .finish()
+            .unwrap();
+        let response = srv.execute(request.send()).unwrap();
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        let bytes = srv.execute(response.body().limit(2_097_152)).unwrap();
+        assert_eq!(bytes, Bytes::from(&data[1000..501_000]));
+    }
+
     #[derive(Default)]
     pub struct OnlyMethodHeadConfig;
     impl StaticFileConfig for OnlyMethodHeadConfig {
@@ -1327,6 +1753,131 @@ mod tests {
         assert_eq!(resp.content_encoding().unwrap().as_str(), "identity");
     }
 
+    #[derive(Default)]
+    pub struct PrecompressedConfig;
+    impl StaticFileConfig for PrecompressedConfig {
+        fn is_use_precompressed() -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_named_file_precompressed() {
+        let tmp = TempDir::new("fs-precompressed");
+        let dir = tmp.path();
+        fs::write(dir.join("app.js"), "var a = 1;").unwrap();
+        fs::write(dir.join("app.js.gz"), "gzip").unwrap();
+        fs::write(dir.join("app.js.br"), "brotli").unwrap();
+        let path = dir.join("app.js");
+
+        let req = TestRequest::with_header(header::ACCEPT_ENCODING, "gzip, br").finish();
+        let file = NamedFile::open_with_config(&path, PrecompressedConfig).unwrap();
+        let resp = file.respond_to(&req).unwrap();
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/javascript"
+        );
+        assert_eq!(resp.headers().get(header::CONTENT_ENCODING).unwrap(), "br");
+        assert_eq!(resp.headers().get(header::CONTENT_LENGTH).unwrap(), "6");
+        assert_eq!(resp.headers().get(header::VARY).unwrap(), "Accept-Encoding");
+        assert_eq!(resp.content_encoding(), Some(ContentEncoding::Identity));
+        let br_etag = resp.headers().get(header::ETAG).unwrap().clone();
+
+        let req =
+            TestRequest::with_header(header::ACCEPT_ENCODING, "br;q=0.5, gzip").finish();
+        let file = NamedFile::open_with_config(&path, PrecompressedConfig).unwrap();
+        let resp = file.respond_to(&req).unwrap();
+        assert_eq!(resp.headers().get(header::CONTENT_ENCODING).unwrap(), "gzip");
+        assert_eq!(resp.headers().get(header::CONTENT_LENGTH).unwrap(), "4");
+        assert_ne!(resp.headers().get(header::ETAG).unwrap(), &br_etag);
+
+        let req = TestRequest::with_header(header::ACCEPT_ENCODING, "deflate").finish();
+        let file = NamedFile::open_with_config(&path, PrecompressedConfig).unwrap();
+        let resp = file.respond_to(&req).unwrap();
+        assert!(resp.headers().get(header::CONTENT_ENCODING).is_none());
+        assert_eq!(resp.headers().get(header::CONTENT_LENGTH).unwrap(), "10");
+        assert_eq!(resp.headers().get(header::VARY).unwrap(), "Accept-Encoding");
+
+        let req = TestRequest::with_header(header::ACCEPT_ENCODING, "br")
+            .header(header::RANGE, "bytes=1-2")
+            .finish();
+        let file = NamedFile::open_with_config(&path, PrecompressedConfig).unwrap();
+        let resp = file.respond_to(&req).unwrap();
+        assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(resp.headers().get(header::CONTENT_ENCODING).unwrap(), "br");
+        assert_eq!(
+            resp.headers().get(header::CONTENT_RANGE).unwrap(),
+            "bytes 1-2/6"
+        );
+
+        let req = TestRequest::with_header(header::ACCEPT_ENCODING, "*").finish();
+        let file = NamedFile::open_with_config(&path, PrecompressedConfig).unwrap();
+        let resp = file.respond_to(&req).unwrap();
+        assert_eq!(resp.headers().get(header::CONTENT_ENCODING).unwrap(), "br");
+
+        let req =
+            TestRequest::with_header(header::ACCEPT_ENCODING, "br;q=0, *;q=0.5").finish();
+        let file = NamedFile::open_with_config(&path, PrecompressedConfig).unwrap();
+        let resp = file.respond_to(&req).unwrap();
+        assert_eq!(resp.headers().get(header::CONTENT_ENCODING).unwrap(), "gzip");
+
+        let req = TestRequest::with_header(header::ACCEPT_ENCODING, "*;q=0").finish();
+        let file = NamedFile::open_with_config(&path, PrecompressedConfig).unwrap();
+        let resp = file.respond_to(&req).unwrap();
+        assert!(resp.headers().get(header::CONTENT_ENCODING).is_none());
+
+        // disabled by default
+        let req = TestRequest::with_header(header::ACCEPT_ENCODING, "br").finish();
+        let resp = NamedFile::open(&path).unwrap().respond_to(&req).unwrap();
+        assert!(resp.headers().get(header::CONTENT_ENCODING).is_none());
+        assert!(resp.headers().get(header::VARY).is_none());
+    }
+
+    #[derive(Default)]
+    pub struct CacheConfig;
+    impl StaticFileConfig for CacheConfig {
+        fn cache_control(_: &Path, typ: &mime::Mime) -> Vec<header::CacheDirective> {
+            if typ.subtype() == mime::HTML {
+                vec![header::CacheDirective::NoCache]
+            } else {
+                vec![
+                    header::CacheDirective::MaxAge(3600),
+                    header::CacheDirective::Extension("immutable".to_owned(), None),
+                ]
+            }
+        }
+    }
+
+    #[test]
+    fn test_named_file_cache_control() {
+        let req = TestRequest::default().finish();
+        let file = NamedFile::open_with_config("Cargo.toml", CacheConfig).unwrap();
+        let resp = file.respond_to(&req).unwrap();
+        assert_eq!(
+            resp.headers().get(header::CACHE_CONTROL).unwrap(),
+            "max-age=3600, immutable"
+        );
+        let exp = resp.headers().get(header::EXPIRES).unwrap().to_str().unwrap();
+        let exp = SystemTime::from(exp.parse::<header::HttpDate>().unwrap());
+        assert!(exp > SystemTime::now() + Duration::from_secs(3500));
+
+        let file = NamedFile::open_with_config("Cargo.toml", CacheConfig)
+            .unwrap()
+            .set_content_type(mime::TEXT_HTML);
+        let resp = file.respond_to(&req).unwrap();
+        assert_eq!(
+            resp.headers().get(header::CACHE_CONTROL).unwrap(),
+            "no-cache"
+        );
+        let exp = resp.headers().get(header::EXPIRES).unwrap().to_str().unwrap();
+        assert_eq!(exp, "Thu, 01 Jan 1970 00:00:00 GMT");
+
+        let file = NamedFile::open("Cargo.toml").unwrap();
+        let resp = file.respond_to(&req).unwrap();
+        assert!(resp.headers().get(header::CACHE_CONTROL).is_none());
+        assert!(resp.headers().get(header::EXPIRES).is_none());
+    }
+
     #[test]
     fn test_named_file_any_method() {
         let req = TestRequest::default().method(Method::POST).finish();
@@ -1364,6 +1915,67 @@ mod tests {
         assert!(format!("{:?}", resp.body()).contains("README.md"));
     }
 
+    #[test]
+    fn test_static_files_listing() {
+        let tmp = TempDir::new("fs-listing");
+        let dir = tmp.path();
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("a.txt"), "aaa").unwrap();
+        fs::write(dir.join("b.txt"), "b").unwrap();
+        fs::write(dir.join("c.txt"), "cc").unwrap();
+        let st = StaticFiles::new(&dir).unwrap().show_files_listing();
+
+        let req = TestRequest::with_uri("/").param("tail", "").finish();
+        let resp = st.handle(&req).respond_to(&req).unwrap();
+        let resp = resp.as_msg();
+        let body = match resp.body() {
+            Body::Binary(ref b) => String::from_utf8(b.as_ref().to_vec()).unwrap(),
+            _ => panic!(),
+        };
+        assert!(body.contains("<td><a href=\"/a.txt\">a.txt</a></td><td>3</td>"));
+        assert!(body.contains("<a href=\"/sub\">sub/</a></td><td>-</td>"));
+        assert_eq!(resp.headers().get(header::VARY).unwrap(), "Accept");
+
+        let req = TestRequest::with_uri("/?sort=size&order=desc&page=1&per_page=2")
+            .header(header::ACCEPT, "text/html;q=0.9, application/json")
+            .param("tail", "")
+            .finish();
+        let resp = st.handle(&req).respond_to(&req).unwrap();
+        let resp = resp.as_msg();
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/json"
+        );
+        assert_eq!(resp.headers().get(header::VARY).unwrap(), "Accept");
+        let listing: serde_json::Value = match resp.body() {
+            Body::Binary(ref b) => serde_json::from_slice(b.as_ref()).unwrap(),
+            _ => panic!(),
+        };
+        assert_eq!(listing["total"], 4);
+        let entries = listing["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["name"], "a.txt");
+        assert_eq!(entries[0]["size"], 3);
+        assert_eq!(entries[0]["is_dir"], false);
+        assert!(entries[0]["modified"].is_string());
+        assert_eq!(entries[1]["name"], "c.txt");
+
+        let req = TestRequest::with_uri("/?page=2&per_page=3")
+            .header(header::ACCEPT, "application/json")
+            .param("tail", "")
+            .finish();
+        let resp = st.handle(&req).respond_to(&req).unwrap();
+        let resp = resp.as_msg();
+        let listing: serde_json::Value = match resp.body() {
+            Body::Binary(ref b) => serde_json::from_slice(b.as_ref()).unwrap(),
+            _ => panic!(),
+        };
+        let entries = listing["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0]["name"], "sub");
+        assert_eq!(entries[0]["is_dir"], true);
+    }
+
     #[test]
     fn test_static_files_bad_directory() {
         let st: Result<StaticFiles<()>, Error> = StaticFiles::new("missing");
@@ -1391,6 +2003,119 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_fallback_file() {
+        let tmp = TempDir::new("fs-fallback");
+        let dir = tmp.path();
+        fs::write(dir.join("index.html"), "<html></html>").unwrap();
+        fs::write(dir.join("app.js"), "var a = 1;").unwrap();
+        let st = StaticFiles::new(&dir).unwrap().fallback_file("index.html");
+
+        let req = TestRequest::with_uri("/about/team")
+            .param("tail", "about/team")
+            .finish();
+        let resp = st.handle(&req).respond_to(&req).unwrap();
+        let resp = resp.as_msg();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/html"
+        );
+
+        let req = TestRequest::with_uri("/app.js").param("tail", "app.js").finish();
+        let resp = st.handle(&req).respond_to(&req).unwrap();
+        let resp = resp.as_msg();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/javascript"
+        );
+
+        let req = TestRequest::with_uri("/missing.js")
+            .param("tail", "missing.js")
+            .finish();
+        let resp = st.handle(&req).respond_to(&req).unwrap();
+        let resp = resp.as_msg();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        // hidden paths are not replaced with fallback
+        fs::write(dir.join(".secret"), "secret").unwrap();
+        let req = TestRequest::with_uri("/.secret")
+            .param("tail", ".secret")
+            .finish();
+        let resp = st.handle(&req).respond_to(&req).unwrap();
+        assert_eq!(resp.as_msg().status(), StatusCode::NOT_FOUND);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hidden_files_and_symlinks() {
+        use std::os::unix::fs::symlink;
+
+        let tmp = TempDir::new("fs-symlinks");
+        let root = tmp.path();
+        let dir = root.join("public");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(".env"), "SECRET=1").unwrap();
+        fs::write(root.join("outside.txt"), "outside").unwrap();
+        symlink(dir.join(".env"), dir.join("env.txt")).unwrap();
+        symlink(root.join("outside.txt"), dir.join("outside.txt")).unwrap();
+
+        let st = StaticFiles::new(&dir).unwrap();
+        let req = TestRequest::with_uri("/.env").param("tail", ".env").finish();
+        let resp = st.handle(&req).respond_to(&req).unwrap();
+        assert_eq!(resp.as_msg().status(), StatusCode::NOT_FOUND);
+
+        let req = TestRequest::with_uri("/env.txt")
+            .param("tail", "env.txt")
+            .finish();
+        let resp = st.handle(&req).respond_to(&req).unwrap();
+        assert_eq!(resp.as_msg().status(), StatusCode::NOT_FOUND);
+
+        let req = TestRequest::with_uri("/outside.txt")
+            .param("tail", "outside.txt")
+            .finish();
+        let resp = st.handle(&req).respond_to(&req).unwrap();
+        assert_eq!(resp.as_msg().status(), StatusCode::NOT_FOUND);
+
+        let st = StaticFiles::new(&dir).unwrap().follow_symlinks();
+        let resp = st.handle(&req).respond_to(&req).unwrap();
+        assert_eq!(resp.as_msg().status(), StatusCode::OK);
+
+        let req = TestRequest::with_uri("/env.txt")
+            .param("tail", "env.txt")
+            .finish();
+        let resp = st.handle(&req).respond_to(&req).unwrap();
+        assert_eq!(resp.as_msg().status(), StatusCode::NOT_FOUND);
+
+        // link to a hidden directory outside of the root
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::write(root.join(".git").join("config"), "[core]").unwrap();
+        symlink(root.join(".git"), dir.join("repo")).unwrap();
+        let req = TestRequest::with_uri("/repo/config")
+            .param("tail", "repo/config")
+            .finish();
+        let resp = st.handle(&req).respond_to(&req).unwrap();
+        assert_eq!(resp.as_msg().status(), StatusCode::NOT_FOUND);
+
+        let st = StaticFiles::new(&dir)
+            .unwrap()
+            .follow_symlinks()
+            .serve_hidden_files();
+        let resp = st.handle(&req).respond_to(&req).unwrap();
+        assert_eq!(resp.as_msg().status(), StatusCode::OK);
+        let req = TestRequest::with_uri("/env.txt")
+            .param("tail", "env.txt")
+            .finish();
+        let resp = st.handle(&req).respond_to(&req).unwrap();
+        assert_eq!(resp.as_msg().status(), StatusCode::OK);
+
+        // dot segments of request path are rejected regardless of policy
+        let req = TestRequest::with_uri("/.env").param("tail", ".env").finish();
+        let resp = st.handle(&req).respond_to(&req).unwrap();
+        assert_eq!(resp.as_msg().status(), StatusCode::NOT_FOUND);
+    }
+
     #[test]
     fn test_redirect_to_index() {
         let st = StaticFiles::new(".").unwrap().index_file("index.html");
@@ -8,7 +8,11 @@ use error::Error;
 use http::StatusCode;
 use httprequest::HttpRequest;
 use httpresponse::HttpResponse;
+#[cfg(feature = "openapi")]
+use openapi::Operation;
+use param::Converters;
 use resource::DefaultResource;
+use router::RouteEntry;
 
 /// Trait defines object that could be registered as route handler
 #[allow(unused_variables)]
@@ -55,6 +59,12 @@ pub trait FromRequest<S>: Sized {
     fn extract(req: &HttpRequest<S>) -> Self::Result {
         Self::from_request(req, &Self::Config::default())
     }
+
+    /// Describe extracted request parts in OpenAPI operation
+    ///
+    /// Default implementation does not describe anything.
+    #[cfg(feature = "openapi")]
+    fn describe_api(_: &mut Operation) {}
 }
 
 /// Combines two different responder types into a single type
@@ -405,6 +415,18 @@ where
     }
 }
 
+/// Type name of `T`, empty if compiler does not provide
+/// `std::any::type_name` (rustc older than 1.38)
+#[cfg(actix_type_name)]
+pub(crate) fn type_name<T: ?Sized>() -> &'static str {
+    ::std::any::type_name::<T>()
+}
+
+#[cfg(not(actix_type_name))]
+pub(crate) fn type_name<T: ?Sized>() -> &'static str {
+    ""
+}
+
 pub(crate) trait RouteHandler<S>: 'static {
     fn handle(&self, &HttpRequest<S>) -> AsyncResult<HttpResponse>;
 
@@ -414,7 +436,18 @@ pub(crate) trait RouteHandler<S>: 'static {
 
     fn default_resource(&mut self, _: DefaultResource<S>) {}
 
-    fn finish(&mut self) {}
+    fn finish(&mut self, _: &Converters) {}
+
+    /// Type name of the handler
+    fn name(&self) -> &'static str {
+        ""
+    }
+
+    /// Add handler routes to the route table
+    fn route_table(&self, mut entry: RouteEntry, table: &mut Vec<RouteEntry>) {
+        entry.handler = self.name();
+        table.push(entry);
+    }
 }
 
 /// Route handler wrapper for Handler
@@ -451,6 +484,10 @@ where
             Err(err) => AsyncResult::err(err.into()),
         }
     }
+
+    fn name(&self) -> &'static str {
+        type_name::<H>()
+    }
 }
 
 /// Async route handler
@@ -1,4 +1,4 @@
-use header::{Encoding, QualityItem};
+use header::{Encoding, QualityItem, ACCEPT_ENCODING};
 
 header! {
     /// `Accept-Encoding` header, defined in
@@ -25,39 +25,35 @@ header! {
     /// * `gzip;q=1.0, identity; q=0.5, *;q=0`
     ///
     /// # Examples
-    /// ```
-    /// use hyper::header::{Headers, AcceptEncoding, Encoding, qitem};
+    /// ```rust
+    /// # extern crate actix_web;
+    /// use actix_web::HttpResponse;
+    /// use actix_web::http::header::{AcceptEncoding, Encoding, qitem};
     ///
-    /// let mut headers = Headers::new();
-    /// headers.set(
+    /// # fn main() {
+    /// let mut builder = HttpResponse::Ok();
+    /// builder.set(
     ///     AcceptEncoding(vec![qitem(Encoding::Chunked)])
     /// );
+    /// # }
     /// ```
-    /// ```
-    /// use hyper::header::{Headers, AcceptEncoding, Encoding, qitem};
-    ///
-    /// let mut headers = Headers::new();
-    /// headers.set(
-    ///     AcceptEncoding(vec![
-    ///         qitem(Encoding::Chunked),
-    ///         qitem(Encoding::Gzip),
-    ///         qitem(Encoding::Deflate),
-    ///     ])
-    /// );
-    /// ```
-    /// ```
-    /// use hyper::header::{Headers, AcceptEncoding, Encoding, QualityItem, q, qitem};
+    /// ```rust
+    /// # extern crate actix_web;
+    /// use actix_web::HttpResponse;
+    /// use actix_web::http::header::{AcceptEncoding, Encoding, q, qitem, QualityItem};
     ///
-    /// let mut headers = Headers::new();
-    /// headers.set(
+    /// # fn main() {
+    /// let mut builder = HttpResponse::Ok();
+    /// builder.set(
     ///     AcceptEncoding(vec![
     ///         qitem(Encoding::Chunked),
     ///         QualityItem::new(Encoding::Gzip, q(600)),
     ///         QualityItem::new(Encoding::EncodingExt("*".to_owned()), q(0)),
     ///     ])
     /// );
+    /// # }
     /// ```
-    (AcceptEncoding, "Accept-Encoding") => (QualityItem<Encoding>)*
+    (AcceptEncoding, ACCEPT_ENCODING) => (QualityItem<Encoding>)*
 
     test_accept_encoding {
         // From the RFC
@@ -8,7 +8,7 @@
 #![cfg_attr(rustfmt, rustfmt_skip)]
 
 pub use self::accept_charset::AcceptCharset;
-//pub use self::accept_encoding::AcceptEncoding;
+pub use self::accept_encoding::AcceptEncoding;
 pub use self::accept_language::AcceptLanguage;
 pub use self::accept::Accept;
 pub use self::allow::Allow;
@@ -329,7 +329,7 @@ macro_rules! header {
 
 
 mod accept_charset;
-//mod accept_encoding;
+mod accept_encoding;
 mod accept_language;
 mod accept;
 mod allow;
@@ -122,6 +122,9 @@ pub enum ContentEncoding {
     /// Gzip algorithm
     #[cfg(feature = "flate2")]
     Gzip,
+    /// Zstandard algorithm
+    #[cfg(feature = "zstd")]
+    Zstd,
     /// Indicates the identity function (i.e. no compression, nor modification)
     Identity,
 }
@@ -146,6 +149,8 @@ impl ContentEncoding {
             ContentEncoding::Gzip => "gzip",
             #[cfg(feature = "flate2")]
             ContentEncoding::Deflate => "deflate",
+            #[cfg(feature = "zstd")]
+            ContentEncoding::Zstd => "zstd",
             ContentEncoding::Identity | ContentEncoding::Auto => "identity",
         }
     }
@@ -156,6 +161,8 @@ impl ContentEncoding {
         match self {
             #[cfg(feature = "brotli")]
             ContentEncoding::Br => 1.1,
+            #[cfg(feature = "zstd")]
+            ContentEncoding::Zstd => 1.05,
             #[cfg(feature = "flate2")]
             ContentEncoding::Gzip => 1.0,
             #[cfg(feature = "flate2")]
@@ -175,6 +182,8 @@ impl<'a> From<&'a str> for ContentEncoding {
             "gzip" => ContentEncoding::Gzip,
             #[cfg(feature = "flate2")]
             "deflate" => ContentEncoding::Deflate,
+            #[cfg(feature = "zstd")]
+            "zstd" => ContentEncoding::Zstd,
             _ => ContentEncoding::Identity,
         }
     }
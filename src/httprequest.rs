@@ -322,6 +322,13 @@ impl<S> HttpRequest<S> {
             payload.set_read_buffer_capacity(cap)
         }
     }
+
+    /// Pass encoded payload to handlers as is
+    pub(crate) fn disable_decompression(&self) {
+        if let Some(payload) = self.request().inner.payload.borrow().as_ref() {
+            payload.disable_decoding()
+        }
+    }
 }
 
 impl<S> Drop for HttpRequest<S> {
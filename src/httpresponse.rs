@@ -12,7 +12,7 @@ use http::{Error as HttpError, HeaderMap, HttpTryFrom, StatusCode, Version};
 use serde::Serialize;
 use serde_json;
 
-use body::Body;
+use body::{Body, FileBody};
 use client::ClientResponse;
 use error::Error;
 use handler::Responder;
@@ -254,12 +254,27 @@ impl HttpResponse {
 
     /// Set a body
     pub fn set_body<B: Into<Body>>(&mut self, body: B) {
-        self.get_mut().body = body.into();
+        let inner = self.get_mut();
+        inner.file = None;
+        inner.body = body.into();
     }
 
     /// Set a body and return previous body value
     pub fn replace_body<B: Into<Body>>(&mut self, body: B) -> Body {
-        mem::replace(&mut self.get_mut().body, body.into())
+        let inner = self.get_mut();
+        inner.file = None;
+        mem::replace(&mut inner.body, body.into())
+    }
+
+    /// Set file region, which can be sent instead of streaming body.
+    ///
+    /// File is dropped as soon as body gets replaced.
+    pub(crate) fn set_file_body(&mut self, file: FileBody) {
+        self.get_mut().file = Some(file);
+    }
+
+    pub(crate) fn take_file_body(&mut self) -> Option<FileBody> {
+        self.get_mut().file.take()
     }
 
     /// Size of response in bytes, excluding HTTP headers
@@ -879,6 +894,7 @@ struct InnerHttpResponse {
     status: StatusCode,
     reason: Option<&'static str>,
     body: Body,
+    file: Option<FileBody>,
     chunked: Option<bool>,
     encoding: Option<ContentEncoding>,
     connection_type: Option<ConnectionType>,
@@ -904,6 +920,7 @@ impl InnerHttpResponse {
         InnerHttpResponse {
             status,
             body,
+            file: None,
             version: None,
             headers: HeaderMap::with_capacity(16),
             reason: None,
@@ -948,6 +965,7 @@ impl InnerHttpResponse {
 
         InnerHttpResponse {
             body,
+            file: None,
             status: parts.status,
             version: parts.version,
             headers: parts.headers,
@@ -1029,6 +1047,7 @@ impl HttpResponsePool {
         let mut p = self.0.borrow_mut();
         if p.len() < 128 {
             inner.headers.clear();
+            inner.file = None;
             inner.version = None;
             inner.chunked = None;
             inner.reason = None;
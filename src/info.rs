@@ -1,3 +1,6 @@
+use std::borrow::Cow;
+use std::net::{IpAddr, SocketAddr};
+
 use http::header::{self, HeaderName};
 use server::Request;
 
@@ -14,37 +17,64 @@ pub struct ConnectionInfo {
     peer: Option<String>,
 }
 
+/// Single element of `Forwarded` header
+#[derive(Default)]
+struct Forwarded {
+    remote: Option<String>,
+    proto: Option<String>,
+    host: Option<String>,
+}
+
 impl ConnectionInfo {
     /// Create *ConnectionInfo* instance for a request.
+    ///
+    /// Forwarded headers are ignored if trusted proxies are configured
+    /// and peer of the request is not one of them.
     #[cfg_attr(feature = "cargo-clippy", allow(cyclomatic_complexity))]
     pub fn update(&mut self, req: &Request) {
+        let settings = req.server_settings();
+        let trusted = settings.is_trusted_proxy(req.peer_addr());
+        // walk forwarded addresses from right to left
+        let walk = settings.has_trusted_proxies();
+        let untrusted = |addr: &str| match parse_node(addr) {
+            Some(ip) => !settings.is_trusted_addr(&ip),
+            None => true,
+        };
+
         let mut host = None;
         let mut scheme = None;
         let mut remote = None;
         let mut peer = None;
 
         // load forwarded header
-        for hdr in req.headers().get_all(header::FORWARDED) {
-            if let Ok(val) = hdr.to_str() {
-                for pair in val.split(';') {
-                    for el in pair.split(',') {
-                        let mut items = el.trim().splitn(2, '=');
-                        if let Some(name) = items.next() {
-                            if let Some(val) = items.next() {
-                                match &name.to_lowercase() as &str {
-                                    "for" => if remote.is_none() {
-                                        remote = Some(val.trim());
-                                    },
-                                    "proto" => if scheme.is_none() {
-                                        scheme = Some(val.trim());
-                                    },
-                                    "host" => if host.is_none() {
-                                        host = Some(val.trim());
-                                    },
-                                    _ => (),
-                                }
-                            }
-                        }
+        if trusted {
+            let mut elements = Vec::new();
+            for hdr in req.headers().get_all(header::FORWARDED) {
+                if let Ok(val) = hdr.to_str() {
+                    elements.extend(split_quoted(val, ',').map(parse_forwarded));
+                }
+            }
+            if walk {
+                let pos = elements
+                    .iter()
+                    .rposition(|el| el.remote.as_ref().map_or(true, |r| untrusted(r)))
+                    .unwrap_or(0);
+                if pos < elements.len() {
+                    let el = elements.swap_remove(pos);
+                    remote = el.remote.map(Cow::Owned);
+                    scheme = el.proto.map(Cow::Owned);
+                    host = el.host.map(Cow::Owned);
+                }
+            } else {
+                for el in elements {
+                    if remote.is_none() {
+                        remote = el.remote.map(Cow::Owned);
+                    }
+                    if scheme.is_none() {
+                        scheme = el.proto.map(Cow::Owned);
+                    }
+                    if host.is_none() {
+                        host = el.host.map(Cow::Owned);
                     }
                 }
             }
@@ -52,40 +82,30 @@ impl ConnectionInfo {
 
         // scheme
         if scheme.is_none() {
-            if let Some(h) = req
-                .headers()
-                .get(HeaderName::from_lowercase(X_FORWARDED_PROTO).unwrap())
-            {
-                if let Ok(h) = h.to_str() {
-                    scheme = h.split(',').next().map(|v| v.trim());
-                }
+            if trusted {
+                scheme = forwarded_value(req, X_FORWARDED_PROTO, walk);
             }
             if scheme.is_none() {
-                scheme = req.uri().scheme_part().map(|a| a.as_str());
+                scheme = req.uri().scheme_part().map(|a| Cow::Borrowed(a.as_str()));
                 if scheme.is_none() && req.server_settings().secure() {
-                    scheme = Some("https")
+                    scheme = Some(Cow::Borrowed("https"))
                 }
             }
         }
 
         // host
         if host.is_none() {
-            if let Some(h) = req
-                .headers()
-                .get(HeaderName::from_lowercase(X_FORWARDED_HOST).unwrap())
-            {
-                if let Ok(h) = h.to_str() {
-                    host = h.split(',').next().map(|v| v.trim());
-                }
+            if trusted {
+                host = forwarded_value(req, X_FORWARDED_HOST, walk);
             }
             if host.is_none() {
                 if let Some(h) = req.headers().get(header::HOST) {
-                    host = h.to_str().ok();
+                    host = h.to_str().ok().map(Cow::Borrowed);
                 }
                 if host.is_none() {
-                    host = req.uri().authority_part().map(|a| a.as_str());
+                    host = req.uri().authority_part().map(|a| Cow::Borrowed(a.as_str()));
                     if host.is_none() {
-                        host = Some(req.server_settings().host());
+                        host = Some(Cow::Borrowed(req.server_settings().host()));
                     }
                 }
             }
@@ -93,13 +113,27 @@ impl ConnectionInfo {
 
         // remote addr
         if remote.is_none() {
-            if let Some(h) = req
-                .headers()
-                .get(HeaderName::from_lowercase(X_FORWARDED_FOR).unwrap())
-            {
-                if let Ok(h) = h.to_str() {
-                    remote = h.split(',').next().map(|v| v.trim());
+            if trusted {
+                let mut addrs = Vec::new();
+                for h in req
+                    .headers()
+                    .get_all(HeaderName::from_lowercase(X_FORWARDED_FOR).unwrap())
+                {
+                    if let Ok(h) = h.to_str() {
+                        addrs.extend(
+                            h.split(',').map(|v| v.trim()).filter(|v| !v.is_empty()),
+                        );
+                    }
                 }
+                remote = if walk {
+                    addrs
+                        .iter()
+                        .rev()
+                        .find(|addr| untrusted(addr))
+                        .or_else(|| addrs.first())
+                } else {
+                    addrs.first()
+                }.map(|addr| Cow::Borrowed(*addr));
             }
             if remote.is_none() {
                 // get peeraddr from socketaddr
@@ -107,9 +141,9 @@ impl ConnectionInfo {
             }
         }
 
-        self.scheme = scheme.unwrap_or("http").to_owned();
-        self.host = host.unwrap_or("localhost").to_owned();
-        self.remote = remote.map(|s| s.to_owned());
+        self.scheme = scheme.unwrap_or(Cow::Borrowed("http")).into_owned();
+        self.host = host.unwrap_or(Cow::Borrowed("localhost")).into_owned();
+        self.remote = remote.map(|s| s.into_owned());
         self.peer = peer;
     }
 
@@ -145,6 +179,10 @@ impl ConnectionInfo {
     /// - Forwarded
     /// - X-Forwarded-For
     /// - peer name of opened socket
+    ///
+    /// If trusted proxies are configured, forwarded addresses are walked
+    /// from right to left and first address which does not belong to
+    /// trusted proxies is used.
     #[inline]
     pub fn remote(&self) -> Option<&str> {
         if let Some(ref r) = self.remote {
@@ -157,11 +195,135 @@ impl ConnectionInfo {
     }
 }
 
+/// Value of `X-Forwarded-Host` or `X-Forwarded-Proto` header.
+///
+/// Last value is set by the nearest proxy, so it is used if proxies
+/// are walked, first value is used otherwise.
+fn forwarded_value<'a>(
+    req: &'a Request, name: &[u8], last: bool,
+) -> Option<Cow<'a, str>> {
+    let mut hdr = req
+        .headers()
+        .get_all(HeaderName::from_lowercase(name).unwrap())
+        .iter()
+        .filter_map(|h| h.to_str().ok())
+        .flat_map(|h| h.split(','))
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty());
+    if last {
+        hdr.last().map(Cow::Borrowed)
+    } else {
+        hdr.next().map(Cow::Borrowed)
+    }
+}
+
+/// Parse element of `Forwarded` header, i.e. `for=192.0.2.60;proto=http`
+fn parse_forwarded(element: &str) -> Forwarded {
+    let mut el = Forwarded::default();
+    for pair in split_quoted(element, ';') {
+        let mut items = pair.trim().splitn(2, '=');
+        if let (Some(name), Some(val)) = (items.next(), items.next()) {
+            let val = unquote(val.trim());
+            match &name.trim().to_lowercase() as &str {
+                "for" => if el.remote.is_none() {
+                    el.remote = Some(normalize_node(val));
+                },
+                "proto" => if el.proto.is_none() {
+                    el.proto = Some(val);
+                },
+                "host" => if el.host.is_none() {
+                    el.host = Some(val);
+                },
+                _ => (),
+            }
+        }
+    }
+    el
+}
+
+/// Split string by separator which is not a part of quoted string
+fn split_quoted(s: &str, sep: char) -> impl Iterator<Item = &str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+    for (idx, ch) in s.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if quoted && ch == '\\' {
+            escaped = true;
+        } else if ch == '"' {
+            quoted = !quoted;
+        } else if ch == sep && !quoted {
+            parts.push(&s[start..idx]);
+            start = idx + 1;
+        }
+    }
+    parts.push(&s[start..]);
+    parts.into_iter().filter(|p| !p.trim().is_empty())
+}
+
+/// Remove quotes and escapes of quoted string
+fn unquote(s: &str) -> String {
+    if s.len() < 2 || !s.starts_with('"') || !s.ends_with('"') {
+        return s.to_owned();
+    }
+    let mut val = String::with_capacity(s.len() - 2);
+    let mut escaped = false;
+    for ch in s[1..s.len() - 1].chars() {
+        if !escaped && ch == '\\' {
+            escaped = true;
+        } else {
+            escaped = false;
+            val.push(ch);
+        }
+    }
+    val
+}
+
+/// Ipv6 node without port is used without brackets, i.e. `[::1]` is `::1`
+fn normalize_node(node: String) -> String {
+    if node.starts_with('[') && node.ends_with(']') {
+        node[1..node.len() - 1].to_owned()
+    } else {
+        node
+    }
+}
+
+/// Ip address of forwarded node, `192.0.2.60`, `[2001:db8::17]:4711` etc.
+///
+/// Obfuscated and unknown nodes do not have address.
+fn parse_node(node: &str) -> Option<IpAddr> {
+    if let Ok(ip) = node.parse() {
+        Some(ip)
+    } else if let Ok(addr) = node.parse::<SocketAddr>() {
+        Some(addr.ip())
+    } else if node.starts_with('[') && node.ends_with(']') {
+        node[1..node.len() - 1].parse().ok()
+    } else {
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use server::ServerSettings;
     use test::TestRequest;
 
+    fn trusted_request(req: TestRequest<()>, peer: &str) -> Request {
+        let mut req = req.request();
+        {
+            let inner = req.inner_mut();
+            inner.addr = Some(peer.parse().unwrap());
+            inner.settings = ServerSettings::default().with_trusted_proxies(Some(vec![
+                "10.0.0.0/8".parse().unwrap(),
+                "2001:db8::/32".parse().unwrap(),
+            ]));
+        }
+        req
+    }
+
     #[test]
     fn test_forwarded() {
         let req = TestRequest::default().request();
@@ -214,4 +376,85 @@ mod tests {
         info.update(&req);
         assert_eq!(info.scheme(), "https");
     }
+
+    #[test]
+    fn test_forwarded_quoted() {
+        let req = TestRequest::default()
+            .header(
+                header::FORWARDED,
+                "for=\"[2001:db8:cafe::17]:4711\";proto=https, for=192.0.2.43",
+            ).request();
+        let mut info = ConnectionInfo::default();
+        info.update(&req);
+        assert_eq!(info.remote(), Some("[2001:db8:cafe::17]:4711"));
+        assert_eq!(info.scheme(), "https");
+
+        let req = TestRequest::default()
+            .header(header::FORWARDED, "For=\"[2001:db8::1]\"; host=\"a,b;c\"")
+            .request();
+        let mut info = ConnectionInfo::default();
+        info.update(&req);
+        assert_eq!(info.remote(), Some("2001:db8::1"));
+        assert_eq!(info.host(), "a,b;c");
+    }
+
+    #[test]
+    fn test_trusted_proxies() {
+        // untrusted peer can not spoof forwarded headers
+        let req = trusted_request(
+            TestRequest::with_header(header::FORWARDED, "for=192.0.2.60;proto=https")
+                .header(X_FORWARDED_FOR, "192.0.2.61")
+                .header(X_FORWARDED_HOST, "rust-lang.org")
+                .header(header::HOST, "localhost"),
+            "192.0.2.1:8080",
+        );
+        let mut info = ConnectionInfo::default();
+        info.update(&req);
+        assert_eq!(info.remote(), Some("192.0.2.1:8080"));
+        assert_eq!(info.scheme(), "http");
+        assert_eq!(info.host(), "localhost");
+
+        // client supplied addresses are skipped
+        let req = trusted_request(
+            TestRequest::with_header(X_FORWARDED_FOR, "1.1.1.1, 192.0.2.60")
+                .header(X_FORWARDED_FOR, "10.0.0.2")
+                .header(X_FORWARDED_PROTO, "http, https"),
+            "10.0.0.1:8080",
+        );
+        let mut info = ConnectionInfo::default();
+        info.update(&req);
+        assert_eq!(info.remote(), Some("192.0.2.60"));
+        assert_eq!(info.scheme(), "https");
+
+        // all addresses are trusted
+        let req = trusted_request(
+            TestRequest::with_header(X_FORWARDED_FOR, "10.0.0.3, 10.0.0.2"),
+            "10.0.0.1:8080",
+        );
+        let mut info = ConnectionInfo::default();
+        info.update(&req);
+        assert_eq!(info.remote(), Some("10.0.0.3"));
+
+        let req = trusted_request(
+            TestRequest::with_header(
+                header::FORWARDED,
+                "for=1.1.1.1;proto=http, for=192.0.2.60;proto=https;host=rust-lang.org",
+            ).header(header::FORWARDED, "for=\"[2001:db8::2]:80\";proto=http"),
+            "[2001:db8::1]:8080",
+        );
+        let mut info = ConnectionInfo::default();
+        info.update(&req);
+        assert_eq!(info.remote(), Some("192.0.2.60"));
+        assert_eq!(info.scheme(), "https");
+        assert_eq!(info.host(), "rust-lang.org");
+
+        // obfuscated identifier is not trusted
+        let req = trusted_request(
+            TestRequest::with_header(header::FORWARDED, "for=1.1.1.1, for=_hidden"),
+            "10.0.0.1:8080",
+        );
+        let mut info = ConnectionInfo::default();
+        info.update(&req);
+        assert_eq!(info.remote(), Some("_hidden"));
+    }
 }
@@ -16,6 +16,8 @@ use http::StatusCode;
 use httpmessage::HttpMessage;
 use httprequest::HttpRequest;
 use httpresponse::HttpResponse;
+#[cfg(feature = "openapi")]
+use openapi::{schema_of, Operation};
 
 /// Json helper
 ///
@@ -149,6 +151,11 @@ where
                 .map(Json),
         )
     }
+
+    #[cfg(feature = "openapi")]
+    fn describe_api(op: &mut Operation) {
+        op.request_body("application/json", schema_of::<T>());
+    }
 }
 
 /// Json extractor configuration
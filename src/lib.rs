@@ -76,6 +76,10 @@
 //!   `c` compiler
 //! * `flate2-rust` - experimental rust based implementation for
 //!   `gzip`, `deflate` compression.
+//! * `zstd` - enables `zstd` compression support, requires `c`
+//!   compiler
+//! * `openapi` - enables OpenAPI document generation from application
+//!   routes, see `openapi` module
 //!
 #![cfg_attr(actix_nightly, feature(
     specialization, // for impl ErrorResponse for std::error::Error
@@ -110,6 +114,8 @@ extern crate http as modhttp;
 extern crate httparse;
 extern crate language_tags;
 extern crate lazycell;
+#[cfg(target_os = "linux")]
+extern crate libc;
 extern crate mime;
 extern crate mime_guess;
 extern crate mio;
@@ -132,11 +138,14 @@ extern crate brotli2;
 extern crate encoding;
 #[cfg(feature = "flate2")]
 extern crate flate2;
+#[cfg(feature = "zstd")]
+extern crate zstd;
 extern crate h2 as http2;
 extern crate num_cpus;
 extern crate serde_urlencoded;
 #[macro_use]
 extern crate percent_encoding;
+#[cfg_attr(feature = "openapi", macro_use)]
 extern crate serde_json;
 extern crate smallvec;
 #[macro_use]
@@ -164,6 +173,8 @@ extern crate tokio_rustls;
 extern crate webpki;
 #[cfg(feature = "rust-tls")]
 extern crate webpki_roots;
+#[cfg(feature = "rust-tls")]
+extern crate untrusted;
 
 mod application;
 mod body;
@@ -195,6 +206,8 @@ pub mod error;
 pub mod fs;
 pub mod middleware;
 pub mod multipart;
+#[cfg(feature = "openapi")]
+pub mod openapi;
 pub mod pred;
 pub mod server;
 pub mod test;
@@ -261,12 +274,14 @@ pub mod dev {
     pub use httpresponse::HttpResponseBuilder;
     pub use info::ConnectionInfo;
     pub use json::{JsonBody, JsonConfig};
-    pub use param::{FromParam, Params};
+    pub use param::{FromParam, ParamConverter, Params};
     pub use payload::{Payload, PayloadBuffer};
     pub use pipeline::Pipeline;
     pub use resource::Resource;
     pub use route::Route;
-    pub use router::{ResourceDef, ResourceInfo, ResourceType, Router};
+    pub use router::{
+        ResourceDef, ResourceInfo, ResourceType, RouteEntry, RouteTable, Router,
+    };
 }
 
 pub mod http {
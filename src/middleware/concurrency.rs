@@ -0,0 +1,386 @@
+//! Concurrency limiting middleware
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::sync::oneshot;
+use futures::{Async, Future, Poll};
+use http::StatusCode;
+use parking_lot::Mutex;
+use tokio_timer::Delay;
+
+use error::{Error, Result};
+use httprequest::HttpRequest;
+use httpresponse::HttpResponse;
+use middleware::{Finished, Middleware, Started};
+
+/// `Middleware` for limiting number of requests processed concurrently.
+///
+/// Requests over the limit wait in a bounded queue until one of in-flight
+/// requests is finished. Requests which could not be queued or wait
+/// longer than queue timeout are rejected with `503 Service Unavailable`
+/// response. By default queue is disabled.
+///
+/// Application factory runs for each worker thread, limiter state is shared
+/// between clones of the middleware, so limiter should be created outside
+/// of the factory to enforce the limit across all workers. Clone of the
+/// limiter could be used to inspect its state with `stats()` method.
+///
+/// ```rust
+/// # extern crate actix_web;
+/// use actix_web::middleware::ConcurrencyLimit;
+/// use actix_web::{server, App, HttpResponse};
+/// use std::time::Duration;
+///
+/// fn main() {
+///     let limit = ConcurrencyLimit::new(10).queue(100, Duration::from_secs(5));
+///
+///     server::new(move || {
+///         App::new()
+///             .scope("/reports", |scope| {
+///                 scope
+///                     .middleware(limit.clone())
+///                     .resource("", |r| r.f(|_| HttpResponse::Ok()))
+///             })
+///             .resource("/", |r| r.f(|_| HttpResponse::Ok()))
+///     });
+/// }
+/// ```
+#[derive(Clone)]
+pub struct ConcurrencyLimit {
+    inner: Arc<Limiter>,
+    queue_size: usize,
+    queue_timeout: Duration,
+}
+
+/// State of concurrency limiter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConcurrencyStats {
+    /// Number of requests in processing
+    pub in_flight: usize,
+    /// Number of requests waiting in queue
+    pub queued: usize,
+    /// Total number of requests rejected because queue was full
+    pub rejected: u64,
+    /// Total number of requests rejected after waiting in queue
+    pub timed_out: u64,
+}
+
+struct Limiter {
+    max: usize,
+    state: Mutex<State>,
+}
+
+struct State {
+    in_flight: usize,
+    queue: VecDeque<oneshot::Sender<()>>,
+    rejected: u64,
+    timed_out: u64,
+}
+
+impl Limiter {
+    /// Pass permit to first waiting request or release it
+    fn release(&self) {
+        let mut state = self.state.lock();
+        while let Some(tx) = state.queue.pop_front() {
+            if tx.send(()).is_ok() {
+                return;
+            }
+        }
+        state.in_flight -= 1;
+    }
+}
+
+/// Permit for in-flight request, released on drop
+struct Permit(Arc<Limiter>);
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.0.release();
+    }
+}
+
+/// Permits held by the request
+struct Permits(Vec<Permit>);
+
+impl ConcurrencyLimit {
+    /// Create limiter with maximum number of in-flight requests
+    pub fn new(max: usize) -> ConcurrencyLimit {
+        assert!(max > 0, "Concurrency limit must be positive");
+        ConcurrencyLimit {
+            inner: Arc::new(Limiter {
+                max,
+                state: Mutex::new(State {
+                    in_flight: 0,
+                    queue: VecDeque::new(),
+                    rejected: 0,
+                    timed_out: 0,
+                }),
+            }),
+            queue_size: 0,
+            queue_timeout: Duration::from_secs(0),
+        }
+    }
+
+    /// Queue up to `size` requests over the limit, each of them waits
+    /// for at most `timeout`.
+    pub fn queue(mut self, size: usize, timeout: Duration) -> Self {
+        self.queue_size = size;
+        self.queue_timeout = timeout;
+        self
+    }
+
+    /// Current state of the limiter
+    pub fn stats(&self) -> ConcurrencyStats {
+        let state = self.inner.state.lock();
+        ConcurrencyStats {
+            in_flight: state.in_flight,
+            queued: state.queue.iter().filter(|tx| !tx.is_canceled()).count(),
+            rejected: state.rejected,
+            timed_out: state.timed_out,
+        }
+    }
+}
+
+fn add_permit<S>(req: &HttpRequest<S>, permit: Permit) {
+    let mut ext = req.extensions_mut();
+    if let Some(permits) = ext.get_mut::<Permits>() {
+        permits.0.push(permit);
+        return;
+    }
+    ext.insert(Permits(vec![permit]));
+}
+
+impl<S: 'static> Middleware<S> for ConcurrencyLimit {
+    fn start(&self, req: &HttpRequest<S>) -> Result<Started> {
+        let mut state = self.inner.state.lock();
+        if state.in_flight < self.inner.max {
+            state.in_flight += 1;
+            drop(state);
+            add_permit(req, Permit(Arc::clone(&self.inner)));
+            return Ok(Started::Done);
+        }
+
+        state.queue.retain(|tx| !tx.is_canceled());
+        if state.queue.len() >= self.queue_size {
+            state.rejected += 1;
+            return Ok(Started::Response(
+                req.build_response(StatusCode::SERVICE_UNAVAILABLE).finish(),
+            ));
+        }
+
+        let (tx, rx) = oneshot::channel();
+        state.queue.push_back(tx);
+        Ok(Started::Future(Box::new(Waiting {
+            rx,
+            delay: Delay::new(Instant::now() + self.queue_timeout),
+            limiter: Arc::clone(&self.inner),
+            req: req.clone(),
+            done: false,
+        })))
+    }
+
+    fn finish(&self, req: &HttpRequest<S>, _: &HttpResponse) -> Finished {
+        let permit = req.extensions_mut().get_mut::<Permits>().and_then(|permits| {
+            let pos = permits.0.iter().rposition(|p| Arc::ptr_eq(&p.0, &self.inner));
+            pos.map(|pos| permits.0.remove(pos))
+        });
+        drop(permit);
+        Finished::Done
+    }
+}
+
+/// Request waiting in queue
+struct Waiting<S> {
+    rx: oneshot::Receiver<()>,
+    delay: Delay,
+    limiter: Arc<Limiter>,
+    req: HttpRequest<S>,
+    done: bool,
+}
+
+impl<S> Waiting<S> {
+    fn acquired(&mut self) -> Poll<Option<HttpResponse>, Error> {
+        self.done = true;
+        add_permit(&self.req, Permit(Arc::clone(&self.limiter)));
+        Ok(Async::Ready(None))
+    }
+
+    fn rejected(&mut self) -> Poll<Option<HttpResponse>, Error> {
+        self.done = true;
+        self.limiter.state.lock().timed_out += 1;
+        Ok(Async::Ready(Some(
+            self.req
+                .build_response(StatusCode::SERVICE_UNAVAILABLE)
+                .finish(),
+        )))
+    }
+}
+
+impl<S> Future for Waiting<S> {
+    type Item = Option<HttpResponse>;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<HttpResponse>, Error> {
+        match self.rx.poll() {
+            Ok(Async::Ready(_)) => return self.acquired(),
+            Ok(Async::NotReady) => (),
+            Err(_) => return self.rejected(),
+        }
+        match self.delay.poll() {
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Ok(Async::Ready(_)) | Err(_) => {
+                // permit could be sent before receiver is closed
+                self.rx.close();
+                match self.rx.try_recv() {
+                    Ok(Some(_)) => self.acquired(),
+                    _ => self.rejected(),
+                }
+            }
+        }
+    }
+}
+
+impl<S> Drop for Waiting<S> {
+    fn drop(&mut self) {
+        if !self.done {
+            self.rx.close();
+            if let Ok(Some(_)) = self.rx.try_recv() {
+                self.limiter.release();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test::TestRequest;
+    use tokio::runtime::current_thread::Runtime;
+
+    fn start(limit: &ConcurrencyLimit, req: &HttpRequest) -> Started {
+        Middleware::start(limit, req).unwrap()
+    }
+
+    fn finish(limit: &ConcurrencyLimit, req: &HttpRequest) {
+        Middleware::finish(limit, req, &HttpResponse::Ok().finish());
+    }
+
+    #[test]
+    fn test_concurrency_limit() {
+        let limit = ConcurrencyLimit::new(1);
+
+        let req1 = TestRequest::default().finish();
+        match start(&limit, &req1) {
+            Started::Done => (),
+            _ => panic!(),
+        }
+        assert_eq!(limit.stats().in_flight, 1);
+
+        let req2 = TestRequest::default().finish();
+        match start(&limit, &req2) {
+            Started::Response(resp) => {
+                assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE)
+            }
+            _ => panic!(),
+        }
+        assert_eq!(limit.stats().rejected, 1);
+
+        finish(&limit, &req1);
+        assert_eq!(limit.stats().in_flight, 0);
+        match start(&limit, &req2) {
+            Started::Done => (),
+            _ => panic!(),
+        }
+
+        // permit is released with request
+        drop(req2);
+        assert_eq!(limit.stats().in_flight, 0);
+    }
+
+    #[test]
+    fn test_concurrency_queue() {
+        let mut rt = Runtime::new().unwrap();
+        let limit = ConcurrencyLimit::new(1).queue(1, Duration::from_millis(50));
+
+        let req1 = TestRequest::default().finish();
+        let _ = start(&limit, &req1);
+
+        let req2 = TestRequest::default().finish();
+        let fut = match start(&limit, &req2) {
+            Started::Future(fut) => fut,
+            _ => panic!(),
+        };
+        assert_eq!(limit.stats().queued, 1);
+
+        // queue is full
+        let req3 = TestRequest::default().finish();
+        match start(&limit, &req3) {
+            Started::Response(_) => (),
+            _ => panic!(),
+        }
+
+        // permit is passed to queued request
+        finish(&limit, &req1);
+        assert!(rt.block_on(fut).unwrap().is_none());
+        assert_eq!(
+            limit.stats(),
+            ConcurrencyStats {
+                in_flight: 1,
+                queued: 0,
+                rejected: 1,
+                timed_out: 0,
+            }
+        );
+
+        // queued request times out
+        let fut = match start(&limit, &req3) {
+            Started::Future(fut) => fut,
+            _ => panic!(),
+        };
+        let resp = rt.block_on(fut).unwrap().unwrap();
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(limit.stats().timed_out, 1);
+        assert_eq!(limit.stats().queued, 0);
+
+        finish(&limit, &req2);
+        assert_eq!(limit.stats().in_flight, 0);
+    }
+
+    #[test]
+    fn test_dropped_waiter() {
+        let limit = ConcurrencyLimit::new(1).queue(1, Duration::from_secs(10));
+
+        let req1 = TestRequest::default().finish();
+        let _ = start(&limit, &req1);
+        let req2 = TestRequest::default().finish();
+        let fut = match start(&limit, &req2) {
+            Started::Future(fut) => fut,
+            _ => panic!(),
+        };
+
+        // permit is passed to waiter which is gone
+        finish(&limit, &req1);
+        assert_eq!(limit.stats().in_flight, 1);
+        drop(fut);
+        assert_eq!(limit.stats().in_flight, 0);
+    }
+
+    #[test]
+    fn test_nested_limits() {
+        let outer = ConcurrencyLimit::new(2);
+        let inner = ConcurrencyLimit::new(1);
+
+        let req = TestRequest::default().finish();
+        let _ = start(&outer, &req);
+        let _ = start(&inner, &req);
+        assert_eq!(outer.stats().in_flight, 1);
+        assert_eq!(inner.stats().in_flight, 1);
+
+        finish(&inner, &req);
+        assert_eq!(outer.stats().in_flight, 1);
+        assert_eq!(inner.stats().in_flight, 0);
+        finish(&outer, &req);
+        assert_eq!(outer.stats().in_flight, 0);
+    }
+}
@@ -2,15 +2,23 @@
 use std::collections::HashSet;
 use std::env;
 use std::fmt::{self, Display, Formatter};
+use std::rc::Rc;
 
 use regex::Regex;
+use serde::Serialize;
+use serde_json::{self, Value};
 use time;
 
 use error::Result;
+use extensions::Extensions;
+use http::header::{self, HeaderValue};
 use httpmessage::HttpMessage;
 use httprequest::HttpRequest;
 use httpresponse::HttpResponse;
+use middleware::request_id::RequestId;
+use middleware::timeout::TimedOut;
 use middleware::{Finished, Middleware, Started};
+use router::MatchedResource;
 
 /// `Middleware` for logging request and response info to the terminal.
 ///
@@ -63,15 +71,123 @@ use middleware::{Finished, Middleware, Started};
 ///
 /// `%D`  Time taken to serve the request, in milliseconds
 ///
+/// `%L`  Request identifier assigned by
+/// [`RequestIdService`](request_id/struct.RequestIdService.html) middleware
+///
+/// `%X`  `timeout` if request processing timed out, otherwise `-`
+/// (see [`Timeout`](struct.Timeout.html) middleware)
+///
+/// `%U`  URL path of the request
+///
+/// `%q`  Query string prepended with `?`, or empty string
+///
+/// `%R`  Path pattern of the matched resource, including scope prefixes
+///
+/// `%H`  Request protocol
+///
+/// `%h`  IP-address of the peer, forwarded headers are ignored
+///
+/// `%I`  Size of request body in bytes, from `Content-Length` header
+///
 /// `%{FOO}i`  request.headers['FOO']
 ///
 /// `%{FOO}o`  response.headers['FOO']
 ///
 /// `%{FOO}e`  os.environ['FOO']
 ///
+/// `%{FOO}xi`  Value returned by custom function registered with
+/// `Logger::custom_token("FOO", ...)`
+///
+/// ```rust
+/// # extern crate actix_web;
+/// use actix_web::middleware::Logger;
+/// use actix_web::{App, HttpMessage};
+///
+/// fn main() {
+///     let app = App::new()
+///         .middleware(
+///             Logger::new("%h %U%q %s %{ctype}xi")
+///                 .custom_token("ctype", |req, _| {
+///                     req.content_type().to_owned()
+///                 })
+///                 .exclude_regex("^/static/"),
+///         )
+///         .finish();
+/// }
+/// ```
+///
+/// ## JSON format
+///
+/// `Logger::json()` creates middleware which logs one JSON object per request.
+/// Object contains `time`, `method`, `path`, `route`, `status`, `size`,
+/// `duration_ms` and `peer` fields by default, fields could be added with
+/// `field()` and `extension()` methods or removed with `remove_field()`.
+///
+/// ```rust
+/// # extern crate actix_web;
+/// use actix_web::middleware::{LogField, Logger};
+/// use actix_web::App;
+///
+/// fn main() {
+///     let app = App::new()
+///         .middleware(
+///             Logger::json()
+///                 .field("agent", LogField::RequestHeader("user-agent".to_owned()))
+///                 .remove_field("peer")
+///                 .sink(|line| println!("{}", line)),
+///         )
+///         .finish();
+/// }
+/// ```
 pub struct Logger {
     format: Format,
+    json: Option<Vec<(String, JsonField)>>,
     exclude: HashSet<String>,
+    exclude_regex: Vec<Regex>,
+    sink: Option<Box<Fn(&str)>>,
+}
+
+/// Function for custom format token
+type TokenFn = Fn(&HttpRequest, &HttpResponse) -> String;
+
+/// Field of the JSON access log record
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogField {
+    /// Time when the request was started to process, RFC 3339
+    Time,
+    /// Request method
+    Method,
+    /// Request path
+    Path,
+    /// Query string, `null` if it is empty
+    Query,
+    /// HTTP version
+    Version,
+    /// Name of the matched resource, `null` if resource has no name
+    Route,
+    /// Path pattern of the matched resource, including scope prefixes
+    Pattern,
+    /// Response status code
+    Status,
+    /// Size of response in bytes, including HTTP headers
+    Size,
+    /// Time taken to serve the request, in milliseconds
+    Duration,
+    /// Remote IP-address, see `ConnectionInfo::remote()`
+    Peer,
+    /// Request identifier, see `RequestIdService`
+    RequestId,
+    /// Request processing timed out, see `Timeout`
+    TimedOut,
+    /// Request header value
+    RequestHeader(String),
+    /// Response header value
+    ResponseHeader(String),
+}
+
+enum JsonField {
+    Field(LogField),
+    Extension(Box<Fn(&Extensions) -> Option<Value>>),
 }
 
 impl Logger {
@@ -79,15 +195,114 @@ impl Logger {
     pub fn new(format: &str) -> Logger {
         Logger {
             format: Format::new(format),
+            json: None,
             exclude: HashSet::new(),
+            exclude_regex: Vec::new(),
+            sink: None,
         }
     }
 
+    /// Create `Logger` middleware with JSON format and default fields.
+    pub fn json() -> Logger {
+        let fields = vec![
+            ("time", LogField::Time),
+            ("method", LogField::Method),
+            ("path", LogField::Path),
+            ("route", LogField::Route),
+            ("status", LogField::Status),
+            ("size", LogField::Size),
+            ("duration_ms", LogField::Duration),
+            ("peer", LogField::Peer),
+        ];
+        Logger {
+            format: Format::default(),
+            json: Some(
+                fields
+                    .into_iter()
+                    .map(|(name, field)| (name.to_owned(), JsonField::Field(field)))
+                    .collect(),
+            ),
+            exclude: HashSet::new(),
+            exclude_regex: Vec::new(),
+            sink: None,
+        }
+    }
+
+    /// Add field to JSON record, field with the same name is replaced.
+    ///
+    /// This method has no effect for text format.
+    pub fn field(self, name: &str, field: LogField) -> Self {
+        self.json_field(name, JsonField::Field(field))
+    }
+
+    /// Add value of request extension of type `T` to JSON record,
+    /// `null` is logged if request has no such extension.
+    ///
+    /// This method has no effect for text format.
+    pub fn extension<T: Serialize + 'static>(self, name: &str) -> Self {
+        self.json_field(
+            name,
+            JsonField::Extension(Box::new(|ext: &Extensions| {
+                ext.get::<T>().and_then(|val| serde_json::to_value(val).ok())
+            })),
+        )
+    }
+
+    /// Remove field from JSON record.
+    pub fn remove_field(mut self, name: &str) -> Self {
+        if let Some(ref mut fields) = self.json {
+            fields.retain(|f| f.0 != name);
+        }
+        self
+    }
+
+    fn json_field(mut self, name: &str, field: JsonField) -> Self {
+        if let Some(ref mut fields) = self.json {
+            if let Some(f) = fields.iter_mut().find(|f| f.0 == name) {
+                f.1 = field;
+                return self;
+            }
+            fields.push((name.to_owned(), field));
+        }
+        self
+    }
+
+    /// Write log records with the function instead of `log` crate.
+    pub fn sink<F: Fn(&str) + 'static>(mut self, f: F) -> Self {
+        self.sink = Some(Box::new(f));
+        self
+    }
+
     /// Ignore and do not log access info for specified path.
     pub fn exclude<T: Into<String>>(mut self, path: T) -> Self {
         self.exclude.insert(path.into());
         self
     }
+
+    /// Ignore and do not log access info for paths matching regex.
+    ///
+    /// Panics if regex is not valid.
+    pub fn exclude_regex(mut self, re: &str) -> Self {
+        self.exclude_regex
+            .push(Regex::new(re).expect("Invalid exclude regex"));
+        self
+    }
+
+    /// Register function for `%{name}xi` format token.
+    pub fn custom_token<F>(mut self, name: &str, f: F) -> Self
+    where
+        F: Fn(&HttpRequest, &HttpResponse) -> String + 'static,
+    {
+        let f: Rc<TokenFn> = Rc::new(f);
+        for unit in &mut self.format.0 {
+            if let FormatText::Custom(ref label, ref mut func) = *unit {
+                if label == name {
+                    *func = Some(CustomFn(Rc::clone(&f)));
+                }
+            }
+        }
+        self
+    }
 }
 
 impl Default for Logger {
@@ -99,7 +314,10 @@ impl Default for Logger {
     fn default() -> Logger {
         Logger {
             format: Format::default(),
+            json: None,
             exclude: HashSet::new(),
+            exclude_regex: Vec::new(),
+            sink: None,
         }
     }
 }
@@ -108,21 +326,106 @@ struct StartTime(time::Tm);
 
 impl Logger {
     fn log<S>(&self, req: &HttpRequest<S>, resp: &HttpResponse) {
-        if let Some(entry_time) = req.extensions().get::<StartTime>() {
+        let entry_time = match req.extensions().get::<StartTime>() {
+            Some(entry_time) => entry_time.0,
+            None => return,
+        };
+        if let Some(ref fields) = self.json {
+            let record = json_record(fields, req, resp, entry_time);
+            match self.sink {
+                Some(ref sink) => sink(&record),
+                None => info!("{}", record),
+            }
+        } else {
             let render = |fmt: &mut Formatter| {
                 for unit in &self.format.0 {
-                    unit.render(fmt, req, resp, entry_time.0)?;
+                    unit.render(fmt, req, resp, entry_time)?;
                 }
                 Ok(())
             };
-            info!("{}", FormatDisplay(&render));
+            match self.sink {
+                Some(ref sink) => sink(&FormatDisplay(&render).to_string()),
+                None => info!("{}", FormatDisplay(&render)),
+            }
+        }
+    }
+}
+
+fn header_value(val: Option<&HeaderValue>) -> Value {
+    val.and_then(|val| val.to_str().ok())
+        .map_or(Value::Null, |val| Value::String(val.to_owned()))
+}
+
+fn json_record<S>(
+    fields: &[(String, JsonField)], req: &HttpRequest<S>, resp: &HttpResponse,
+    entry_time: time::Tm,
+) -> String {
+    let mut record = String::from("{");
+    for (idx, &(ref name, ref field)) in fields.iter().enumerate() {
+        let value = match *field {
+            JsonField::Field(ref field) => field.value(req, resp, entry_time),
+            JsonField::Extension(ref f) => f(&req.extensions()).unwrap_or(Value::Null),
+        };
+        if idx > 0 {
+            record.push(',');
+        }
+        record.push_str(&Value::String(name.clone()).to_string());
+        record.push(':');
+        record.push_str(&value.to_string());
+    }
+    record.push('}');
+    record
+}
+
+impl LogField {
+    fn value<S>(
+        &self, req: &HttpRequest<S>, resp: &HttpResponse, entry_time: time::Tm,
+    ) -> Value {
+        match *self {
+            LogField::Time => entry_time.to_utc().rfc3339().to_string().into(),
+            LogField::Method => req.method().as_str().into(),
+            LogField::Path => req.path().into(),
+            LogField::Query => match req.query_string() {
+                "" => Value::Null,
+                query => query.into(),
+            },
+            LogField::Version => format!("{:?}", req.version()).into(),
+            LogField::Route => match MatchedResource::get(req).name() {
+                "" => Value::Null,
+                name => name.into(),
+            },
+            LogField::Pattern => MatchedResource::get(req)
+                .pattern()
+                .map_or(Value::Null, |pattern| pattern.into()),
+            LogField::Status => resp.status().as_u16().into(),
+            LogField::Size => resp.response_size().into(),
+            LogField::Duration => {
+                let rt = time::now() - entry_time;
+                (rt.num_nanoseconds().unwrap_or(0) as f64 / 1_000_000.0).into()
+            }
+            LogField::Peer => req
+                .connection_info()
+                .remote()
+                .map_or(Value::Null, |remote| remote.into()),
+            LogField::RequestId => req
+                .extensions()
+                .get::<RequestId>()
+                .map_or(Value::Null, |id| id.as_str().into()),
+            LogField::TimedOut => req.extensions().get::<TimedOut>().is_some().into(),
+            LogField::RequestHeader(ref name) => header_value(req.headers().get(name)),
+            LogField::ResponseHeader(ref name) => {
+                header_value(resp.headers().get(name))
+            }
         }
     }
 }
 
 impl<S> Middleware<S> for Logger {
     fn start(&self, req: &HttpRequest<S>) -> Result<Started> {
-        if !self.exclude.contains(req.path()) {
+        let path = req.path();
+        if !self.exclude.contains(path)
+            && !self.exclude_regex.iter().any(|re| re.is_match(path))
+        {
             req.extensions_mut().insert(StartTime(time::now()));
         }
         Ok(Started::Done)
@@ -153,7 +456,9 @@ impl Format {
     /// Returns `None` if the format string syntax is incorrect.
     pub fn new(s: &str) -> Format {
         trace!("Access log format: {}", s);
-        let fmt = Regex::new(r"%(\{([A-Za-z0-9\-_]+)\}([ioe])|[atPrsbTD]?)").unwrap();
+        let fmt =
+            Regex::new(r"%(\{([A-Za-z0-9\-_]+)\}(xi|[ioe])|[atPrsbTDLXUqRHhI]?)")
+                .unwrap();
 
         let mut idx = 0;
         let mut results = Vec::new();
@@ -170,6 +475,7 @@ impl Format {
                     "i" => FormatText::RequestHeader(key.as_str().to_owned()),
                     "o" => FormatText::ResponseHeader(key.as_str().to_owned()),
                     "e" => FormatText::EnvironHeader(key.as_str().to_owned()),
+                    "xi" => FormatText::Custom(key.as_str().to_owned(), None),
                     _ => unreachable!(),
                 })
             } else {
@@ -183,6 +489,14 @@ impl Format {
                     "b" => FormatText::ResponseSize,
                     "T" => FormatText::Time,
                     "D" => FormatText::TimeMillis,
+                    "L" => FormatText::RequestId,
+                    "X" => FormatText::TimedOut,
+                    "U" => FormatText::UrlPath,
+                    "q" => FormatText::Query,
+                    "R" => FormatText::Pattern,
+                    "H" => FormatText::Protocol,
+                    "h" => FormatText::PeerAddr,
+                    "I" => FormatText::RequestSize,
                     _ => FormatText::Str(m.as_str().to_owned()),
                 });
             }
@@ -208,10 +522,29 @@ pub enum FormatText {
     ResponseSize,
     Time,
     TimeMillis,
+    TimedOut,
+    RequestId,
     RemoteAddr,
+    UrlPath,
+    Query,
+    Pattern,
+    Protocol,
+    PeerAddr,
+    RequestSize,
     RequestHeader(String),
     ResponseHeader(String),
     EnvironHeader(String),
+    Custom(String, Option<CustomFn>),
+}
+
+#[doc(hidden)]
+#[derive(Clone)]
+pub struct CustomFn(Rc<TokenFn>);
+
+impl fmt::Debug for CustomFn {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "CustomFn")
+    }
 }
 
 impl FormatText {
@@ -252,6 +585,17 @@ impl FormatText {
                 let rt = (rt.num_nanoseconds().unwrap_or(0) as f64) / 1_000_000.0;
                 fmt.write_fmt(format_args!("{:.6}", rt))
             }
+            FormatText::TimedOut => {
+                if req.extensions().get::<TimedOut>().is_some() {
+                    "timeout".fmt(fmt)
+                } else {
+                    "-".fmt(fmt)
+                }
+            }
+            FormatText::RequestId => match req.extensions().get::<RequestId>() {
+                Some(id) => id.fmt(fmt),
+                None => "-".fmt(fmt),
+            },
             FormatText::RemoteAddr => {
                 if let Some(remote) = req.connection_info().remote() {
                     return remote.fmt(fmt);
@@ -294,6 +638,34 @@ impl FormatText {
                     "-".fmt(fmt)
                 }
             }
+            FormatText::UrlPath => req.path().fmt(fmt),
+            FormatText::Query => match req.query_string() {
+                "" => Ok(()),
+                query => write!(fmt, "?{}", query),
+            },
+            FormatText::Pattern => match MatchedResource::get(req).pattern() {
+                Some(pattern) => pattern.fmt(fmt),
+                None => "-".fmt(fmt),
+            },
+            FormatText::Protocol => write!(fmt, "{:?}", req.version()),
+            FormatText::PeerAddr => match req.peer_addr() {
+                Some(addr) => addr.ip().fmt(fmt),
+                None => "-".fmt(fmt),
+            },
+            FormatText::RequestSize => {
+                let len = req.headers().get(header::CONTENT_LENGTH).map(|val| {
+                    val.to_str().ok().and_then(|s| s.parse::<u64>().ok())
+                });
+                match len {
+                    Some(Some(len)) => len.fmt(fmt),
+                    Some(None) => "-".fmt(fmt),
+                    None => "0".fmt(fmt),
+                }
+            }
+            FormatText::Custom(_, ref f) => match *f {
+                Some(ref f) => (f.0)(&req.drop_state(), resp).fmt(fmt),
+                None => "-".fmt(fmt),
+            },
         }
     }
 }
@@ -311,7 +683,7 @@ mod tests {
     use time;
 
     use super::*;
-    use http::{header, StatusCode};
+    use http::{header, Method, StatusCode};
     use test::TestRequest;
 
     #[test]
@@ -346,6 +718,169 @@ mod tests {
         assert!(s.contains("ACTIX-WEB ttt"));
     }
 
+    #[test]
+    fn test_timed_out_format() {
+        let format = Format::new("%s %X");
+
+        let req = TestRequest::default().finish();
+        let resp = HttpResponse::build(StatusCode::OK).finish();
+        let entry_time = time::now();
+        let render = |fmt: &mut Formatter| {
+            for unit in &format.0 {
+                unit.render(fmt, &req, &resp, entry_time)?;
+            }
+            Ok(())
+        };
+        assert_eq!(format!("{}", FormatDisplay(&render)), "200 -");
+
+        req.extensions_mut().insert(TimedOut);
+        let render = |fmt: &mut Formatter| {
+            for unit in &format.0 {
+                unit.render(fmt, &req, &resp, entry_time)?;
+            }
+            Ok(())
+        };
+        assert_eq!(format!("{}", FormatDisplay(&render)), "200 timeout");
+    }
+
+    #[test]
+    fn test_extended_format() {
+        let format = Format::new("%U%q %R %H %h %I %{ctype}xi %{missing}xi");
+        let logger = Logger {
+            format,
+            ..Logger::default()
+        }.custom_token("ctype", |req, resp| {
+            format!("{}>{}", req.content_type(), resp.status().as_u16())
+        });
+
+        let req = TestRequest::with_uri("/test?a=b")
+            .header(header::CONTENT_TYPE, "text/plain")
+            .header(header::CONTENT_LENGTH, "11")
+            .finish();
+        let resp = HttpResponse::build(StatusCode::OK).finish();
+        let entry_time = time::now();
+        let render = |fmt: &mut Formatter| {
+            for unit in &logger.format.0 {
+                unit.render(fmt, &req, &resp, entry_time)?;
+            }
+            Ok(())
+        };
+        assert_eq!(
+            format!("{}", FormatDisplay(&render)),
+            "/test?a=b - HTTP/1.1 - 11 text/plain>200 -"
+        );
+    }
+
+    #[test]
+    fn test_exclude() {
+        let logger = Logger::default()
+            .exclude("/health")
+            .exclude_regex("^/static/");
+
+        for (path, logged) in &[
+            ("/health", false),
+            ("/static/app.js", false),
+            ("/health/db", true),
+            ("/app/static/", true),
+        ] {
+            let req = TestRequest::with_uri(path).finish();
+            let _ = logger.start(&req);
+            assert_eq!(req.extensions().get::<StartTime>().is_some(), *logged);
+        }
+    }
+
+    #[test]
+    fn test_json_logger() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        #[derive(Serialize)]
+        struct User {
+            id: u32,
+        }
+
+        let lines = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&lines);
+        let logger = Logger::json()
+            .field("agent", LogField::RequestHeader("user-agent".to_owned()))
+            .field("query", LogField::Query)
+            .field("path", LogField::Pattern)
+            .extension::<User>("user")
+            .extension::<String>("missing")
+            .remove_field("time")
+            .sink(move |line| sink.borrow_mut().push(line.to_owned()));
+
+        let req = TestRequest::with_uri("/test?q=1")
+            .header(header::USER_AGENT, "ACTIX-WEB")
+            .method(Method::POST)
+            .finish();
+        req.extensions_mut().insert(User { id: 10 });
+        let resp = HttpResponse::build(StatusCode::CREATED).finish();
+        let _ = logger.start(&req);
+        logger.finish(&req, &resp);
+
+        let lines = lines.borrow();
+        assert_eq!(lines.len(), 1);
+        let record: Value = serde_json::from_str(&lines[0]).unwrap();
+        let keys: Vec<_> = record.as_object().unwrap().keys().cloned().collect();
+        assert_eq!(keys.len(), 11);
+        assert!(record.get("time").is_none());
+        assert_eq!(record["method"], "POST");
+        assert_eq!(record["path"], Value::Null);
+        assert_eq!(record["route"], Value::Null);
+        assert_eq!(record["status"], 201);
+        assert!(record["duration_ms"].is_f64());
+        assert_eq!(record["agent"], "ACTIX-WEB");
+        assert_eq!(record["query"], "q=1");
+        assert_eq!(record["user"]["id"], 10);
+        assert_eq!(record["missing"], Value::Null);
+        assert!(lines[0].starts_with("{\"method\":\"POST\","));
+    }
+
+    #[test]
+    fn test_text_sink() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let lines = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&lines);
+        let logger = Logger::new("%s %{X-Test}o")
+            .field("ignored", LogField::Method)
+            .sink(move |line| sink.borrow_mut().push(line.to_owned()));
+
+        let req = TestRequest::default().finish();
+        let resp = HttpResponse::Ok().header("X-Test", "ttt").finish();
+        let _ = logger.start(&req);
+        logger.finish(&req, &resp);
+        assert_eq!(*lines.borrow(), vec!["200 ttt".to_owned()]);
+    }
+
+    #[test]
+    fn test_request_id_format() {
+        use middleware::request_id::RequestIdService;
+
+        let format = Format::new("%L %s");
+        let req = TestRequest::with_header("x-request-id", "abc").finish();
+        let resp = HttpResponse::build(StatusCode::OK).finish();
+        let entry_time = time::now();
+        let render = |fmt: &mut Formatter| {
+            for unit in &format.0 {
+                unit.render(fmt, &req, &resp, entry_time)?;
+            }
+            Ok(())
+        };
+        assert_eq!(format!("{}", FormatDisplay(&render)), "- 200");
+
+        let _ = RequestIdService::new().start(&req);
+        let render = |fmt: &mut Formatter| {
+            for unit in &format.0 {
+                unit.render(fmt, &req, &resp, entry_time)?;
+            }
+            Ok(())
+        };
+        assert_eq!(format!("{}", FormatDisplay(&render)), "abc 200");
+    }
+
     #[test]
     fn test_default_format() {
         let format = Format::default();
@@ -0,0 +1,437 @@
+//! Prometheus metrics middleware
+//!
+//! [`Metrics`](struct.Metrics.html) middleware collects request counts,
+//! latency and response size histograms and in-flight requests gauge.
+//! Metrics are labelled by request method, path pattern of the matched
+//! resource, including patterns of enclosing scopes, and response status.
+//! Requests which did not match any resource are labelled with empty path.
+//! Resource is not known until request is routed, so in-flight requests
+//! gauge is labelled by method only.
+//!
+//! `Metrics` is a handler as well, it renders collected metrics in
+//! [Prometheus text exposition
+//! format](https://prometheus.io/docs/instrumenting/exposition_formats/)
+//! together with the number of open connections of all server workers.
+//! It could receive `ClientConnectorStats` messages, see
+//! `ClientConnector::stats()`.
+//!
+//! Application factory runs for each worker thread, metrics are shared
+//! between clones of `Metrics`, so it should be created outside of
+//! the factory.
+//!
+//! ```rust
+//! # extern crate actix_web;
+//! use actix_web::middleware::metrics::Metrics;
+//! use actix_web::{server, App, HttpResponse};
+//!
+//! fn main() {
+//!     let metrics = Metrics::new();
+//!
+//!     server::new(move || {
+//!         let handler = metrics.clone();
+//!         App::new()
+//!             .middleware(metrics.clone())
+//!             .resource("/metrics", move |r| r.h(handler))
+//!             .resource("/user/{name}", |r| r.f(|_| HttpResponse::Ok()))
+//!     });
+//! }
+//! ```
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::Instant;
+
+use actix;
+use http::header;
+use parking_lot::Mutex;
+
+use client::ClientConnectorStats;
+use error::Result;
+use handler::Handler;
+use httprequest::HttpRequest;
+use httpresponse::HttpResponse;
+use middleware::{Finished, Middleware, Started};
+use router::MatchedResource;
+
+/// Default latency buckets, in seconds
+const LATENCY_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Response size buckets, in bytes
+const SIZE_BUCKETS: &[f64] = &[
+    100.0, 1_000.0, 10_000.0, 100_000.0, 1_000_000.0, 10_000_000.0,
+];
+
+/// Prometheus metrics collector.
+#[derive(Clone)]
+pub struct Metrics {
+    inner: Arc<Mutex<Registry>>,
+}
+
+struct Histogram {
+    buckets: Arc<Vec<f64>>,
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn new(buckets: &Arc<Vec<f64>>) -> Histogram {
+        Histogram {
+            buckets: Arc::clone(buckets),
+            counts: vec![0; buckets.len()],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn observe(&mut self, value: f64) {
+        if let Some(idx) = self.buckets.iter().position(|b| value <= *b) {
+            self.counts[idx] += 1;
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+
+    fn render(&self, out: &mut String, name: &str, labels: &str) {
+        let mut total = 0;
+        for (bound, count) in self.buckets.iter().zip(&self.counts) {
+            total += count;
+            let _ = writeln!(
+                out,
+                "{}_bucket{{{},le=\"{}\"}} {}",
+                name, labels, bound, total
+            );
+        }
+        let _ = writeln!(
+            out,
+            "{}_bucket{{{},le=\"+Inf\"}} {}",
+            name, labels, self.count
+        );
+        let _ = writeln!(out, "{}_sum{{{}}} {}", name, labels, self.sum);
+        let _ = writeln!(out, "{}_count{{{}}} {}", name, labels, self.count);
+    }
+}
+
+struct RequestStats {
+    count: u64,
+    duration: Histogram,
+    size: Histogram,
+}
+
+struct Registry {
+    latency_buckets: Arc<Vec<f64>>,
+    size_buckets: Arc<Vec<f64>>,
+    requests: BTreeMap<(String, String, u16), RequestStats>,
+    in_flight: BTreeMap<String, u64>,
+    client: Option<ClientConnectorStats>,
+}
+
+/// In-flight request, gauge is decremented on drop
+struct InFlight {
+    inner: Arc<Mutex<Registry>>,
+    method: String,
+    start: Instant,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        if let Some(val) = self.inner.lock().in_flight.get_mut(&self.method) {
+            *val -= 1;
+        }
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Metrics {
+        Metrics::new()
+    }
+}
+
+impl Metrics {
+    /// Create metrics collector
+    pub fn new() -> Metrics {
+        Metrics {
+            inner: Arc::new(Mutex::new(Registry {
+                latency_buckets: Arc::new(LATENCY_BUCKETS.to_vec()),
+                size_buckets: Arc::new(SIZE_BUCKETS.to_vec()),
+                requests: BTreeMap::new(),
+                in_flight: BTreeMap::new(),
+                client: None,
+            })),
+        }
+    }
+
+    /// Set upper bounds of latency histogram buckets, in seconds.
+    ///
+    /// Bounds must be sorted, already collected metrics are discarded.
+    pub fn buckets(self, buckets: &[f64]) -> Self {
+        {
+            let mut inner = self.inner.lock();
+            inner.latency_buckets = Arc::new(buckets.to_vec());
+            inner.requests.clear();
+        }
+        self
+    }
+
+    /// Render metrics in Prometheus text format
+    pub fn render(&self) -> String {
+        let inner = self.inner.lock();
+        let mut out = String::new();
+
+        out.push_str("# HELP http_requests_total Total number of HTTP requests\n");
+        out.push_str("# TYPE http_requests_total counter\n");
+        for (&(ref method, ref path, status), stats) in &inner.requests {
+            let _ = writeln!(
+                out,
+                "http_requests_total{{{}}} {}",
+                labels(method, path, status),
+                stats.count
+            );
+        }
+
+        out.push_str(
+            "# HELP http_request_duration_seconds HTTP request latency in seconds\n",
+        );
+        out.push_str("# TYPE http_request_duration_seconds histogram\n");
+        for (&(ref method, ref path, status), stats) in &inner.requests {
+            stats.duration.render(
+                &mut out,
+                "http_request_duration_seconds",
+                &labels(method, path, status),
+            );
+        }
+
+        out.push_str("# HELP http_response_size_bytes HTTP response size in bytes\n");
+        out.push_str("# TYPE http_response_size_bytes histogram\n");
+        for (&(ref method, ref path, status), stats) in &inner.requests {
+            stats.size.render(
+                &mut out,
+                "http_response_size_bytes",
+                &labels(method, path, status),
+            );
+        }
+
+        out.push_str(
+            "# HELP http_requests_in_flight Number of HTTP requests in processing\n",
+        );
+        out.push_str("# TYPE http_requests_in_flight gauge\n");
+        for (method, val) in &inner.in_flight {
+            let _ = writeln!(
+                out,
+                "http_requests_in_flight{{method=\"{}\"}} {}",
+                method, val
+            );
+        }
+
+        if let Some(ref stats) = inner.client {
+            for &(name, help, val) in &[
+                ("opened", "Number of opened connections", stats.opened),
+                ("reused", "Number of reused connections", stats.reused),
+                ("closed", "Number of closed connections", stats.closed),
+                ("errors", "Number of connections with errors", stats.errors),
+                ("timeouts", "Number of connection timeouts", stats.timeouts),
+                ("waits", "Number of waited-on connections", stats.waits),
+            ] {
+                let _ = writeln!(
+                    out,
+                    "# HELP http_client_connections_{0}_total {1}\n\
+                     # TYPE http_client_connections_{0}_total counter\n\
+                     http_client_connections_{0}_total {2}",
+                    name, help, val
+                );
+            }
+            out.push_str("# HELP http_client_wait_queue Size of the wait queue\n");
+            out.push_str("# TYPE http_client_wait_queue gauge\n");
+            let _ = writeln!(out, "http_client_wait_queue {}", stats.wait_queue);
+        }
+        out
+    }
+}
+
+fn escape(val: &str) -> String {
+    val.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn labels(method: &str, path: &str, status: u16) -> String {
+    format!(
+        "method=\"{}\",path=\"{}\",status=\"{}\"",
+        method,
+        escape(path),
+        status
+    )
+}
+
+impl<S> Middleware<S> for Metrics {
+    fn start(&self, req: &HttpRequest<S>) -> Result<Started> {
+        let method = req.method().as_str().to_owned();
+        *self
+            .inner
+            .lock()
+            .in_flight
+            .entry(method.clone())
+            .or_insert(0) += 1;
+        req.extensions_mut().insert(InFlight {
+            method,
+            inner: Arc::clone(&self.inner),
+            start: Instant::now(),
+        });
+        Ok(Started::Done)
+    }
+
+    fn finish(&self, req: &HttpRequest<S>, resp: &HttpResponse) -> Finished {
+        let in_flight = req.extensions_mut().remove::<InFlight>();
+        if let Some(in_flight) = in_flight {
+            let elapsed = in_flight.start.elapsed();
+            let elapsed = elapsed.as_secs() as f64
+                + f64::from(elapsed.subsec_nanos()) / 1_000_000_000.0;
+            let method = in_flight.method.clone();
+            drop(in_flight);
+            let path = MatchedResource::get(req).pattern().unwrap_or_default();
+
+            let mut inner = self.inner.lock();
+            let Registry {
+                ref latency_buckets,
+                ref size_buckets,
+                ref mut requests,
+                ..
+            } = *inner;
+            let stats = requests
+                .entry((method, path, resp.status().as_u16()))
+                .or_insert_with(|| RequestStats {
+                    count: 0,
+                    duration: Histogram::new(latency_buckets),
+                    size: Histogram::new(size_buckets),
+                });
+            stats.count += 1;
+            stats.duration.observe(elapsed);
+            stats.size.observe(resp.response_size() as f64);
+        }
+        Finished::Done
+    }
+}
+
+impl<S> Handler<S> for Metrics {
+    type Result = HttpResponse;
+
+    fn handle(&self, req: &HttpRequest<S>) -> HttpResponse {
+        let mut body = self.render();
+        if let Some(conns) = req.server_settings().connections() {
+            body.push_str("# HELP http_server_connections Number of open connections\n");
+            body.push_str("# TYPE http_server_connections gauge\n");
+            let _ = writeln!(body, "http_server_connections {}", conns);
+        }
+        HttpResponse::Ok()
+            .header(header::CONTENT_TYPE, "text/plain; version=0.0.4")
+            .body(body)
+    }
+}
+
+impl actix::Actor for Metrics {
+    type Context = actix::Context<Self>;
+}
+
+impl actix::Handler<ClientConnectorStats> for Metrics {
+    type Result = ();
+
+    fn handle(&mut self, stats: ClientConnectorStats, _: &mut Self::Context) {
+        self.add_client_stats(&stats);
+    }
+}
+
+impl Metrics {
+    fn add_client_stats(&self, stats: &ClientConnectorStats) {
+        let mut inner = self.inner.lock();
+        let total = inner.client.get_or_insert_with(ClientConnectorStats::default);
+        total.waits += stats.waits;
+        total.reused += stats.reused;
+        total.opened += stats.opened;
+        total.closed += stats.closed;
+        total.errors += stats.errors;
+        total.timeouts += stats.timeouts;
+        total.wait_queue = stats.wait_queue;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::Method;
+    use test::TestRequest;
+
+    #[test]
+    fn test_histogram() {
+        let mut h = Histogram::new(&Arc::new(vec![1.0, 2.0]));
+        h.observe(0.5);
+        h.observe(1.5);
+        h.observe(3.0);
+        let mut out = String::new();
+        h.render(&mut out, "test", "a=\"b\"");
+        assert_eq!(
+            out,
+            "test_bucket{a=\"b\",le=\"1\"} 1\n\
+             test_bucket{a=\"b\",le=\"2\"} 2\n\
+             test_bucket{a=\"b\",le=\"+Inf\"} 3\n\
+             test_sum{a=\"b\"} 5\n\
+             test_count{a=\"b\"} 3\n"
+        );
+    }
+
+    #[test]
+    fn test_metrics() {
+        let metrics = Metrics::new().buckets(&[0.5]);
+
+        let req = TestRequest::default().method(Method::POST).finish();
+        let _ = metrics.start(&req);
+        assert!(
+            metrics
+                .render()
+                .contains("http_requests_in_flight{method=\"POST\"} 1")
+        );
+
+        let resp = HttpResponse::NotFound().finish();
+        metrics.finish(&req, &resp);
+
+        let out = metrics.render();
+        assert!(out.contains("http_requests_in_flight{method=\"POST\"} 0"));
+        let total = "http_requests_total{method=\"POST\",path=\"\",status=\"404\"} 1";
+        assert!(out.contains(total));
+        assert!(out.contains(
+            "http_request_duration_seconds_bucket\
+             {method=\"POST\",path=\"\",status=\"404\",le=\"0.5\"} 1"
+        ));
+        assert!(out.contains(
+            "http_response_size_bytes_count{method=\"POST\",path=\"\",status=\"404\"} 1"
+        ));
+        assert!(!out.contains("http_client"));
+
+        // finish without start is ignored
+        metrics.finish(&TestRequest::default().finish(), &resp);
+        assert!(metrics.render().contains(total));
+    }
+
+    #[test]
+    fn test_client_stats() {
+        let metrics = Metrics::new();
+        for _ in 0..2 {
+            let mut stats = ClientConnectorStats::default();
+            stats.opened = 2;
+            stats.wait_queue = 1;
+            metrics.add_client_stats(&stats);
+        }
+        let out = metrics.render();
+        assert!(out.contains("http_client_connections_opened_total 4\n"));
+        assert!(out.contains("http_client_wait_queue 1\n"));
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            labels("GET", "/a\"b\\", 200),
+            "method=\"GET\",path=\"/a\\\"b\\\\\",status=\"200\""
+        );
+    }
+}
@@ -7,17 +7,26 @@ use httpresponse::HttpResponse;
 
 mod logger;
 
+mod concurrency;
 pub mod cors;
 pub mod csrf;
 mod defaultheaders;
 mod errhandlers;
 #[cfg(feature = "session")]
 pub mod identity;
+pub mod metrics;
+pub mod ratelimit;
+pub mod request_id;
 #[cfg(feature = "session")]
 pub mod session;
+mod timeout;
+pub use self::concurrency::{ConcurrencyLimit, ConcurrencyStats};
 pub use self::defaultheaders::DefaultHeaders;
 pub use self::errhandlers::ErrorHandlers;
-pub use self::logger::Logger;
+pub use self::logger::{LogField, Logger};
+pub use self::timeout::Timeout;
+
+pub(crate) use self::timeout::with_deadline;
 
 /// Middleware start result
 pub enum Started {
@@ -0,0 +1,632 @@
+//! Rate limiting middleware
+//!
+//! [`RateLimit`](struct.RateLimit.html) middleware counts requests per key
+//! and rejects requests over the quota with `429 Too Many Requests` response.
+//! By default requests are keyed by client address, keys could be derived
+//! from the request identity or with custom function.
+//!
+//! Two algorithms are supported:
+//!
+//! * *token bucket* - up to `limit` requests could be made at once, then
+//!   tokens are refilled at constant rate of `limit` per `period`.
+//! * *sliding window* - at most `limit` requests over any `period`, number
+//!   of requests is estimated from current and previous fixed windows.
+//!
+//! Allowed responses contain `RateLimit-Limit`, `RateLimit-Remaining`
+//! and `RateLimit-Reset` headers, rejected responses contain `Retry-After`
+//! header as well.
+//!
+//! State is kept in a [`RateLimitStore`](trait.RateLimitStore.html).
+//! Application factory runs for each worker thread, and limiter created
+//! with `RateLimit::new()` has its own store, so its limit applies per
+//! worker. To enforce one limit for the whole server, create a store
+//! outside of the factory and pass it to `RateLimit::with_store()`.
+//!
+//! # Example
+//!
+//! ```rust
+//! # extern crate actix_web;
+//! use actix_web::middleware::ratelimit::{MemoryStore, Quota, RateLimit};
+//! use actix_web::{server, App, HttpResponse};
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! fn main() {
+//!     let store = Arc::new(MemoryStore::new());
+//!
+//!     server::new(move || {
+//!         App::new()
+//!             .middleware(RateLimit::with_store(
+//!                 Quota::token_bucket(100, Duration::from_secs(60)),
+//!                 store.clone(),
+//!             ))
+//!             .resource("/", |r| r.f(|_| HttpResponse::Ok()))
+//!     });
+//! }
+//! ```
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use http::header::{self, HeaderName, HeaderValue};
+use http::StatusCode;
+use parking_lot::Mutex;
+
+use error::Result;
+use httprequest::HttpRequest;
+use httpresponse::HttpResponse;
+use middleware::{Middleware, Response, Started};
+
+#[cfg(feature = "session")]
+use middleware::identity::RequestIdentity;
+
+/// Rate limiting algorithm
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    /// Token bucket, allows bursts up to the limit
+    TokenBucket,
+    /// Sliding window counter
+    SlidingWindow,
+}
+
+/// Number of requests allowed per period
+#[derive(Debug, Clone, Copy)]
+pub struct Quota {
+    limit: u32,
+    period: Duration,
+    algorithm: Algorithm,
+}
+
+impl Quota {
+    /// Quota enforced with token bucket algorithm
+    pub fn token_bucket(limit: u32, period: Duration) -> Quota {
+        Quota::new(limit, period, Algorithm::TokenBucket)
+    }
+
+    /// Quota enforced with sliding window algorithm
+    pub fn sliding_window(limit: u32, period: Duration) -> Quota {
+        Quota::new(limit, period, Algorithm::SlidingWindow)
+    }
+
+    fn new(limit: u32, period: Duration, algorithm: Algorithm) -> Quota {
+        assert!(limit > 0, "Quota limit must be positive");
+        assert!(period > Duration::from_secs(0), "Quota period must be positive");
+        Quota {
+            limit,
+            period,
+            algorithm,
+        }
+    }
+
+    /// Maximum number of requests
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    /// Period of the quota
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Algorithm used for the quota
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+}
+
+/// Result of the quota check
+#[derive(Debug, Clone, Copy)]
+pub struct Decision {
+    /// Request is allowed
+    pub allowed: bool,
+    /// Maximum number of requests
+    pub limit: u32,
+    /// Number of requests remaining
+    pub remaining: u32,
+    /// Time until quota is fully restored
+    pub reset: Duration,
+    /// Time until next request could be allowed, set for rejected requests
+    pub retry_after: Option<Duration>,
+}
+
+/// Storage for rate limiter state.
+///
+/// Store is shared between workers and must be thread safe.
+pub trait RateLimitStore: Send + Sync + 'static {
+    /// Check quota for the key and count the request if it is allowed
+    fn check(&self, key: &str, quota: &Quota, now: Instant) -> Decision;
+}
+
+/// In-memory rate limiter store.
+///
+/// Idle keys are periodically removed from the store.
+pub struct MemoryStore {
+    inner: Mutex<MemoryInner>,
+}
+
+struct MemoryInner {
+    entries: HashMap<String, Entry>,
+    checks: usize,
+}
+
+struct Entry {
+    state: State,
+    expires: Instant,
+}
+
+enum State {
+    Bucket {
+        tokens: f64,
+        updated: Instant,
+    },
+    Window {
+        start: Instant,
+        current: u32,
+        previous: u32,
+    },
+}
+
+const PRUNE_INTERVAL: usize = 1024;
+
+impl MemoryStore {
+    /// Create new empty store
+    pub fn new() -> MemoryStore {
+        MemoryStore {
+            inner: Mutex::new(MemoryInner {
+                entries: HashMap::new(),
+                checks: 0,
+            }),
+        }
+    }
+}
+
+impl Default for MemoryStore {
+    fn default() -> MemoryStore {
+        MemoryStore::new()
+    }
+}
+
+impl RateLimitStore for MemoryStore {
+    fn check(&self, key: &str, quota: &Quota, now: Instant) -> Decision {
+        let mut inner = self.inner.lock();
+
+        inner.checks += 1;
+        if inner.checks >= PRUNE_INTERVAL {
+            inner.checks = 0;
+            inner.entries.retain(|_, entry| entry.expires > now);
+        }
+
+        if !inner.entries.contains_key(key) {
+            let state = match quota.algorithm {
+                Algorithm::TokenBucket => State::Bucket {
+                    tokens: f64::from(quota.limit),
+                    updated: now,
+                },
+                Algorithm::SlidingWindow => State::Window {
+                    start: now,
+                    current: 0,
+                    previous: 0,
+                },
+            };
+            inner.entries.insert(
+                key.to_owned(),
+                Entry {
+                    state,
+                    expires: now,
+                },
+            );
+        }
+        let entry = inner.entries.get_mut(key).unwrap();
+        entry.expires = now + quota.period * 2;
+        entry.state.check(quota, now)
+    }
+}
+
+fn secs(d: Duration) -> f64 {
+    d.as_secs() as f64 + f64::from(d.subsec_nanos()) / 1_000_000_000.0
+}
+
+fn duration(secs: f64) -> Duration {
+    let secs = secs.max(0.0);
+    Duration::new(secs.trunc() as u64, (secs.fract() * 1_000_000_000.0) as u32)
+}
+
+impl State {
+    fn check(&mut self, quota: &Quota, now: Instant) -> Decision {
+        let limit = f64::from(quota.limit);
+        let period = secs(quota.period);
+
+        match *self {
+            State::Bucket {
+                ref mut tokens,
+                ref mut updated,
+            } => {
+                let rate = limit / period;
+                let elapsed = secs(now.duration_since(*updated));
+                *tokens = (*tokens + elapsed * rate).min(limit);
+                *updated = now;
+
+                let allowed = *tokens >= 1.0;
+                if allowed {
+                    *tokens -= 1.0;
+                }
+                Decision {
+                    allowed,
+                    limit: quota.limit,
+                    remaining: tokens.floor() as u32,
+                    reset: duration((limit - *tokens) * period / limit),
+                    retry_after: if allowed {
+                        None
+                    } else {
+                        Some(duration((1.0 - *tokens) * period / limit))
+                    },
+                }
+            }
+            State::Window {
+                ref mut start,
+                ref mut current,
+                ref mut previous,
+            } => {
+                let mut elapsed = now.duration_since(*start);
+                if elapsed >= quota.period * 2 {
+                    *start = now;
+                    *previous = 0;
+                    *current = 0;
+                    elapsed = Duration::from_secs(0);
+                } else if elapsed >= quota.period {
+                    *start += quota.period;
+                    *previous = *current;
+                    *current = 0;
+                    elapsed -= quota.period;
+                }
+                let elapsed = secs(elapsed);
+                let weight = 1.0 - elapsed / period;
+                let estimated = f64::from(*previous) * weight + f64::from(*current);
+
+                let allowed = estimated + 1.0 <= limit;
+                if allowed {
+                    *current += 1;
+                }
+                let used = (estimated + if allowed { 1.0 } else { 0.0 }).ceil();
+
+                let retry_after = if allowed {
+                    None
+                } else if *current < quota.limit {
+                    // wait until weighted previous window drops enough
+                    let free = limit - 1.0 - f64::from(*current);
+                    let at = period * (1.0 - free / f64::from(*previous));
+                    Some(duration(at - elapsed))
+                } else {
+                    // wait until current window becomes previous one
+                    let at = period * (1.0 - (limit - 1.0) / f64::from(*current));
+                    Some(duration(period - elapsed + at))
+                };
+                Decision {
+                    allowed,
+                    retry_after,
+                    limit: quota.limit,
+                    remaining: (limit - used).max(0.0) as u32,
+                    reset: duration(if *previous > 0 {
+                        2.0 * period - elapsed
+                    } else {
+                        period - elapsed
+                    }),
+                }
+            }
+        }
+    }
+}
+
+/// `Middleware` for rate limiting requests.
+///
+/// Requests for which the key could not be found are not limited.
+///
+/// ```rust
+/// # extern crate actix_web;
+/// use actix_web::middleware::ratelimit::{Quota, RateLimit};
+/// use actix_web::{App, HttpRequest, HttpResponse};
+/// use std::time::Duration;
+///
+/// fn main() {
+///     let app = App::new()
+///         .resource("/login", |r| {
+///             r.middleware(
+///                 RateLimit::new(Quota::sliding_window(5, Duration::from_secs(60)))
+///                     .key_by(|req: &HttpRequest| {
+///                         req.query().get("user").map(|user| user.to_owned())
+///                     }),
+///             );
+///             r.f(|_| HttpResponse::Ok())
+///         })
+///         .finish();
+/// }
+/// ```
+pub struct RateLimit<S> {
+    quota: Quota,
+    store: Arc<RateLimitStore>,
+    key: Box<Fn(&HttpRequest<S>) -> Option<String>>,
+}
+
+impl<S: 'static> RateLimit<S> {
+    /// Create rate limiter with the specified quota.
+    ///
+    /// Requests are keyed by client address, see `ConnectionInfo::remote()`.
+    /// Limiter uses its own `MemoryStore`, so the quota is enforced per
+    /// worker thread, use `with_store()` to share the limit between workers.
+    pub fn new(quota: Quota) -> RateLimit<S> {
+        RateLimit::with_store(quota, Arc::new(MemoryStore::new()))
+    }
+
+    /// Create rate limiter with the specified quota and store.
+    ///
+    /// Limiters which use the same store share the quota, store created
+    /// outside of the application factory is shared by all workers.
+    /// Limiters with different quotas should not share a store.
+    pub fn with_store(quota: Quota, store: Arc<RateLimitStore>) -> RateLimit<S> {
+        RateLimit {
+            quota,
+            store,
+            key: Box::new(peer_key),
+        }
+    }
+
+    /// Derive key from the request with the function.
+    ///
+    /// Request is not limited if the function returns `None`.
+    pub fn key_by<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&HttpRequest<S>) -> Option<String> + 'static,
+    {
+        self.key = Box::new(f);
+        self
+    }
+
+    /// Use request identity as a key.
+    ///
+    /// Requests without identity are keyed by client address. `IdentityService`
+    /// middleware has to be registered before the limiter.
+    #[cfg(feature = "session")]
+    pub fn key_by_identity(self) -> Self {
+        self.key_by(|req| match req.identity() {
+            Some(id) => Some(format!("id:{}", id)),
+            None => peer_key(req),
+        })
+    }
+}
+
+fn peer_key<S>(req: &HttpRequest<S>) -> Option<String> {
+    let info = req.connection_info();
+    info.remote().map(|remote| match remote.parse::<SocketAddr>() {
+        Ok(addr) => format!("ip:{}", addr.ip()),
+        Err(_) => format!("ip:{}", remote),
+    })
+}
+
+/// Most restrictive decision for the request
+struct RateLimited(Decision);
+
+fn ceil_secs(d: Duration) -> u64 {
+    if d.subsec_nanos() > 0 {
+        d.as_secs() + 1
+    } else {
+        d.as_secs()
+    }
+}
+
+fn set_headers(resp: &mut HttpResponse, decision: &Decision) {
+    let headers = resp.headers_mut();
+    for &(name, value) in &[
+        ("ratelimit-limit", u64::from(decision.limit)),
+        ("ratelimit-remaining", u64::from(decision.remaining)),
+        ("ratelimit-reset", ceil_secs(decision.reset)),
+    ] {
+        headers.insert(HeaderName::from_static(name), HeaderValue::from(value));
+    }
+    if let Some(retry) = decision.retry_after {
+        headers.insert(header::RETRY_AFTER, HeaderValue::from(ceil_secs(retry)));
+    }
+}
+
+impl<S: 'static> Middleware<S> for RateLimit<S> {
+    fn start(&self, req: &HttpRequest<S>) -> Result<Started> {
+        let key = match (self.key)(req) {
+            Some(key) => key,
+            None => return Ok(Started::Done),
+        };
+        let decision = self.store.check(&key, &self.quota, Instant::now());
+
+        if !decision.allowed {
+            let mut resp = req
+                .build_response(StatusCode::TOO_MANY_REQUESTS)
+                .finish();
+            set_headers(&mut resp, &decision);
+            return Ok(Started::Response(resp));
+        }
+
+        let mut ext = req.extensions_mut();
+        if ext
+            .get::<RateLimited>()
+            .map_or(true, |d| decision.remaining < d.0.remaining)
+        {
+            ext.insert(RateLimited(decision));
+        }
+        Ok(Started::Done)
+    }
+
+    fn response(
+        &self, req: &HttpRequest<S>, mut resp: HttpResponse,
+    ) -> Result<Response> {
+        if let Some(decision) = req.extensions().get::<RateLimited>() {
+            set_headers(&mut resp, &decision.0);
+        }
+        Ok(Response::Done(resp))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test::TestRequest;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn test_token_bucket() {
+        let store = MemoryStore::new();
+        let quota = Quota::token_bucket(2, Duration::from_secs(1));
+        let now = Instant::now();
+
+        let d = store.check("a", &quota, now);
+        assert!(d.allowed);
+        assert_eq!(d.remaining, 1);
+        assert!(store.check("a", &quota, now).allowed);
+        let d = store.check("a", &quota, now);
+        assert!(!d.allowed);
+        assert_eq!(d.remaining, 0);
+        assert_eq!(d.retry_after, Some(ms(500)));
+        assert_eq!(d.reset, Duration::from_secs(1));
+
+        // other keys are not affected
+        assert!(store.check("b", &quota, now).allowed);
+
+        // one token is refilled
+        assert!(store.check("a", &quota, now + ms(500)).allowed);
+        assert!(!store.check("a", &quota, now + ms(500)).allowed);
+
+        // bucket is full again
+        let d = store.check("a", &quota, now + ms(2000));
+        assert!(d.allowed);
+        assert_eq!(d.remaining, 1);
+    }
+
+    #[test]
+    fn test_sliding_window() {
+        let store = MemoryStore::new();
+        let quota = Quota::sliding_window(4, Duration::from_secs(1));
+        let now = Instant::now();
+
+        for i in 0..4 {
+            let d = store.check("a", &quota, now);
+            assert!(d.allowed);
+            assert_eq!(d.remaining, 3 - i);
+        }
+        let d = store.check("a", &quota, now + ms(500));
+        assert!(!d.allowed);
+        assert_eq!(d.retry_after, Some(ms(750)));
+
+        // previous window weighs 3/4 at this point
+        let d = store.check("a", &quota, now + ms(1250));
+        assert!(d.allowed);
+        assert_eq!(d.remaining, 0);
+        assert!(!store.check("a", &quota, now + ms(1250)).allowed);
+
+        // both windows are passed
+        let d = store.check("a", &quota, now + ms(3000));
+        assert!(d.allowed);
+        assert_eq!(d.remaining, 3);
+    }
+
+    #[test]
+    fn test_prune() {
+        let store = MemoryStore::new();
+        let quota = Quota::token_bucket(1, Duration::from_secs(1));
+        let now = Instant::now();
+
+        for i in 0..PRUNE_INTERVAL - 1 {
+            store.check(&i.to_string(), &quota, now);
+        }
+        assert_eq!(store.inner.lock().entries.len(), PRUNE_INTERVAL - 1);
+        store.check("a", &quota, now + Duration::from_secs(3));
+        assert_eq!(store.inner.lock().entries.len(), 1);
+    }
+
+    #[test]
+    fn test_middleware() {
+        let limiter = RateLimit::new(Quota::token_bucket(1, Duration::from_secs(60)));
+
+        let req = TestRequest::with_header("x-forwarded-for", "127.0.0.1").finish();
+        match limiter.start(&req) {
+            Ok(Started::Done) => (),
+            _ => panic!(),
+        }
+        let resp = match limiter.response(&req, HttpResponse::Ok().finish()) {
+            Ok(Response::Done(resp)) => resp,
+            _ => panic!(),
+        };
+        assert_eq!(resp.headers().get("ratelimit-limit").unwrap(), "1");
+        assert_eq!(resp.headers().get("ratelimit-remaining").unwrap(), "0");
+        assert_eq!(resp.headers().get("ratelimit-reset").unwrap(), "60");
+
+        let req = TestRequest::with_header("x-forwarded-for", "127.0.0.1").finish();
+        let resp = match limiter.start(&req) {
+            Ok(Started::Response(resp)) => resp,
+            _ => panic!(),
+        };
+        assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(resp.headers().get(header::RETRY_AFTER).unwrap(), "60");
+
+        // requests without key are not limited
+        let limiter = RateLimit::new(Quota::token_bucket(1, Duration::from_secs(60)))
+            .key_by(|req: &HttpRequest| {
+                req.headers()
+                    .get("x-api-key")
+                    .and_then(|key| key.to_str().ok())
+                    .map(|key| key.to_owned())
+            });
+        let req = TestRequest::default().finish();
+        for _ in 0..2 {
+            match limiter.start(&req) {
+                Ok(Started::Done) => (),
+                _ => panic!(),
+            }
+        }
+        let req = TestRequest::with_header("x-api-key", "key").finish();
+        assert!(limiter.start(&req).is_ok());
+        match limiter.start(&req) {
+            Ok(Started::Response(_)) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_shared_store() {
+        let quota = Quota::token_bucket(2, Duration::from_secs(60));
+        let store: Arc<RateLimitStore> = Arc::new(MemoryStore::new());
+        // i.e. limiters of two workers
+        let limiter1 = RateLimit::with_store(quota, store.clone());
+        let limiter2 = RateLimit::with_store(quota, store.clone());
+
+        let req = TestRequest::with_header("x-forwarded-for", "127.0.0.1").finish();
+        match limiter1.start(&req) {
+            Ok(Started::Done) => (),
+            _ => panic!(),
+        }
+        match limiter2.start(&req) {
+            Ok(Started::Done) => (),
+            _ => panic!(),
+        }
+        for limiter in &[limiter1, limiter2] {
+            match limiter.start(&req) {
+                Ok(Started::Response(resp)) => {
+                    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS)
+                }
+                _ => panic!(),
+            }
+        }
+
+        // limiters with own stores count requests separately
+        let limiter1 = RateLimit::new(quota);
+        let limiter2 = RateLimit::new(quota);
+        for limiter in &[limiter1, limiter2] {
+            for _ in 0..2 {
+                match limiter.start(&req) {
+                    Ok(Started::Done) => (),
+                    _ => panic!(),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,270 @@
+//! Request identifier middleware
+//!
+//! [`RequestIdService`](struct.RequestIdService.html) middleware assigns an
+//! identifier to each request. Identifier is taken from the `X-Request-Id`
+//! request header if it is present, otherwise new random UUID (version 4)
+//! is generated. Identifier is stored in request extensions as
+//! [`RequestId`](struct.RequestId.html) and echoed in the response header.
+//!
+//! `Logger` renders the identifier with `%L` format token, and
+//! `ClientRequestBuilder::forward_request_id()` passes it to upstream
+//! requests.
+//!
+//! ```rust
+//! # extern crate actix_web;
+//! use actix_web::middleware::request_id::{RequestId, RequestIdService};
+//! use actix_web::middleware::Logger;
+//! use actix_web::App;
+//!
+//! fn index(id: RequestId) -> String {
+//!     format!("Request: {}", id)
+//! }
+//!
+//! fn main() {
+//!     let app = App::new()
+//!         .middleware(RequestIdService::new())
+//!         .middleware(Logger::new("%L %a \"%r\" %s"))
+//!         .resource("/", |r| r.with(index));
+//! }
+//! ```
+use std::fmt;
+use std::ops::Deref;
+
+use http::header::{HeaderName, HeaderValue};
+use rand::{self, Rng};
+
+use error::{Error, ErrorInternalServerError, Result};
+use handler::FromRequest;
+use httprequest::HttpRequest;
+use httpresponse::HttpResponse;
+use middleware::{Middleware, Response, Started};
+
+/// Maximum length of request identifier accepted from the client
+const MAX_LENGTH: usize = 128;
+
+/// Request identifier.
+///
+/// Identifier is available in request extensions, it could be used
+/// as an extractor as well.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId {
+    id: String,
+    header: HeaderName,
+}
+
+impl RequestId {
+    /// Request identifier
+    pub fn as_str(&self) -> &str {
+        &self.id
+    }
+
+    /// Header used for request identifier
+    pub fn header(&self) -> &HeaderName {
+        &self.header
+    }
+}
+
+impl Deref for RequestId {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.id
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.id.fmt(f)
+    }
+}
+
+impl<S> FromRequest<S> for RequestId {
+    type Config = ();
+    type Result = Result<Self, Error>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest<S>, _: &Self::Config) -> Self::Result {
+        req.extensions()
+            .get::<RequestId>()
+            .cloned()
+            .ok_or_else(|| ErrorInternalServerError("Request id is not assigned"))
+    }
+}
+
+/// Generate random UUID, version 4
+pub fn uuid4() -> String {
+    let mut b = [0u8; 16];
+    rand::thread_rng().fill(&mut b);
+    b[6] = (b[6] & 0x0f) | 0x40;
+    b[8] = (b[8] & 0x3f) | 0x80;
+    format!(
+        "{:02x}{:02x}{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}-\
+         {:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
+        b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7], b[8], b[9], b[10], b[11],
+        b[12], b[13], b[14], b[15]
+    )
+}
+
+/// `Middleware` which assigns identifier to requests.
+pub struct RequestIdService {
+    header: HeaderName,
+    generator: Box<Fn() -> String>,
+    use_incoming: bool,
+}
+
+impl Default for RequestIdService {
+    fn default() -> RequestIdService {
+        RequestIdService::new()
+    }
+}
+
+impl RequestIdService {
+    /// Create middleware with `X-Request-Id` header and UUID identifiers
+    pub fn new() -> RequestIdService {
+        RequestIdService {
+            header: HeaderName::from_static("x-request-id"),
+            generator: Box::new(uuid4),
+            use_incoming: true,
+        }
+    }
+
+    /// Set header used for request identifier.
+    ///
+    /// Panics if header name is not valid.
+    pub fn header(mut self, name: &str) -> Self {
+        self.header = HeaderName::from_bytes(name.as_bytes()).expect("Invalid header");
+        self
+    }
+
+    /// Use function for generating identifiers
+    pub fn generator<F>(mut self, f: F) -> Self
+    where
+        F: Fn() -> String + 'static,
+    {
+        self.generator = Box::new(f);
+        self
+    }
+
+    /// Accept identifier from the request header.
+    ///
+    /// Identifier is accepted if it is not longer than 128 characters and
+    /// consists of alphanumeric characters, `-`, `_`, `.`, `:` and `/`.
+    /// By default this is enabled.
+    pub fn use_incoming(mut self, enabled: bool) -> Self {
+        self.use_incoming = enabled;
+        self
+    }
+
+    fn incoming<S>(&self, req: &HttpRequest<S>) -> Option<String> {
+        if !self.use_incoming {
+            return None;
+        }
+        let val = req.headers().get(&self.header)?.to_str().ok()?;
+        let valid = !val.is_empty()
+            && val.len() <= MAX_LENGTH
+            && val.bytes().all(|b| match b {
+                b'0'..=b'9' | b'a'..=b'z' | b'A'..=b'Z' => true,
+                b'-' | b'_' | b'.' | b':' | b'/' => true,
+                _ => false,
+            });
+        if valid {
+            Some(val.to_owned())
+        } else {
+            None
+        }
+    }
+}
+
+impl<S> Middleware<S> for RequestIdService {
+    fn start(&self, req: &HttpRequest<S>) -> Result<Started> {
+        if req.extensions().get::<RequestId>().is_none() {
+            let id = self
+                .incoming(req)
+                .unwrap_or_else(|| (self.generator)());
+            req.extensions_mut().insert(RequestId {
+                id,
+                header: self.header.clone(),
+            });
+        }
+        Ok(Started::Done)
+    }
+
+    fn response(
+        &self, req: &HttpRequest<S>, mut resp: HttpResponse,
+    ) -> Result<Response> {
+        if let Some(id) = req.extensions().get::<RequestId>() {
+            if !resp.headers().contains_key(&id.header) {
+                if let Ok(val) = HeaderValue::from_str(&id.id) {
+                    resp.headers_mut().insert(id.header.clone(), val);
+                }
+            }
+        }
+        Ok(Response::Done(resp))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test::TestRequest;
+
+    fn process(mw: &RequestIdService, req: &HttpRequest) -> HttpResponse {
+        let _ = mw.start(req).unwrap();
+        match mw.response(req, HttpResponse::Ok().finish()) {
+            Ok(Response::Done(resp)) => resp,
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_uuid4() {
+        let id = uuid4();
+        assert_eq!(id.len(), 36);
+        assert_eq!(&id[14..15], "4");
+        assert!("89ab".contains(&id[19..20]));
+        assert_ne!(id, uuid4());
+    }
+
+    #[test]
+    fn test_generated() {
+        let mw = RequestIdService::new();
+        let req = TestRequest::default().finish();
+        let resp = process(&mw, &req);
+
+        let id = RequestId::extract(&req).unwrap();
+        assert_eq!(id.len(), 36);
+        assert_eq!(resp.headers().get("x-request-id").unwrap(), id.as_str());
+    }
+
+    #[test]
+    fn test_incoming() {
+        let mw = RequestIdService::new();
+        let req = TestRequest::with_header("x-request-id", "abc-123").finish();
+        let resp = process(&mw, &req);
+        assert_eq!(resp.headers().get("x-request-id").unwrap(), "abc-123");
+
+        // invalid identifier is replaced
+        let req = TestRequest::with_header("x-request-id", "abc 123").finish();
+        let resp = process(&mw, &req);
+        assert_eq!(resp.headers().get("x-request-id").unwrap().len(), 36);
+
+        let mw = RequestIdService::new().use_incoming(false);
+        let req = TestRequest::with_header("x-request-id", "abc-123").finish();
+        let resp = process(&mw, &req);
+        assert_ne!(resp.headers().get("x-request-id").unwrap(), "abc-123");
+    }
+
+    #[test]
+    fn test_custom() {
+        let mw = RequestIdService::new()
+            .header("X-Correlation-Id")
+            .generator(|| "custom".to_owned());
+        let req = TestRequest::default().finish();
+        let resp = process(&mw, &req);
+        assert_eq!(resp.headers().get("x-correlation-id").unwrap(), "custom");
+        assert!(resp.headers().get("x-request-id").is_none());
+
+        let req = TestRequest::default().finish();
+        assert!(RequestId::extract(&req).is_err());
+    }
+}
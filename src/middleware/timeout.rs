@@ -0,0 +1,176 @@
+//! Request processing timeout middleware
+use std::time::{Duration, Instant};
+
+use futures::{Async, Future, Poll};
+use http::StatusCode;
+use tokio_timer::Delay;
+
+use error::{Error, Result};
+use httprequest::HttpRequest;
+use httpresponse::HttpResponse;
+use middleware::{Middleware, Started};
+
+/// `Middleware` for bounding request processing time.
+///
+/// If handler's future does not resolve within the timeout, the future
+/// is dropped and response with `504 Gateway Timeout` status is
+/// returned instead. Status of the response could be changed with
+/// `Timeout::status()` method.
+///
+/// Middleware could be registered for application, scope or resource,
+/// the earliest deadline wins if several timeouts apply to a request.
+/// `Logger` renders timed out requests with `%X` format token.
+///
+/// ```rust
+/// # extern crate actix_web;
+/// use actix_web::middleware::Timeout;
+/// use actix_web::{http, App, HttpResponse};
+/// use std::time::Duration;
+///
+/// fn main() {
+///     let app = App::new()
+///         .middleware(Timeout::new(Duration::from_secs(30)))
+///         .resource("/upstream", |r| {
+///             r.middleware(
+///                 Timeout::new(Duration::from_secs(5))
+///                     .status(http::StatusCode::SERVICE_UNAVAILABLE),
+///             );
+///             r.f(|_| HttpResponse::Ok())
+///         })
+///         .finish();
+/// }
+/// ```
+pub struct Timeout {
+    timeout: Duration,
+    status: StatusCode,
+}
+
+impl Timeout {
+    /// Create `Timeout` middleware with the specified timeout
+    pub fn new(timeout: Duration) -> Timeout {
+        Timeout {
+            timeout,
+            status: StatusCode::GATEWAY_TIMEOUT,
+        }
+    }
+
+    /// Set status of the response for timed out requests.
+    ///
+    /// By default `504 Gateway Timeout` status is used.
+    pub fn status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+}
+
+/// Deadline of request processing
+struct Deadline {
+    at: Instant,
+    status: StatusCode,
+}
+
+/// Request processing timed out
+pub(crate) struct TimedOut;
+
+impl<S> Middleware<S> for Timeout {
+    fn start(&self, req: &HttpRequest<S>) -> Result<Started> {
+        let at = Instant::now() + self.timeout;
+        let mut ext = req.extensions_mut();
+        if ext.get::<Deadline>().map_or(true, |d| at < d.at) {
+            ext.insert(Deadline {
+                at,
+                status: self.status,
+            });
+        }
+        Ok(Started::Done)
+    }
+}
+
+/// Bound handler's future with request's deadline
+pub(crate) fn with_deadline<S: 'static>(
+    req: &HttpRequest<S>, fut: Box<Future<Item = HttpResponse, Error = Error>>,
+) -> Box<Future<Item = HttpResponse, Error = Error>> {
+    let (at, status) = match req.extensions().get::<Deadline>() {
+        Some(deadline) => (deadline.at, deadline.status),
+        None => return fut,
+    };
+    Box::new(DeadlineFut {
+        fut,
+        status,
+        delay: Delay::new(at),
+        req: req.clone(),
+    })
+}
+
+struct DeadlineFut<S> {
+    fut: Box<Future<Item = HttpResponse, Error = Error>>,
+    delay: Delay,
+    status: StatusCode,
+    req: HttpRequest<S>,
+}
+
+impl<S> Future for DeadlineFut<S> {
+    type Item = HttpResponse;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<HttpResponse, Error> {
+        if let Async::Ready(resp) = self.fut.poll()? {
+            return Ok(Async::Ready(resp));
+        }
+        match self.delay.poll() {
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Ok(Async::Ready(_)) | Err(_) => {
+                debug!("Request processing timed out: {}", self.req.path());
+                self.req.extensions_mut().insert(TimedOut);
+                Ok(Async::Ready(self.req.build_response(self.status).finish()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::future;
+    use std::time::Duration;
+    use test::TestRequest;
+    use tokio::runtime::current_thread::Runtime;
+
+    #[test]
+    fn test_timeout() {
+        let mut rt = Runtime::new().unwrap();
+        let req = TestRequest::default().finish();
+
+        // no deadline
+        let fut = with_deadline(&req, Box::new(future::empty()));
+        let err = rt.block_on(fut.select2(Delay::new(
+            Instant::now() + Duration::from_millis(50),
+        )));
+        assert!(err.is_ok());
+
+        let mw = Timeout::new(Duration::from_millis(10));
+        let _ = Middleware::start(&mw, &req);
+        let fut = with_deadline(&req, Box::new(future::empty()));
+        let resp = rt.block_on(fut).unwrap();
+        assert_eq!(resp.status(), StatusCode::GATEWAY_TIMEOUT);
+        assert!(req.extensions().get::<TimedOut>().is_some());
+
+        // earliest deadline wins
+        let req = TestRequest::default().finish();
+        let mw = Timeout::new(Duration::from_millis(10))
+            .status(StatusCode::SERVICE_UNAVAILABLE);
+        let _ = Middleware::start(&mw, &req);
+        let _ = Middleware::start(&Timeout::new(Duration::from_secs(10)), &req);
+        let fut = with_deadline(&req, Box::new(future::empty()));
+        let resp = rt.block_on(fut).unwrap();
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        // handler responds in time
+        let req = TestRequest::default().finish();
+        let _ = Middleware::start(&Timeout::new(Duration::from_secs(10)), &req);
+        let fut = with_deadline(&req, Box::new(future::ok(HttpResponse::Ok().finish())));
+        let resp = rt.block_on(fut).unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(req.extensions().get::<TimedOut>().is_none());
+    }
+}
@@ -0,0 +1,731 @@
+//! OpenAPI document generation
+//!
+//! Document is generated from application route table. Request parameters
+//! and body are derived from extractors of `Route::with()` handlers,
+//! `Path<T>`, `Query<T>`, `Json<T>` and `Form<T>` extractor types are traced
+//! with their `Deserialize` implementation. Summaries, tags and responses
+//! are described with `Route::openapi()` annotations. Routes without
+//! method predicates are not included in the document.
+//!
+//! Module is available with `openapi` feature enabled.
+//!
+//! ```rust
+//! # extern crate actix_web;
+//! # extern crate serde_json;
+//! # #[macro_use] extern crate serde_derive;
+//! use actix_web::openapi::{schema_of, OpenApi, Schema};
+//! use actix_web::{http, App, Json, Path, Query};
+//! use serde_json::Value;
+//!
+//! #[derive(Deserialize, Serialize)]
+//! struct User {
+//!     name: String,
+//! }
+//!
+//! impl Schema for User {
+//!     fn schema() -> Value {
+//!         schema_of::<User>()
+//!     }
+//! }
+//!
+//! #[derive(Deserialize)]
+//! struct Paging {
+//!     offset: Option<usize>,
+//! }
+//!
+//! fn users(info: (Path<u32>, Query<Paging>)) -> Json<Vec<User>> {
+//!     Json(Vec::new())
+//! }
+//!
+//! fn main() {
+//!     let app = App::new()
+//!         .resource("/{group}/users", |r| {
+//!             r.method(http::Method::GET)
+//!                 .openapi(|op| {
+//!                     op.summary("List users of the group")
+//!                         .tag("users")
+//!                         .response_with::<Json<Vec<User>>>(
+//!                             http::StatusCode::OK,
+//!                             "Users of the group",
+//!                         );
+//!                 }).with(users)
+//!         })
+//!         // serve document as json
+//!         .openapi("/openapi.json", OpenApi::new("Users", "1.0.0"))
+//!         .finish();
+//! }
+//! ```
+use std::fmt;
+
+use bytes::Bytes;
+use http::StatusCode;
+use serde_json::{Map, Value};
+
+use body::Binary;
+use httpresponse::HttpResponse;
+use json::Json;
+use router::RouteTable;
+
+mod schema;
+pub use self::schema::{schema_of, Schema};
+
+/// Description of route handler extractors.
+///
+/// Extractors are described with `FromRequest::describe_api()` when
+/// document is generated, not when route is registered.
+#[derive(Clone, Copy)]
+pub(crate) struct ExtractorApi(fn(&mut Operation));
+
+impl ExtractorApi {
+    pub(crate) fn new(describe: fn(&mut Operation)) -> ExtractorApi {
+        ExtractorApi(describe)
+    }
+
+    fn describe(&self, op: &mut Operation) {
+        (self.0)(op)
+    }
+}
+
+impl fmt::Debug for ExtractorApi {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ExtractorApi")
+    }
+}
+
+/// Methods supported by OpenAPI path item
+const METHODS: &[&str] = &[
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// Responder type with response body schema.
+///
+/// Used by `Operation::response_with()` method.
+pub trait ResponseSchema {
+    /// Content type and schema of response body, `None` for empty body
+    fn content() -> Option<(&'static str, Value)>;
+}
+
+impl<T: Schema> ResponseSchema for Json<T> {
+    fn content() -> Option<(&'static str, Value)> {
+        Some(("application/json", T::schema()))
+    }
+}
+
+impl ResponseSchema for String {
+    fn content() -> Option<(&'static str, Value)> {
+        Some(("text/plain", String::schema()))
+    }
+}
+
+impl ResponseSchema for &'static str {
+    fn content() -> Option<(&'static str, Value)> {
+        Some(("text/plain", String::schema()))
+    }
+}
+
+impl ResponseSchema for Bytes {
+    fn content() -> Option<(&'static str, Value)> {
+        Some(("application/octet-stream", Bytes::schema()))
+    }
+}
+
+impl ResponseSchema for Binary {
+    fn content() -> Option<(&'static str, Value)> {
+        Some(("application/octet-stream", Bytes::schema()))
+    }
+}
+
+impl ResponseSchema for HttpResponse {
+    fn content() -> Option<(&'static str, Value)> {
+        None
+    }
+}
+
+impl<T: ResponseSchema> ResponseSchema for Option<T> {
+    fn content() -> Option<(&'static str, Value)> {
+        T::content()
+    }
+}
+
+impl<T: ResponseSchema, E> ResponseSchema for Result<T, E> {
+    fn content() -> Option<(&'static str, Value)> {
+        T::content()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Parameter {
+    name: String,
+    required: bool,
+    schema: Value,
+}
+
+#[derive(Debug, Clone)]
+struct Response {
+    status: StatusCode,
+    description: String,
+    content: Option<(&'static str, Value)>,
+}
+
+/// OpenAPI operation of a route.
+///
+/// Operation is described by extractors of the route handler, see
+/// `FromRequest::describe_api()`, and by `Route::openapi()` annotations.
+#[derive(Debug, Clone, Default)]
+pub struct Operation {
+    summary: Option<String>,
+    description: Option<String>,
+    operation_id: Option<String>,
+    tags: Vec<String>,
+    deprecated: bool,
+    hidden: bool,
+    path: Option<Value>,
+    query: Vec<Parameter>,
+    body: Option<(&'static str, Value)>,
+    responses: Vec<Response>,
+}
+
+impl Operation {
+    /// Set short summary of the operation
+    pub fn summary(&mut self, summary: &str) -> &mut Self {
+        self.summary = Some(summary.to_owned());
+        self
+    }
+
+    /// Set description of the operation
+    pub fn description(&mut self, description: &str) -> &mut Self {
+        self.description = Some(description.to_owned());
+        self
+    }
+
+    /// Set unique identifier of the operation
+    pub fn operation_id(&mut self, id: &str) -> &mut Self {
+        self.operation_id = Some(id.to_owned());
+        self
+    }
+
+    /// Add tag to the operation
+    pub fn tag(&mut self, tag: &str) -> &mut Self {
+        self.tags.push(tag.to_owned());
+        self
+    }
+
+    /// Mark operation as deprecated
+    pub fn deprecated(&mut self) -> &mut Self {
+        self.deprecated = true;
+        self
+    }
+
+    /// Exclude route from OpenAPI document
+    pub fn hidden(&mut self) -> &mut Self {
+        self.hidden = true;
+        self
+    }
+
+    /// Add response without body.
+    ///
+    /// If no responses are added, document lists *200 OK* response.
+    pub fn response(&mut self, status: StatusCode, description: &str) -> &mut Self {
+        self.responses.push(Response {
+            status,
+            description: description.to_owned(),
+            content: None,
+        });
+        self
+    }
+
+    /// Add response with body of the responder type
+    pub fn response_with<R: ResponseSchema>(
+        &mut self, status: StatusCode, description: &str,
+    ) -> &mut Self {
+        self.responses.push(Response {
+            status,
+            description: description.to_owned(),
+            content: R::content(),
+        });
+        self
+    }
+
+    /// Set schema of path parameters.
+    ///
+    /// Object properties are matched with parameters by name, tuple
+    /// items by position. Parameters without schema are strings.
+    pub fn path_schema(&mut self, schema: Value) -> &mut Self {
+        self.path = Some(schema);
+        self
+    }
+
+    /// Add query parameters, one for each property of the object schema
+    pub fn query_schema(&mut self, schema: &Value) -> &mut Self {
+        let required = schema.get("required").and_then(|r| r.as_array());
+        if let Some(props) = schema.get("properties").and_then(|p| p.as_object()) {
+            for (name, schema) in props {
+                let name = Value::String(name.clone());
+                self.query.push(Parameter {
+                    name: name.as_str().unwrap().to_owned(),
+                    required: required.map_or(false, |r| r.contains(&name)),
+                    schema: schema.clone(),
+                });
+            }
+        }
+        self
+    }
+
+    /// Set content type and schema of request body
+    pub fn request_body(
+        &mut self, content_type: &'static str, schema: Value,
+    ) -> &mut Self {
+        self.body = Some((content_type, schema));
+        self
+    }
+
+    pub(crate) fn merge(&mut self, other: &Operation) {
+        if other.path.is_some() {
+            self.path = other.path.clone();
+        }
+        if other.body.is_some() {
+            self.body = other.body.clone();
+        }
+        self.query.extend(other.query.iter().cloned());
+    }
+
+    fn path_param(&self, name: &str, idx: usize) -> Value {
+        let schema = match self.path {
+            Some(ref schema) => schema,
+            None => return json!({"type": "string"}),
+        };
+        let param = if let Some(props) = schema.get("properties") {
+            props.get(name)
+        } else if schema.get("minItems").is_some() {
+            // tuple
+            let items = &schema["items"];
+            match items.get("oneOf").and_then(|items| items.as_array()) {
+                Some(items) => items.get(idx),
+                None => Some(items),
+            }
+        } else if idx == 0 {
+            Some(schema)
+        } else {
+            None
+        };
+        match param {
+            Some(param) if param.get("type").is_some() => param.clone(),
+            _ => json!({"type": "string"}),
+        }
+    }
+
+    fn to_json(
+        &self, params: &[(String, Option<String>)], routes: &RouteTable,
+    ) -> Value {
+        let mut op = Map::new();
+        if let Some(ref summary) = self.summary {
+            op.insert("summary".to_owned(), json!(summary));
+        }
+        if let Some(ref description) = self.description {
+            op.insert("description".to_owned(), json!(description));
+        }
+        if let Some(ref id) = self.operation_id {
+            op.insert("operationId".to_owned(), json!(id));
+        }
+        if !self.tags.is_empty() {
+            op.insert("tags".to_owned(), json!(self.tags));
+        }
+        if self.deprecated {
+            op.insert("deprecated".to_owned(), json!(true));
+        }
+
+        let mut parameters = Vec::new();
+        for (idx, &(ref name, ref pattern)) in params.iter().enumerate() {
+            let mut schema = self.path_param(name, idx);
+            if let Some(ref pattern) = *pattern {
+                if let Some(conv) = converter_schema(pattern) {
+                    schema = conv;
+                } else if schema["type"] == "string" {
+                    let re = match routes.converter(pattern) {
+                        Some(conv) => conv.regex().to_owned(),
+                        None => pattern.clone(),
+                    };
+                    schema["pattern"] = json!(format!("^{}$", re));
+                }
+            }
+            parameters.push(json!({
+                "name": name, "in": "path", "required": true, "schema": schema,
+            }));
+        }
+        for param in &self.query {
+            parameters.push(json!({
+                "name": param.name,
+                "in": "query",
+                "required": param.required,
+                "schema": param.schema,
+            }));
+        }
+        if !parameters.is_empty() {
+            op.insert("parameters".to_owned(), Value::Array(parameters));
+        }
+
+        if let Some((ctype, ref schema)) = self.body {
+            op.insert(
+                "requestBody".to_owned(),
+                json!({"required": true, "content": {ctype: {"schema": schema}}}),
+            );
+        }
+
+        let mut responses = Map::new();
+        for resp in &self.responses {
+            let mut item = json!({ "description": resp.description });
+            if let Some((ctype, ref schema)) = resp.content {
+                item["content"] = json!({ ctype: {"schema": schema} });
+            }
+            responses.insert(resp.status.as_u16().to_string(), item);
+        }
+        if responses.is_empty() {
+            responses.insert("200".to_owned(), json!({"description": "OK"}));
+        }
+        op.insert("responses".to_owned(), Value::Object(responses));
+        Value::Object(op)
+    }
+}
+
+/// Schema of parameter with built-in converter
+fn converter_schema(name: &str) -> Option<Value> {
+    let schema = match name {
+        "u8" => u8::schema(),
+        "u16" => u16::schema(),
+        "u32" => u32::schema(),
+        "u64" => u64::schema(),
+        "usize" => usize::schema(),
+        "i8" => i8::schema(),
+        "i16" => i16::schema(),
+        "i32" => i32::schema(),
+        "i64" => i64::schema(),
+        "isize" => isize::schema(),
+        "uuid" => json!({"type": "string", "format": "uuid"}),
+        "*" => String::schema(),
+        _ => return None,
+    };
+    Some(schema)
+}
+
+/// Convert resource pattern to OpenAPI path template,
+/// returns names and custom regex of path parameters
+fn path_template(pattern: &str) -> (String, Vec<(String, Option<String>)>) {
+    let mut path = String::new();
+    let mut params = Vec::new();
+    let mut rest = pattern;
+    while let Some(start) = rest.find('{') {
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => break,
+        };
+        path.push_str(&rest[..start]);
+        let param = &rest[start + 1..end];
+        let (name, re) = match param.find(':') {
+            Some(pos) => (&param[..pos], Some(param[pos + 1..].trim().to_owned())),
+            None => (param, None),
+        };
+        path.push('{');
+        path.push_str(name);
+        path.push('}');
+        params.push((name.to_owned(), re));
+        rest = &rest[end + 1..];
+    }
+    path.push_str(rest);
+    if path.is_empty() {
+        path.push('/');
+    }
+    (path, params)
+}
+
+/// OpenAPI document builder.
+///
+/// See module documentation for example.
+#[derive(Debug, Clone)]
+pub struct OpenApi {
+    title: String,
+    version: String,
+    description: Option<String>,
+    servers: Vec<String>,
+}
+
+impl OpenApi {
+    /// Create document builder with API title and version
+    pub fn new(title: &str, version: &str) -> OpenApi {
+        OpenApi {
+            title: title.to_owned(),
+            version: version.to_owned(),
+            description: None,
+            servers: Vec::new(),
+        }
+    }
+
+    /// Set description of the API
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_owned());
+        self
+    }
+
+    /// Add server url
+    pub fn server(mut self, url: &str) -> Self {
+        self.servers.push(url.to_owned());
+        self
+    }
+
+    /// Generate OpenAPI 3 document from the route table.
+    ///
+    /// If several routes match same path and method, first route
+    /// is documented.
+    pub fn document(&self, routes: &RouteTable) -> Value {
+        let mut paths = Map::new();
+        for entry in routes {
+            let mut op = match entry.operation {
+                Some(ref op) if !op.hidden => op.clone(),
+                _ => continue,
+            };
+            let methods = match entry.methods() {
+                Some(methods) => methods,
+                None => continue,
+            };
+            if let Some(ref extractor) = entry.extractor {
+                let mut extracted = Operation::default();
+                extractor.describe(&mut extracted);
+                op.merge(&extracted);
+            }
+            let (path, params) = path_template(entry.pattern());
+            let item = paths
+                .entry(path)
+                .or_insert_with(|| Value::Object(Map::new()));
+            for method in methods {
+                let method = method.as_str().to_lowercase();
+                if METHODS.contains(&method.as_str()) && item.get(&method).is_none() {
+                    item[&method] = op.to_json(&params, routes);
+                }
+            }
+        }
+        let paths: Map<String, Value> = paths
+            .into_iter()
+            .filter(|(_, item)| item.as_object().map_or(false, |i| !i.is_empty()))
+            .collect();
+
+        let mut info = json!({"title": self.title, "version": self.version});
+        if let Some(ref description) = self.description {
+            info["description"] = json!(description);
+        }
+        let mut doc = json!({"openapi": "3.0.0", "info": info, "paths": paths});
+        if !self.servers.is_empty() {
+            let servers = self.servers.iter().map(|url| json!({ "url": url }));
+            doc["servers"] = Value::Array(servers.collect());
+        }
+        doc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use application::App;
+    use error::Error;
+    use extractor::{Path, Query};
+    use handler::FromRequest;
+    use http::Method;
+    use httprequest::HttpRequest;
+    use param::ParamConverter;
+
+    #[derive(Deserialize)]
+    struct Params {
+        id: u32,
+        name: String,
+    }
+
+    #[derive(Deserialize)]
+    struct Paging {
+        offset: Option<usize>,
+        limit: usize,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    struct User {
+        name: String,
+    }
+
+    impl Schema for User {
+        fn schema() -> Value {
+            schema_of::<User>()
+        }
+    }
+
+    fn user(_: (Path<Params>, Query<Paging>)) -> &'static str {
+        ""
+    }
+
+    fn create(_: (Path<(String,)>, Json<User>)) -> Json<User> {
+        Json(User {
+            name: String::new(),
+        })
+    }
+
+    static DESCRIBED: AtomicUsize = AtomicUsize::new(0);
+
+    struct Described;
+
+    impl<S> FromRequest<S> for Described {
+        type Config = ();
+        type Result = Result<Described, Error>;
+
+        fn from_request(_: &HttpRequest<S>, _: &()) -> Self::Result {
+            Ok(Described)
+        }
+
+        fn describe_api(op: &mut Operation) {
+            DESCRIBED.fetch_add(1, Ordering::SeqCst);
+            op.request_body("text/plain", String::schema());
+        }
+    }
+
+    #[test]
+    fn test_path_template() {
+        assert_eq!(path_template(""), ("/".to_owned(), vec![]));
+        assert_eq!(
+            path_template("/{id}/{name:[a-z]+}/index.html"),
+            (
+                "/{id}/{name}/index.html".to_owned(),
+                vec![
+                    ("id".to_owned(), None),
+                    ("name".to_owned(), Some("[a-z]+".to_owned()))
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn test_document() {
+        let app = App::new()
+            .prefix("/api")
+            .resource("/users/{id}/{name}", |r| {
+                r.get()
+                    .openapi(|op| {
+                        op.summary("Get user").tag("users").response_with::<String>(
+                            StatusCode::OK,
+                            "User name",
+                        );
+                    }).with(user)
+            }).resource("/groups/{group:[a-z]+}", |r| {
+                r.post().with(create);
+                r.method(Method::PUT).openapi(|op| {
+                    op.hidden();
+                }).f(|_| "");
+                r.route().f(|_| "");
+            }).resource("/items/{id:u64}", |r| r.get().f(|_| ""))
+            .resource("/index.html", |r| r.f(|_| ""));
+        let doc = OpenApi::new("Test", "1.0")
+            .description("Test api")
+            .server("http://localhost:8080")
+            .document(&app.routes());
+
+        assert_eq!(doc["openapi"], "3.0.0");
+        assert_eq!(
+            doc["info"],
+            json!({"title": "Test", "version": "1.0", "description": "Test api"})
+        );
+        assert_eq!(doc["servers"], json!([{"url": "http://localhost:8080"}]));
+        assert_eq!(doc["paths"].as_object().unwrap().len(), 3);
+
+        let op = &doc["paths"]["/api/users/{id}/{name}"]["get"];
+        assert_eq!(op["summary"], "Get user");
+        assert_eq!(op["tags"], json!(["users"]));
+        assert_eq!(
+            op["parameters"],
+            json!([
+                {
+                    "name": "id", "in": "path", "required": true,
+                    "schema": {"type": "integer", "format": "int32", "minimum": 0},
+                },
+                {
+                    "name": "name", "in": "path", "required": true,
+                    "schema": {"type": "string"},
+                },
+                {
+                    "name": "limit", "in": "query", "required": true,
+                    "schema": {"type": "integer", "format": "int64", "minimum": 0},
+                },
+                {
+                    "name": "offset", "in": "query", "required": false,
+                    "schema": {
+                        "type": "integer", "format": "int64",
+                        "minimum": 0, "nullable": true,
+                    },
+                },
+            ])
+        );
+        assert_eq!(
+            op["responses"],
+            json!({"200": {
+                "description": "User name",
+                "content": {"text/plain": {"schema": {"type": "string"}}},
+            }})
+        );
+
+        let item = &doc["paths"]["/api/groups/{group}"];
+        assert_eq!(item.as_object().unwrap().len(), 1);
+        let op = &item["post"];
+        assert_eq!(
+            op["parameters"],
+            json!([{
+                "name": "group", "in": "path", "required": true,
+                "schema": {"type": "string", "pattern": "^[a-z]+$"},
+            }])
+        );
+        assert_eq!(
+            op["requestBody"],
+            json!({"required": true, "content": {"application/json": {"schema": {
+                "type": "object",
+                "properties": {"name": {"type": "string"}},
+                "required": ["name"],
+            }}}})
+        );
+        assert_eq!(op["responses"], json!({"200": {"description": "OK"}}));
+
+        let op = &doc["paths"]["/api/items/{id}"]["get"];
+        assert_eq!(
+            op["parameters"][0]["schema"],
+            json!({"type": "integer", "format": "int64", "minimum": 0})
+        );
+    }
+
+    #[test]
+    fn test_document_converter() {
+        struct Hex;
+
+        impl ParamConverter for Hex {
+            fn regex(&self) -> &str {
+                "[0-9a-f]+"
+            }
+        }
+
+        let app = App::new()
+            .resource("/hash/{hash:hex}", |r| r.get().f(|_| ""))
+            .converter("hex", Hex);
+        let doc = OpenApi::new("Test", "1.0").document(&app.routes());
+        assert_eq!(
+            doc["paths"]["/hash/{hash}"]["get"]["parameters"][0]["schema"],
+            json!({"type": "string", "pattern": "^[0-9a-f]+$"})
+        );
+    }
+
+    #[test]
+    fn test_describe_on_document() {
+        let app = App::new().resource("/described", |r| {
+            r.post().with(|_: Described| "");
+        });
+        assert_eq!(DESCRIBED.load(Ordering::SeqCst), 0);
+
+        let doc = OpenApi::new("Test", "1.0").document(&app.routes());
+        assert_eq!(DESCRIBED.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            doc["paths"]["/described"]["post"]["requestBody"]["content"],
+            json!({"text/plain": {"schema": {"type": "string"}}})
+        );
+    }
+}
@@ -0,0 +1,698 @@
+use std::collections::{BTreeMap, HashMap};
+
+use bytes::Bytes;
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, Deserializer, EnumAccess, Error as DeError,
+    IntoDeserializer, MapAccess, SeqAccess, VariantAccess, Visitor,
+};
+use serde_json::{Map, Value};
+
+/// Nesting level at which tracing of recursive types stops
+const MAX_DEPTH: usize = 16;
+
+/// Type with JSON schema.
+///
+/// Schema is used for request and response bodies of OpenAPI document.
+/// Types that implement `Deserialize` could use `schema_of()` function.
+///
+/// ```rust
+/// # extern crate actix_web;
+/// # extern crate serde_json;
+/// # #[macro_use] extern crate serde_derive;
+/// use actix_web::openapi::{schema_of, Schema};
+/// use serde_json::Value;
+///
+/// #[derive(Deserialize)]
+/// struct User {
+///     name: String,
+///     age: Option<u32>,
+/// }
+///
+/// impl Schema for User {
+///     fn schema() -> Value {
+///         schema_of::<User>()
+///     }
+/// }
+/// # fn main() {}
+/// ```
+pub trait Schema {
+    /// JSON schema of the type
+    fn schema() -> Value;
+}
+
+macro_rules! simple_schema {
+    ($tp:ty, $($schema:tt)+) => {
+        impl Schema for $tp {
+            fn schema() -> Value {
+                json!($($schema)+)
+            }
+        }
+    };
+}
+
+simple_schema!(bool, {"type": "boolean"});
+simple_schema!(i8, {"type": "integer"});
+simple_schema!(i16, {"type": "integer"});
+simple_schema!(i32, {"type": "integer", "format": "int32"});
+simple_schema!(i64, {"type": "integer", "format": "int64"});
+simple_schema!(isize, {"type": "integer"});
+simple_schema!(u8, {"type": "integer", "minimum": 0});
+simple_schema!(u16, {"type": "integer", "minimum": 0});
+simple_schema!(u32, {"type": "integer", "format": "int32", "minimum": 0});
+simple_schema!(u64, {"type": "integer", "format": "int64", "minimum": 0});
+simple_schema!(usize, {"type": "integer", "minimum": 0});
+simple_schema!(f32, {"type": "number", "format": "float"});
+simple_schema!(f64, {"type": "number", "format": "double"});
+simple_schema!(char, {"type": "string", "minLength": 1, "maxLength": 1});
+simple_schema!(String, {"type": "string"});
+simple_schema!(&'static str, {"type": "string"});
+simple_schema!(Bytes, {"type": "string", "format": "binary"});
+simple_schema!(Value, {});
+
+impl<T: Schema> Schema for Option<T> {
+    fn schema() -> Value {
+        nullable(T::schema())
+    }
+}
+
+impl<T: Schema> Schema for Vec<T> {
+    fn schema() -> Value {
+        json!({"type": "array", "items": T::schema()})
+    }
+}
+
+impl<T: Schema> Schema for HashMap<String, T> {
+    fn schema() -> Value {
+        json!({"type": "object", "additionalProperties": T::schema()})
+    }
+}
+
+impl<T: Schema> Schema for BTreeMap<String, T> {
+    fn schema() -> Value {
+        json!({"type": "object", "additionalProperties": T::schema()})
+    }
+}
+
+/// Derive JSON schema of the type from its `Deserialize` implementation.
+///
+/// Struct fields become object properties, `Option` fields are not
+/// required, unit enum variants become string enumeration. Types that
+/// can not be traced, i.e. types with custom deserialization logic, get
+/// an empty schema.
+pub fn schema_of<T: DeserializeOwned>() -> Value {
+    let mut schema = Value::Null;
+    match T::deserialize(Tracer::new(&mut schema, 0)) {
+        Ok(_) => schema,
+        Err(_) => json!({}),
+    }
+}
+
+fn nullable(mut schema: Value) -> Value {
+    if let Value::Object(ref mut obj) = schema {
+        obj.insert("nullable".to_owned(), Value::Bool(true));
+    }
+    schema
+}
+
+pub(crate) fn is_nullable(schema: &Value) -> bool {
+    schema.get("nullable") == Some(&Value::Bool(true))
+}
+
+/// Deserializer which records schema of deserialized type
+/// and feeds sample values to the visitor
+struct Tracer<'a> {
+    schema: &'a mut Value,
+    depth: usize,
+}
+
+impl<'a> Tracer<'a> {
+    fn new(schema: &'a mut Value, depth: usize) -> Self {
+        Tracer { schema, depth }
+    }
+
+    fn nested<'b>(&self, schema: &'b mut Value) -> Tracer<'b> {
+        Tracer {
+            schema,
+            depth: self.depth + 1,
+        }
+    }
+
+    fn check_depth(&self) -> Result<(), de::value::Error> {
+        if self.depth > MAX_DEPTH {
+            Err(de::value::Error::custom("recursive type"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+macro_rules! trace_value {
+    ($trait_fn:ident, $visit_fn:ident, $tp:ty, $value:expr) => {
+        fn $trait_fn<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            *self.schema = <$tp as Schema>::schema();
+            visitor.$visit_fn($value)
+        }
+    };
+}
+
+impl<'de, 'a> Deserializer<'de> for Tracer<'a> {
+    type Error = de::value::Error;
+
+    trace_value!(deserialize_bool, visit_bool, bool, false);
+    trace_value!(deserialize_i8, visit_i8, i8, 0);
+    trace_value!(deserialize_i16, visit_i16, i16, 0);
+    trace_value!(deserialize_i32, visit_i32, i32, 0);
+    trace_value!(deserialize_i64, visit_i64, i64, 0);
+    trace_value!(deserialize_u8, visit_u8, u8, 0);
+    trace_value!(deserialize_u16, visit_u16, u16, 0);
+    trace_value!(deserialize_u32, visit_u32, u32, 0);
+    trace_value!(deserialize_u64, visit_u64, u64, 0);
+    trace_value!(deserialize_f32, visit_f32, f32, 0.0);
+    trace_value!(deserialize_f64, visit_f64, f64, 0.0);
+    trace_value!(deserialize_char, visit_char, char, ' ');
+    trace_value!(deserialize_str, visit_str, String, "");
+    trace_value!(deserialize_string, visit_string, String, String::new());
+    trace_value!(deserialize_bytes, visit_bytes, Bytes, b"");
+    trace_value!(deserialize_byte_buf, visit_byte_buf, Bytes, Vec::new());
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        *self.schema = json!({});
+        visitor.visit_unit()
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.depth >= MAX_DEPTH {
+            *self.schema = json!({"nullable": true});
+            return visitor.visit_none();
+        }
+        let mut inner = Value::Null;
+        let value = visitor.visit_some(self.nested(&mut inner))?;
+        *self.schema = nullable(inner);
+        Ok(value)
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        *self.schema = json!({"nullable": true});
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(
+        self, _: &'static str, visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self, _: &'static str, visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.check_depth()?;
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let mut items = vec![json!({})];
+        let value = if self.depth >= MAX_DEPTH {
+            visitor.visit_seq(TraceSeq::new(&mut items[..0], self.depth))?
+        } else {
+            visitor.visit_seq(TraceSeq::new(&mut items, self.depth))?
+        };
+        *self.schema = json!({"type": "array", "items": items.remove(0)});
+        Ok(value)
+    }
+
+    fn deserialize_tuple<V>(
+        self, len: usize, visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.check_depth()?;
+        let mut items = vec![Value::Null; len];
+        let value = visitor.visit_seq(TraceSeq::new(&mut items, self.depth))?;
+
+        // tuple items are listed in `oneOf` if item types differ
+        let items = if items.iter().all(|item| *item == items[0]) {
+            items.into_iter().next().unwrap_or_else(|| json!({}))
+        } else {
+            json!({ "oneOf": items })
+        };
+        *self.schema = json!({
+            "type": "array",
+            "items": items,
+            "minItems": len,
+            "maxItems": len,
+        });
+        Ok(value)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self, _: &'static str, len: usize, visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let mut values = [Value::Null];
+        let value = if self.depth >= MAX_DEPTH {
+            visitor.visit_map(TraceMap::new(&[], &mut values[..0], self.depth))?
+        } else {
+            visitor.visit_map(TraceMap::new(&[""], &mut values, self.depth))?
+        };
+        let [additional] = values;
+        let additional = if additional.is_null() {
+            json!({})
+        } else {
+            additional
+        };
+        *self.schema = json!({"type": "object", "additionalProperties": additional});
+        Ok(value)
+    }
+
+    fn deserialize_struct<V>(
+        self, _: &'static str, fields: &'static [&'static str], visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.check_depth()?;
+        let mut values = vec![Value::Null; fields.len()];
+        let value = visitor.visit_map(TraceMap::new(fields, &mut values, self.depth))?;
+
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        for (name, schema) in fields.iter().zip(values.into_iter()) {
+            if !is_nullable(&schema) {
+                required.push(Value::String((*name).to_owned()));
+            }
+            properties.insert((*name).to_owned(), schema);
+        }
+        let mut schema = json!({"type": "object", "properties": properties});
+        if !required.is_empty() {
+            schema["required"] = Value::Array(required);
+        }
+        *self.schema = schema;
+        Ok(value)
+    }
+
+    fn deserialize_enum<V>(
+        self, _: &'static str, variants: &'static [&'static str], visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.check_depth()?;
+        let value = visitor.visit_enum(TraceEnum {
+            variant: variants.first().cloned().unwrap_or(""),
+            depth: self.depth,
+        })?;
+        *self.schema = json!({"type": "string", "enum": variants});
+        Ok(value)
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+}
+
+/// Feeds one traced value per schema slot
+struct TraceSeq<'a> {
+    items: &'a mut [Value],
+    idx: usize,
+    depth: usize,
+}
+
+impl<'a> TraceSeq<'a> {
+    fn new(items: &'a mut [Value], depth: usize) -> Self {
+        TraceSeq {
+            items,
+            depth,
+            idx: 0,
+        }
+    }
+}
+
+impl<'de, 'a> SeqAccess<'de> for TraceSeq<'a> {
+    type Error = de::value::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.idx >= self.items.len() {
+            return Ok(None);
+        }
+        let schema = &mut self.items[self.idx];
+        self.idx += 1;
+        seed.deserialize(Tracer::new(schema, self.depth + 1))
+            .map(Some)
+    }
+}
+
+/// Feeds struct fields, traces schema of each field value
+struct TraceMap<'a> {
+    fields: &'a [&'static str],
+    values: &'a mut [Value],
+    idx: usize,
+    depth: usize,
+}
+
+impl<'a> TraceMap<'a> {
+    fn new(fields: &'a [&'static str], values: &'a mut [Value], depth: usize) -> Self {
+        TraceMap {
+            fields,
+            values,
+            depth,
+            idx: 0,
+        }
+    }
+}
+
+impl<'de, 'a> MapAccess<'de> for TraceMap<'a> {
+    type Error = de::value::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if self.idx >= self.fields.len() || self.idx >= self.values.len() {
+            return Ok(None);
+        }
+        let mut schema = Value::Null;
+        seed.deserialize(KeyTracer {
+            name: self.fields[self.idx],
+            inner: Tracer::new(&mut schema, self.depth + 1),
+        }).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let schema = &mut self.values[self.idx];
+        self.idx += 1;
+        seed.deserialize(Tracer::new(schema, self.depth + 1))
+    }
+}
+
+/// Deserializes field names as strings, map keys of other types are traced
+struct KeyTracer<'a> {
+    name: &'static str,
+    inner: Tracer<'a>,
+}
+
+macro_rules! forward_to_tracer {
+    ($($trait_fn:ident)*) => {
+        $(
+            fn $trait_fn<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                self.inner.$trait_fn(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de, 'a> Deserializer<'de> for KeyTracer<'a> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_str(self.name)
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_str(self.name)
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_str(self.name)
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_str(self.name)
+    }
+
+    forward_to_tracer! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32
+        deserialize_i64 deserialize_u8 deserialize_u16 deserialize_u32
+        deserialize_u64 deserialize_f32 deserialize_f64 deserialize_char
+        deserialize_bytes deserialize_byte_buf deserialize_option
+        deserialize_unit deserialize_seq deserialize_map deserialize_ignored_any
+    }
+
+    fn deserialize_unit_struct<V>(
+        self, name: &'static str, visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner.deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self, name: &'static str, visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner.deserialize_newtype_struct(name, visitor)
+    }
+
+    fn deserialize_tuple<V>(
+        self, len: usize, visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self, name: &'static str, len: usize, visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner.deserialize_tuple_struct(name, len, visitor)
+    }
+
+    fn deserialize_struct<V>(
+        self, name: &'static str, fields: &'static [&'static str], visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner.deserialize_struct(name, fields, visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self, name: &'static str, variants: &'static [&'static str], visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner.deserialize_enum(name, variants, visitor)
+    }
+}
+
+/// Selects first enum variant
+struct TraceEnum {
+    variant: &'static str,
+    depth: usize,
+}
+
+impl<'de> EnumAccess<'de> for TraceEnum {
+    type Error = de::value::Error;
+    type Variant = TraceVariant;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, TraceVariant), Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let deserializer: de::value::StrDeserializer<Self::Error> =
+            self.variant.into_deserializer();
+        let value = seed.deserialize(deserializer)?;
+        Ok((value, TraceVariant { depth: self.depth }))
+    }
+}
+
+/// Traces variant content, schema of variant content is not recorded
+struct TraceVariant {
+    depth: usize,
+}
+
+impl<'de> VariantAccess<'de> for TraceVariant {
+    type Error = de::value::Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let mut schema = Value::Null;
+        seed.deserialize(Tracer::new(&mut schema, self.depth + 1))
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let mut schema = Value::Null;
+        Tracer::new(&mut schema, self.depth + 1).deserialize_tuple(len, visitor)
+    }
+
+    fn struct_variant<V>(
+        self, fields: &'static [&'static str], visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let mut schema = Value::Null;
+        Tracer::new(&mut schema, self.depth + 1).deserialize_struct("", fields, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Info {
+        name: String,
+        age: Option<u32>,
+        tags: Vec<String>,
+    }
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    enum Color {
+        Red,
+        Green,
+    }
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Node {
+        children: Vec<Node>,
+    }
+
+    #[test]
+    fn test_schema_of_struct() {
+        assert_eq!(
+            schema_of::<Info>(),
+            json!({
+                "type": "object",
+                "properties": {
+                    "name": {"type": "string"},
+                    "age": {
+                        "type": "integer", "format": "int32",
+                        "minimum": 0, "nullable": true,
+                    },
+                    "tags": {"type": "array", "items": {"type": "string"}},
+                },
+                "required": ["name", "tags"],
+            })
+        );
+    }
+
+    #[test]
+    fn test_schema_of_enum() {
+        assert_eq!(
+            schema_of::<Color>(),
+            json!({"type": "string", "enum": ["Red", "Green"]})
+        );
+    }
+
+    #[test]
+    fn test_schema_of_tuple() {
+        assert_eq!(
+            schema_of::<(String, String)>(),
+            json!({
+                "type": "array", "items": {"type": "string"},
+                "minItems": 2, "maxItems": 2,
+            })
+        );
+        assert_eq!(
+            schema_of::<(String, bool)>(),
+            json!({
+                "type": "array",
+                "items": {"oneOf": [{"type": "string"}, {"type": "boolean"}]},
+                "minItems": 2, "maxItems": 2,
+            })
+        );
+    }
+
+    #[test]
+    fn test_schema_of_recursive() {
+        let schema = schema_of::<Node>();
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["properties"]["children"]["type"], "array");
+    }
+
+    #[test]
+    fn test_schema_impls() {
+        assert_eq!(
+            <Option<Vec<u8>>>::schema(),
+            json!({
+                "type": "array", "nullable": true,
+                "items": {"type": "integer", "minimum": 0},
+            })
+        );
+        assert_eq!(
+            <HashMap<String, bool>>::schema(),
+            json!({"type": "object", "additionalProperties": {"type": "boolean"}})
+        );
+    }
+}
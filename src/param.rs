@@ -1,10 +1,14 @@
 use std;
+use std::collections::HashMap;
+use std::fmt;
 use std::ops::Index;
 use std::path::PathBuf;
 use std::rc::Rc;
 use std::str::FromStr;
+use std::sync::Arc;
 
 use http::StatusCode;
+use regex::Regex;
 use smallvec::SmallVec;
 
 use error::{InternalError, ResponseError, UriSegmentError};
@@ -265,6 +269,216 @@ FROM_STR!(std::net::SocketAddr);
 FROM_STR!(std::net::SocketAddrV4);
 FROM_STR!(std::net::SocketAddrV6);
 
+/// Typed path parameter converter.
+///
+/// Converter is referenced by name in resource pattern, i.e. `{id:u64}`.
+/// It constrains matching of the path segment with its regex and checks
+/// matched value, so `FromParam` and `Path<T>` conversions of the value
+/// do not fail in the handler. Converted value is not stored, handler
+/// converts string value of the parameter as usual. Values passed to
+/// `url_for()` are checked as well.
+///
+/// Built-in converters are `u8`, `u16`, `u32`, `u64`, `usize`, `i8`,
+/// `i16`, `i32`, `i64`, `isize`, `uuid` and `*`, the last one matches
+/// rest of the path including slashes. Values of other converters are
+/// matched within one path segment, so converter regex should not match
+/// `/`. Custom converters are registered with `App::converter()`.
+///
+/// ```rust
+/// # extern crate actix_web;
+/// use actix_web::dev::ParamConverter;
+/// use actix_web::{App, Path};
+///
+/// struct Hex;
+///
+/// impl ParamConverter for Hex {
+///     fn regex(&self) -> &str {
+///         "[0-9a-f]+"
+///     }
+///
+///     fn is_valid(&self, value: &str) -> bool {
+///         value.len() <= 16
+///     }
+/// }
+///
+/// fn index(info: Path<(u64, String)>) -> String {
+///     format!("{} {}", info.0, info.1)
+/// }
+///
+/// fn main() {
+///     let app = App::new()
+///         .resource("/{id:u64}/{hash:hex}", |r| r.with(index))
+///         .converter("hex", Hex)
+///         .finish();
+/// }
+/// ```
+pub trait ParamConverter: Send + Sync + 'static {
+    /// Regex matched by parameter values
+    fn regex(&self) -> &str;
+
+    /// Check matched value, i.e. range of a numeric type.
+    ///
+    /// Default implementation accepts any value matched by regex.
+    fn is_valid(&self, value: &str) -> bool {
+        let _ = value;
+        true
+    }
+}
+
+struct BuiltinConverter {
+    regex: &'static str,
+    check: fn(&str) -> bool,
+}
+
+impl ParamConverter for BuiltinConverter {
+    fn regex(&self) -> &str {
+        self.regex
+    }
+
+    fn is_valid(&self, value: &str) -> bool {
+        (self.check)(value)
+    }
+}
+
+macro_rules! int_converter {
+    ($map:ident, $type:ty, $regex:expr) => {
+        $map.insert(
+            stringify!($type).to_owned(),
+            Converter::new(
+                stringify!($type),
+                Arc::new(BuiltinConverter {
+                    regex: $regex,
+                    check: |v| <$type as FromStr>::from_str(v).is_ok(),
+                }),
+            ),
+        );
+    };
+}
+
+fn builtin_converters() -> HashMap<String, Converter> {
+    let mut map = HashMap::new();
+    int_converter!(map, u8, "[0-9]+");
+    int_converter!(map, u16, "[0-9]+");
+    int_converter!(map, u32, "[0-9]+");
+    int_converter!(map, u64, "[0-9]+");
+    int_converter!(map, usize, "[0-9]+");
+    int_converter!(map, i8, "-?[0-9]+");
+    int_converter!(map, i16, "-?[0-9]+");
+    int_converter!(map, i32, "-?[0-9]+");
+    int_converter!(map, i64, "-?[0-9]+");
+    int_converter!(map, isize, "-?[0-9]+");
+    map.insert(
+        "uuid".to_owned(),
+        Converter::new(
+            "uuid",
+            Arc::new(BuiltinConverter {
+                regex: "[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-\
+                        [0-9a-fA-F]{4}-[0-9a-fA-F]{12}",
+                check: |_| true,
+            }),
+        ),
+    );
+    map.insert(
+        "*".to_owned(),
+        Converter::new(
+            "*",
+            Arc::new(BuiltinConverter {
+                regex: ".*",
+                check: |_| true,
+            }),
+        ),
+    );
+    map
+}
+
+lazy_static! {
+    static ref BUILTIN_CONVERTERS: HashMap<String, Converter> = builtin_converters();
+}
+
+/// Path parameter converters registered on application
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Converters(HashMap<String, Converter>);
+
+impl Converters {
+    /// Register named converter, replaces previously registered converter.
+    ///
+    /// Panics if name is a name of built-in converter or if converter
+    /// regex is wrong.
+    pub(crate) fn register(&mut self, name: &str, converter: Arc<ParamConverter>) {
+        if BUILTIN_CONVERTERS.contains_key(name) {
+            panic!("Can not replace built-in path converter: {}", name);
+        }
+        self.0
+            .insert(name.to_owned(), Converter::new(name, converter));
+    }
+
+    /// Check if converter is registered, built-in converters are not checked
+    pub(crate) fn contains(&self, name: &str) -> bool {
+        self.0.contains_key(name)
+    }
+
+    /// Find built-in or registered converter
+    pub(crate) fn get(&self, name: &str) -> Option<Converter> {
+        BUILTIN_CONVERTERS
+            .get(name)
+            .or_else(|| self.0.get(name))
+            .cloned()
+    }
+}
+
+/// Named converter of a resource pattern parameter
+#[derive(Clone)]
+pub(crate) struct Converter {
+    name: String,
+    re: Regex,
+    inner: Arc<ParamConverter>,
+}
+
+impl Converter {
+    fn new(name: &str, inner: Arc<ParamConverter>) -> Converter {
+        let re = match Regex::new(&format!("^(?:{})$", inner.regex())) {
+            Ok(re) => re,
+            Err(err) => panic!("Wrong regex of path converter {}: {}", name, err),
+        };
+        Converter {
+            re,
+            inner,
+            name: name.to_owned(),
+        }
+    }
+
+    /// Name of the converter
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Regex matched by parameter values
+    pub(crate) fn regex(&self) -> &str {
+        self.inner.regex()
+    }
+
+    /// Values are matched within one path segment
+    pub(crate) fn is_segment(&self) -> bool {
+        self.name != "*"
+    }
+
+    /// Check value which is already matched by converter regex
+    pub(crate) fn is_valid(&self, value: &str) -> bool {
+        self.inner.is_valid(value)
+    }
+
+    /// Check arbitrary value
+    pub(crate) fn is_match(&self, value: &str) -> bool {
+        self.re.is_match(value) && self.inner.is_valid(value)
+    }
+}
+
+impl fmt::Debug for Converter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Converter({})", self.name)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -8,6 +8,7 @@ use std::collections::VecDeque;
 use std::rc::{Rc, Weak};
 
 use error::PayloadError;
+use server::input::{DecodeLimits, PayloadDecoder};
 
 /// max buffer size 32k
 pub(crate) const MAX_BUFFER_SIZE: usize = 32_768;
@@ -89,6 +90,21 @@ impl Payload {
     pub fn set_read_buffer_capacity(&mut self, cap: usize) {
         self.inner.borrow_mut().capacity = cap;
     }
+
+    /// Set limits of decoded content
+    pub(crate) fn set_decode_limits(&self, limits: DecodeLimits) {
+        if let Some(ref mut decoder) = self.inner.borrow_mut().decoder {
+            decoder.set_limits(limits);
+        }
+    }
+
+    /// Pass encoded content as is, if decoding is not started yet
+    pub(crate) fn disable_decoding(&self) {
+        let mut inner = self.inner.borrow_mut();
+        if inner.decoder.as_ref().map_or(false, |d| !d.is_started()) {
+            inner.decoder = None;
+        }
+    }
 }
 
 impl Stream for Payload {
@@ -129,6 +145,15 @@ pub struct PayloadSender {
     inner: Weak<RefCell<Inner>>,
 }
 
+impl PayloadSender {
+    /// Decode content with the decoder while it is read
+    pub(crate) fn set_decoder(&self, decoder: PayloadDecoder) {
+        if let Some(shared) = self.inner.upgrade() {
+            shared.borrow_mut().decoder = Some(decoder);
+        }
+    }
+}
+
 impl PayloadWriter for PayloadSender {
     #[inline]
     fn set_error(&mut self, err: PayloadError) {
@@ -183,6 +208,8 @@ struct Inner {
     capacity: usize,
     task: Option<Task>,
     io_task: Option<Task>,
+    decoder: Option<PayloadDecoder>,
+    unread: VecDeque<Bytes>,
 }
 
 impl Inner {
@@ -196,6 +223,8 @@ impl Inner {
             capacity: MAX_BUFFER_SIZE,
             task: None,
             io_task: None,
+            decoder: None,
+            unread: VecDeque::new(),
         }
     }
 
@@ -242,6 +271,41 @@ impl Inner {
     }
 
     fn readany(&mut self) -> Poll<Option<Bytes>, PayloadError> {
+        if let Some(data) = self.unread.pop_front() {
+            return Ok(Async::Ready(Some(data)));
+        }
+        if self.decoder.is_none() {
+            return self.read_encoded();
+        }
+
+        loop {
+            let res = match self.read_encoded() {
+                Ok(Async::Ready(Some(data))) => {
+                    self.decoder.as_mut().unwrap().feed_data(data)
+                }
+                Ok(Async::Ready(None)) => match self.decoder.take() {
+                    Some(mut decoder) => decoder.feed_eof(),
+                    None => return Ok(Async::Ready(None)),
+                },
+                res => return res,
+            };
+            match res {
+                Ok(Some(data)) => return Ok(Async::Ready(Some(data))),
+                Ok(None) => if self.decoder.is_none() {
+                    return Ok(Async::Ready(None));
+                },
+                Err(err) => {
+                    self.decoder.take();
+                    self.items.clear();
+                    self.len = 0;
+                    self.eof = true;
+                    return Err(err);
+                }
+            }
+        }
+    }
+
+    fn read_encoded(&mut self) -> Poll<Option<Bytes>, PayloadError> {
         if let Some(data) = self.items.pop_front() {
             self.len -= data.len();
             self.need_read = self.len < self.capacity;
@@ -275,8 +339,13 @@ impl Inner {
     }
 
     fn unread_data(&mut self, data: Bytes) {
-        self.len += data.len();
-        self.items.push_front(data);
+        if self.decoder.is_some() {
+            // data is decoded already
+            self.unread.push_front(data);
+        } else {
+            self.len += data.len();
+            self.items.push_front(data);
+        }
     }
 }
 
@@ -624,6 +693,94 @@ mod tests {
             })).unwrap();
     }
 
+    #[cfg(feature = "flate2")]
+    fn gzip(data: &[u8]) -> Bytes {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        let mut enc = GzEncoder::new(Vec::new(), Compression::default());
+        enc.write_all(data).unwrap();
+        Bytes::from(enc.finish().unwrap())
+    }
+
+    #[cfg(feature = "flate2")]
+    fn read_decoded(payload: &mut Payload) -> Result<Bytes, PayloadError> {
+        let mut buf = BytesMut::new();
+        loop {
+            match payload.poll()? {
+                Async::Ready(Some(chunk)) => buf.extend_from_slice(&chunk),
+                Async::Ready(None) => return Ok(buf.freeze()),
+                Async::NotReady => panic!(),
+            }
+        }
+    }
+
+    #[cfg(feature = "flate2")]
+    #[test]
+    fn test_decode() {
+        use header::ContentEncoding;
+
+        let (mut sender, mut payload) = Payload::new(false);
+        sender.set_decoder(PayloadDecoder::new(ContentEncoding::Gzip));
+        let data = gzip(b"data");
+        sender.feed_data(data.slice_to(10));
+        sender.feed_data(data.slice_from(10));
+        sender.feed_eof();
+        assert_eq!(read_decoded(&mut payload).unwrap(), Bytes::from("data"));
+
+        // unread data is not decoded again
+        payload.unread_data(Bytes::from("data"));
+        assert_eq!(read_decoded(&mut payload).unwrap(), Bytes::from("data"));
+
+        // decoding is disabled
+        let (mut sender, mut payload) = Payload::new(false);
+        sender.set_decoder(PayloadDecoder::new(ContentEncoding::Gzip));
+        payload.disable_decoding();
+        sender.feed_data(data.clone());
+        sender.feed_eof();
+        assert_eq!(read_decoded(&mut payload).unwrap(), data);
+    }
+
+    #[cfg(feature = "flate2")]
+    #[test]
+    fn test_decode_limits() {
+        use header::ContentEncoding;
+
+        let data = gzip(&[0u8; 1_000_000]);
+        let decode = |limits| {
+            let (mut sender, mut payload) = Payload::new(false);
+            sender.set_decoder(PayloadDecoder::new(ContentEncoding::Gzip));
+            payload.set_decode_limits(limits);
+            sender.feed_data(data.clone());
+            sender.feed_eof();
+            read_decoded(&mut payload).map(|b| b.len())
+        };
+
+        assert_eq!(decode(DecodeLimits::default()).unwrap(), 1_000_000);
+        assert_eq!(
+            decode(DecodeLimits {
+                max_size: Some(1_000_000),
+                max_ratio: Some(1000),
+            }).unwrap(),
+            1_000_000
+        );
+        match decode(DecodeLimits {
+            max_size: Some(100_000),
+            max_ratio: None,
+        }) {
+            Err(PayloadError::Overflow) => (),
+            _ => panic!(),
+        }
+        match decode(DecodeLimits {
+            max_size: None,
+            max_ratio: Some(10),
+        }) {
+            Err(PayloadError::Overflow) => (),
+            _ => panic!(),
+        }
+    }
+
     #[test]
     fn test_readexactly() {
         Runtime::new()
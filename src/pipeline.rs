@@ -1,25 +1,27 @@
 use std::marker::PhantomData;
 use std::rc::Rc;
-use std::{io, mem};
+use std::{cmp, io, mem};
 
 use futures::sync::oneshot;
 use futures::{Async, Future, Poll, Stream};
 use log::Level::Debug;
 
-use body::{Body, BodyStream};
+use body::{Body, BodyStream, FileBody};
 use context::{ActorHttpContext, Frame};
 use error::Error;
 use handler::{AsyncResult, AsyncResultItem};
 use header::ContentEncoding;
 use httprequest::HttpRequest;
 use httpresponse::HttpResponse;
-use middleware::{Finished, Middleware, Response, Started};
-use server::{HttpHandlerTask, Writer, WriterState};
+use middleware::{with_deadline, Finished, Middleware, Response, Started};
+use server::{CompressionPolicy, HttpHandlerTask, Writer, WriterState};
 
 #[doc(hidden)]
 pub trait PipelineHandler<S> {
     fn encoding(&self) -> ContentEncoding;
 
+    fn compression(&self) -> &Rc<CompressionPolicy>;
+
     fn handle(&self, &HttpRequest<S>) -> AsyncResult<HttpResponse>;
 }
 
@@ -64,6 +66,7 @@ struct PipelineInfo<S: 'static> {
     error: Option<Error>,
     disconnected: Option<bool>,
     encoding: ContentEncoding,
+    compression: Rc<CompressionPolicy>,
 }
 
 impl<S: 'static> PipelineInfo<S> {
@@ -91,6 +94,7 @@ impl<S: 'static, H: PipelineHandler<S>> Pipeline<S, H> {
             context: None,
             disconnected: None,
             encoding: handler.encoding(),
+            compression: Rc::clone(handler.compression()),
         };
         let state = StartMiddlewares::init(&mut info, &mws, handler);
 
@@ -288,7 +292,7 @@ impl<S: 'static, H> WaitingResponse<S, H> {
             AsyncResultItem::Ok(resp) => RunMiddlewares::init(info, mws, resp),
             AsyncResultItem::Err(err) => RunMiddlewares::init(info, mws, err.into()),
             AsyncResultItem::Future(fut) => PipelineState::Handler(WaitingResponse {
-                fut,
+                fut: with_deadline(&info.req, fut),
                 _s: PhantomData,
                 _h: PhantomData,
             }),
@@ -424,6 +428,7 @@ impl RunningState {
 enum IOState {
     Response,
     Payload(BodyStream),
+    File(FileBody),
     Actor(Box<ActorHttpContext>),
     Done,
 }
@@ -451,7 +456,7 @@ impl<S: 'static, H> ProcessResponse<S, H> {
                 mws,
                 self.resp.take().unwrap(),
             )),
-            IOState::Payload(_) => Some(FinishingMiddlewares::init(
+            IOState::Payload(_) | IOState::File(_) => Some(FinishingMiddlewares::init(
                 info,
                 mws,
                 self.resp.take().unwrap(),
@@ -530,11 +535,13 @@ impl<S: 'static, H> ProcessResponse<S, H> {
                                 .unwrap()
                                 .content_encoding()
                                 .unwrap_or(info.encoding);
+                            let file = self.resp.as_mut().unwrap().take_file_body();
 
                             let result = match io.start(
                                 &info.req,
                                 self.resp.as_mut().unwrap(),
                                 encoding,
+                                &info.compression,
                             ) {
                                 Ok(res) => res,
                                 Err(err) => {
@@ -568,7 +575,12 @@ impl<S: 'static, H> ProcessResponse<S, H> {
                             // always poll stream or actor for the first time
                             match self.resp.as_mut().unwrap().replace_body(Body::Empty) {
                                 Body::Streaming(stream) => {
-                                    self.iostate = IOState::Payload(stream);
+                                    self.iostate = match file {
+                                        Some(file) if io.can_send_file() => {
+                                            IOState::File(file)
+                                        }
+                                        _ => IOState::Payload(stream),
+                                    };
                                     continue 'inner;
                                 }
                                 Body::Actor(ctx) => {
@@ -619,6 +631,39 @@ impl<S: 'static, H> ProcessResponse<S, H> {
                                 ));
                             }
                         },
+                        IOState::File(mut file) => {
+                            match io.send_file(&file.file, file.offset, file.size) {
+                                Ok(Async::Ready(n)) => {
+                                    file.offset += n as u64;
+                                    file.size -= cmp::min(file.size, n as u64);
+                                    if file.size == 0 {
+                                        if let Err(err) = io.write_eof() {
+                                            info.error = Some(err.into());
+                                            return Ok(FinishingMiddlewares::init(
+                                                info,
+                                                mws,
+                                                self.resp.take().unwrap(),
+                                            ));
+                                        }
+                                        break;
+                                    }
+                                    self.iostate = IOState::File(file);
+                                    WriterState::Done
+                                }
+                                Ok(Async::NotReady) => {
+                                    self.iostate = IOState::File(file);
+                                    break;
+                                }
+                                Err(err) => {
+                                    info.error = Some(err.into());
+                                    return Ok(FinishingMiddlewares::init(
+                                        info,
+                                        mws,
+                                        self.resp.take().unwrap(),
+                                    ));
+                                }
+                            }
+                        }
                         IOState::Actor(mut ctx) => {
                             if info.disconnected.take().is_some() {
                                 ctx.disconnected();
@@ -2,6 +2,7 @@
 #![allow(non_snake_case)]
 use std::marker::PhantomData;
 
+use handler::type_name;
 use http;
 use http::{header, HttpTryFrom};
 use server::message::Request;
@@ -13,6 +14,53 @@ use server::message::Request;
 pub trait Predicate<S> {
     /// Check if request matches predicate
     fn check(&self, &Request, &S) -> bool;
+
+    /// Http methods matched by predicate.
+    ///
+    /// `None` means predicate does not restrict request method. This list
+    /// is used for `Allow` header of automatic *405 Method Not Allowed*
+    /// and *OPTIONS* responses.
+    fn methods(&self) -> Option<Vec<http::Method>> {
+        None
+    }
+
+    /// Predicate name, used in application route table.
+    ///
+    /// Defaults to type name of the predicate, which is empty with rustc
+    /// older than 1.38. Empty name hides predicate from the route table.
+    fn name(&self) -> &'static str {
+        type_name::<Self>()
+    }
+
+    /// Names of predicates, wrapper predicates list wrapped predicates
+    #[doc(hidden)]
+    fn names(&self) -> Vec<&'static str> {
+        vec![self.name()]
+    }
+}
+
+/// Names of predicates shown in route table
+pub(crate) fn names<S>(preds: &[Box<Predicate<S>>]) -> Vec<&'static str> {
+    preds
+        .iter()
+        .flat_map(|p| p.names())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// Methods matched by all predicates, `None` if none of predicates
+/// restricts request method
+pub(crate) fn all_methods<S>(preds: &[Box<Predicate<S>>]) -> Option<Vec<http::Method>> {
+    let mut result: Option<Vec<http::Method>> = None;
+    for pred in preds {
+        if let Some(methods) = pred.methods() {
+            result = Some(match result {
+                Some(prev) => prev.into_iter().filter(|m| methods.contains(m)).collect(),
+                None => methods,
+            });
+        }
+    }
+    result
 }
 
 /// Return predicate that matches if any of supplied predicate matches.
@@ -53,6 +101,18 @@ impl<S: 'static> Predicate<S> for AnyPredicate<S> {
         }
         false
     }
+
+    fn methods(&self) -> Option<Vec<http::Method>> {
+        let mut result = Vec::new();
+        for p in &self.0 {
+            for method in p.methods()? {
+                if !result.contains(&method) {
+                    result.push(method);
+                }
+            }
+        }
+        Some(result)
+    }
 }
 
 /// Return predicate that matches if all of supplied predicate matches.
@@ -96,6 +156,10 @@ impl<S: 'static> Predicate<S> for AllPredicate<S> {
         }
         true
     }
+
+    fn methods(&self) -> Option<Vec<http::Method>> {
+        all_methods(&self.0)
+    }
 }
 
 /// Return predicate that matches if supplied predicate does not match.
@@ -120,6 +184,15 @@ impl<S: 'static> Predicate<S> for MethodPredicate<S> {
     fn check(&self, req: &Request, _: &S) -> bool {
         *req.method() == self.0
     }
+
+    fn methods(&self) -> Option<Vec<http::Method>> {
+        Some(vec![self.0.clone()])
+    }
+
+    fn name(&self) -> &'static str {
+        // methods are listed separately
+        ""
+    }
 }
 
 /// Predicate to match *GET* http method
@@ -325,4 +398,23 @@ mod tests {
         assert!(Any(Get()).or(Trace()).check(&r, r.state()));
         assert!(!Any(Get()).or(Get()).check(&r, r.state()));
     }
+
+    #[test]
+    fn test_pred_methods() {
+        assert_eq!(Get::<()>().methods(), Some(vec![Method::GET]));
+        assert_eq!(Header::<()>("content-type", "text/plain").methods(), None);
+        assert_eq!(Not(Get::<()>()).methods(), None);
+        assert_eq!(
+            Any(Get::<()>()).or(Post()).or(Get()).methods(),
+            Some(vec![Method::GET, Method::POST])
+        );
+        assert_eq!(Any(Get::<()>()).or(Host("localhost")).methods(), None);
+        assert_eq!(
+            All(Any(Get::<()>()).or(Post()))
+                .and(Post())
+                .and(Header("content-type", "text/plain"))
+                .methods(),
+            Some(vec![Method::POST])
+        );
+    }
 }
@@ -2,7 +2,7 @@ use std::ops::Deref;
 use std::rc::Rc;
 
 use futures::Future;
-use http::Method;
+use http::{header, Method, StatusCode};
 use smallvec::SmallVec;
 
 use error::Error;
@@ -12,7 +12,7 @@ use httpresponse::HttpResponse;
 use middleware::Middleware;
 use pred;
 use route::Route;
-use router::ResourceDef;
+use router::{MatchedResource, ResourceDef, RouteEntry};
 use with::WithFactory;
 
 #[derive(Copy, Clone)]
@@ -42,6 +42,7 @@ pub struct Resource<S = ()> {
     rdef: ResourceDef,
     routes: SmallVec<[Route<S>; 3]>,
     middlewares: Rc<Vec<Box<Middleware<S>>>>,
+    decompress: bool,
 }
 
 impl<S> Resource<S> {
@@ -51,6 +52,7 @@ impl<S> Resource<S> {
             rdef,
             routes: SmallVec::new(),
             middlewares: Rc::new(Vec::new()),
+            decompress: true,
         }
     }
 
@@ -278,6 +280,30 @@ impl<S: 'static> Resource<S> {
             .push(Box::new(mw));
     }
 
+    /// Decode compressed request payload, enabled by default.
+    ///
+    /// If decompression is disabled, payload is passed to handlers as is,
+    /// i.e. for proxying or storing, `Content-Encoding` header describes
+    /// its encoding.
+    ///
+    /// The setting applies to all routes of the resource, use
+    /// `Route::decompress()` to disable decompression for a single route.
+    ///
+    /// ```rust
+    /// # extern crate actix_web;
+    /// use actix_web::{App, HttpResponse};
+    ///
+    /// fn main() {
+    ///     let app = App::new().resource("/upload", |r| {
+    ///         r.decompress(false);
+    ///         r.f(|_| HttpResponse::Ok())
+    ///     });
+    /// }
+    /// ```
+    pub fn decompress(&mut self, enabled: bool) {
+        self.decompress = enabled;
+    }
+
     #[inline]
     pub(crate) fn get_route_id(&self, req: &HttpRequest<S>) -> Option<RouteId> {
         for idx in 0..self.routes.len() {
@@ -288,14 +314,93 @@ impl<S: 'static> Resource<S> {
         None
     }
 
+    /// Methods matched by resource routes, `None` if any route matches
+    /// all methods
+    fn allowed_methods(&self) -> Option<Vec<Method>> {
+        if self.routes.is_empty() {
+            return None;
+        }
+        let mut result = Vec::new();
+        for route in &self.routes {
+            for method in route.methods()? {
+                if !result.contains(&method) {
+                    result.push(method);
+                }
+            }
+        }
+        Some(result)
+    }
+
+    /// Response for request method which is not matched by any route.
+    ///
+    /// *OPTIONS* request gets *200 OK* response, other methods get
+    /// *405 Method Not Allowed*, both with `Allow` header. Response goes
+    /// through resource middlewares.
+    pub(crate) fn method_not_allowed(
+        &self, req: &HttpRequest<S>,
+    ) -> Option<AsyncResult<HttpResponse>> {
+        let mut methods = self.allowed_methods()?;
+        if methods.contains(req.method()) {
+            return None;
+        }
+        if !methods.contains(&Method::OPTIONS) {
+            methods.push(Method::OPTIONS);
+        }
+        let allow = methods
+            .iter()
+            .map(|m| m.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let status = if *req.method() == Method::OPTIONS {
+            StatusCode::OK
+        } else {
+            StatusCode::METHOD_NOT_ALLOWED
+        };
+        let mut route = Route::default();
+        route.f(move |_| {
+            HttpResponse::build(status)
+                .header(header::ALLOW, allow.as_str())
+                .finish()
+        });
+        Some(self.handle_route(&route, req))
+    }
+
+    /// Add resource routes to the route table
+    pub(crate) fn route_table(&self, base: RouteEntry, table: &mut Vec<RouteEntry>) {
+        for route in &self.routes {
+            let mut entry = base.clone();
+            entry.name = self.rdef.name().to_owned();
+            entry.methods = route.methods();
+            entry.predicates.extend(route.predicates());
+            entry.middlewares += self.middlewares.len();
+            entry.handler = route.handler_name();
+            #[cfg(feature = "openapi")]
+            {
+                entry.operation = Some(route.operation().clone());
+                entry.extractor = route.extractor();
+            }
+            table.push(entry);
+        }
+    }
+
     #[inline]
     pub(crate) fn handle(
         &self, id: RouteId, req: &HttpRequest<S>,
     ) -> AsyncResult<HttpResponse> {
+        self.handle_route(&self.routes[id.0], req)
+    }
+
+    fn handle_route(
+        &self, route: &Route<S>, req: &HttpRequest<S>,
+    ) -> AsyncResult<HttpResponse> {
+        if !self.decompress || !route.is_decompress() {
+            req.disable_decompression();
+        }
+        req.extensions_mut().insert(MatchedResource::new(req.resource()));
         if self.middlewares.is_empty() {
-            (&self.routes[id.0]).handle(req)
+            route.handle(req)
         } else {
-            (&self.routes[id.0]).compose(req.clone(), Rc::clone(&self.middlewares))
+            route.compose(req.clone(), Rc::clone(&self.middlewares))
         }
     }
 }
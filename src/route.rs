@@ -6,16 +6,18 @@ use futures::{Async, Future, Poll};
 use error::Error;
 use handler::{
     AsyncHandler, AsyncResult, AsyncResultItem, FromRequest, Handler, Responder,
-    RouteHandler, WrapHandler,
+    RouteHandler, WrapHandler, type_name,
 };
-use http::StatusCode;
+use http::{Method, StatusCode};
 use httprequest::HttpRequest;
 use httpresponse::HttpResponse;
 use middleware::{
-    Finished as MiddlewareFinished, Middleware, Response as MiddlewareResponse,
-    Started as MiddlewareStarted,
+    with_deadline, Finished as MiddlewareFinished, Middleware,
+    Response as MiddlewareResponse, Started as MiddlewareStarted,
 };
-use pred::Predicate;
+#[cfg(feature = "openapi")]
+use openapi::{ExtractorApi, Operation};
+use pred::{self, Predicate};
 use with::{WithAsyncFactory, WithFactory};
 
 /// Resource route definition
@@ -25,6 +27,12 @@ use with::{WithAsyncFactory, WithFactory};
 pub struct Route<S> {
     preds: Vec<Box<Predicate<S>>>,
     handler: InnerHandler<S>,
+    handler_name: &'static str,
+    decompress: bool,
+    #[cfg(feature = "openapi")]
+    api: Operation,
+    #[cfg(feature = "openapi")]
+    extractor: Option<ExtractorApi>,
 }
 
 impl<S: 'static> Default for Route<S> {
@@ -32,6 +40,12 @@ impl<S: 'static> Default for Route<S> {
         Route {
             preds: Vec::new(),
             handler: InnerHandler::new(|_: &_| HttpResponse::new(StatusCode::NOT_FOUND)),
+            handler_name: "",
+            decompress: true,
+            #[cfg(feature = "openapi")]
+            api: Operation::default(),
+            #[cfg(feature = "openapi")]
+            extractor: None,
         }
     }
 }
@@ -48,6 +62,38 @@ impl<S: 'static> Route<S> {
         true
     }
 
+    /// Methods matched by route predicates
+    pub(crate) fn methods(&self) -> Option<Vec<Method>> {
+        pred::all_methods(&self.preds)
+    }
+
+    /// Names of route predicates
+    pub(crate) fn predicates(&self) -> Vec<&'static str> {
+        pred::names(&self.preds)
+    }
+
+    /// Type name of route handler
+    pub(crate) fn handler_name(&self) -> &'static str {
+        self.handler_name
+    }
+
+    /// Request payload of the route is decompressed
+    pub(crate) fn is_decompress(&self) -> bool {
+        self.decompress
+    }
+
+    /// OpenAPI operation described by route annotations
+    #[cfg(feature = "openapi")]
+    pub(crate) fn operation(&self) -> &Operation {
+        &self.api
+    }
+
+    /// OpenAPI description of route handler extractors
+    #[cfg(feature = "openapi")]
+    pub(crate) fn extractor(&self) -> Option<ExtractorApi> {
+        self.extractor
+    }
+
     #[inline]
     pub(crate) fn handle(&self, req: &HttpRequest<S>) -> AsyncResult<HttpResponse> {
         self.handler.handle(req)
@@ -80,10 +126,62 @@ impl<S: 'static> Route<S> {
         self
     }
 
+    /// Enable or disable decompression of request payload for the route.
+    ///
+    /// Decompression is enabled by default, payload of the route is
+    /// decompressed only if it is enabled for the route and for its
+    /// resource, see `Resource::decompress()`.
+    ///
+    /// ```rust
+    /// # extern crate actix_web;
+    /// # use actix_web::*;
+    /// # fn main() {
+    /// App::new().resource("/upload", |r| {
+    ///     r.post().decompress(false).f(|req| HttpResponse::Ok());
+    ///     r.put().f(|req| HttpResponse::Ok())
+    /// })
+    /// #      .finish();
+    /// # }
+    /// ```
+    pub fn decompress(&mut self, enabled: bool) -> &mut Self {
+        self.decompress = enabled;
+        self
+    }
+
+    /// Describe route in OpenAPI document.
+    ///
+    /// Request parameters and body are described by handler extractors,
+    /// this method adds summary, tags, responses and other annotations.
+    ///
+    /// ```rust
+    /// # extern crate actix_web;
+    /// # use actix_web::*;
+    /// # fn main() {
+    /// App::new().resource("/path", |r| {
+    ///     r.get()
+    ///         .openapi(|op| {
+    ///             op.summary("Index page").tag("pages");
+    ///         })
+    ///         .f(|req| HttpResponse::Ok())
+    /// })
+    /// #      .finish();
+    /// # }
+    /// ```
+    #[cfg(feature = "openapi")]
+    pub fn openapi<F: FnOnce(&mut Operation)>(&mut self, f: F) -> &mut Self {
+        f(&mut self.api);
+        self
+    }
+
     /// Set handler object. Usually call to this method is last call
     /// during route configuration, so it does not return reference to self.
     pub fn h<H: Handler<S>>(&mut self, handler: H) {
         self.handler = InnerHandler::new(handler);
+        self.handler_name = type_name::<H>();
+        #[cfg(feature = "openapi")]
+        {
+            self.extractor = None;
+        }
     }
 
     /// Set handler function. Usually call to this method is last call
@@ -94,6 +192,11 @@ impl<S: 'static> Route<S> {
         R: Responder + 'static,
     {
         self.handler = InnerHandler::new(handler);
+        self.handler_name = type_name::<F>();
+        #[cfg(feature = "openapi")]
+        {
+            self.extractor = None;
+        }
     }
 
     /// Set async handler function.
@@ -105,6 +208,11 @@ impl<S: 'static> Route<S> {
         E: Into<Error> + 'static,
     {
         self.handler = InnerHandler::async(handler);
+        self.handler_name = type_name::<H>();
+        #[cfg(feature = "openapi")]
+        {
+            self.extractor = None;
+        }
     }
 
     /// Set handler function, use request extractor for parameters.
@@ -171,6 +279,11 @@ impl<S: 'static> Route<S> {
         T: FromRequest<S> + 'static,
     {
         self.h(handler.create());
+        self.handler_name = type_name::<F>();
+        #[cfg(feature = "openapi")]
+        {
+            self.extractor = Some(ExtractorApi::new(T::describe_api));
+        }
     }
 
     /// Set handler function. Same as `.with()` but it allows to configure
@@ -207,6 +320,11 @@ impl<S: 'static> Route<S> {
         let mut cfg = <T::Config as Default>::default();
         cfg_f(&mut cfg);
         self.h(handler.create_with_config(cfg));
+        self.handler_name = type_name::<F>();
+        #[cfg(feature = "openapi")]
+        {
+            self.extractor = Some(ExtractorApi::new(T::describe_api));
+        }
     }
 
     /// Set async handler function, use request extractor for parameters.
@@ -247,6 +365,11 @@ impl<S: 'static> Route<S> {
         T: FromRequest<S> + 'static,
     {
         self.h(handler.create());
+        self.handler_name = type_name::<F>();
+        #[cfg(feature = "openapi")]
+        {
+            self.extractor = Some(ExtractorApi::new(T::describe_api));
+        }
     }
 
     /// Set async handler function, use request extractor for parameters.
@@ -293,6 +416,11 @@ impl<S: 'static> Route<S> {
         let mut extractor_cfg = <T::Config as Default>::default();
         cfg(&mut extractor_cfg);
         self.h(handler.create_with_config(extractor_cfg));
+        self.handler_name = type_name::<F>();
+        #[cfg(feature = "openapi")]
+        {
+            self.extractor = Some(ExtractorApi::new(T::describe_api));
+        }
     }
 }
 
@@ -495,7 +623,7 @@ impl<S: 'static> WaitingResponse<S> {
             AsyncResultItem::Ok(resp) => RunMiddlewares::init(info, resp),
             AsyncResultItem::Err(err) => RunMiddlewares::init(info, err.into()),
             AsyncResultItem::Future(fut) => ComposeState::Handler(WaitingResponse {
-                fut,
+                fut: with_deadline(&info.req, fut),
                 _s: PhantomData,
             }),
         }
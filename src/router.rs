@@ -1,19 +1,24 @@
 use std::cell::RefCell;
-use std::cmp::min;
+use std::cmp::{max, min};
 use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
+use std::mem;
 use std::rc::Rc;
+use std::{fmt, slice};
 
-use regex::{escape, Regex};
+use regex::{escape, Captures, Regex};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
 use url::Url;
 
-use error::UrlGenerationError;
+use error::{Error, UrlGenerationError};
 use handler::{AsyncResult, FromRequest, Responder, RouteHandler};
 use http::{Method, StatusCode};
 use httprequest::HttpRequest;
 use httpresponse::HttpResponse;
-use param::{ParamItem, Params};
-use pred::Predicate;
+#[cfg(feature = "openapi")]
+use openapi::{ExtractorApi, Operation};
+use param::{Converter, Converters, ParamItem, Params};
+use pred::{self, Predicate};
 use resource::{DefaultResource, Resource};
 use scope::Scope;
 use server::Request;
@@ -43,6 +48,8 @@ pub struct Router<S> {
     patterns: Vec<ResourcePattern<S>>,
     resources: Vec<ResourceItem<S>>,
     default: Option<DefaultResource<S>>,
+    tree: PathNode,
+    linear: Vec<usize>,
 }
 
 /// Information about current resource
@@ -58,18 +65,14 @@ impl ResourceInfo {
     /// Name os the resource
     #[inline]
     pub fn name(&self) -> &str {
-        if let ResourceId::Normal(idx) = self.resource {
-            self.rmap.patterns[idx as usize].0.name()
-        } else {
-            ""
-        }
+        self.rdef().map_or("", |rdef| rdef.name())
     }
 
     /// This method returns reference to matched `ResourceDef` object.
     #[inline]
     pub fn rdef(&self) -> Option<&ResourceDef> {
         if let ResourceId::Normal(idx) = self.resource {
-            Some(&self.rmap.patterns[idx as usize].0)
+            self.rmap.patterns.get(idx as usize).map(|p| &p.0)
         } else {
             None
         }
@@ -166,6 +169,66 @@ impl ResourceInfo {
     }
 }
 
+/// Resource matched by the innermost router.
+///
+/// `Resource` puts it to the request extensions, so middlewares of the
+/// application and of enclosing scopes see resource matched within a scope.
+#[derive(Clone)]
+pub(crate) struct MatchedResource {
+    rmap: Rc<ResourceMap>,
+    resource: ResourceId,
+}
+
+impl MatchedResource {
+    pub fn new(info: &ResourceInfo) -> MatchedResource {
+        MatchedResource {
+            rmap: Rc::clone(&info.rmap),
+            resource: info.resource,
+        }
+    }
+
+    /// Matched resource of the request, resource of the request's own
+    /// router if request is not handled by a resource yet
+    pub fn get<S>(req: &HttpRequest<S>) -> MatchedResource {
+        req.extensions()
+            .get::<MatchedResource>()
+            .cloned()
+            .unwrap_or_else(|| MatchedResource::new(req.resource()))
+    }
+
+    fn rdef(&self) -> Option<&ResourceDef> {
+        if let ResourceId::Normal(idx) = self.resource {
+            self.rmap.patterns.get(idx as usize).map(|p| &p.0)
+        } else {
+            None
+        }
+    }
+
+    /// Name of the resource
+    pub fn name(&self) -> &str {
+        self.rdef().map_or("", |rdef| rdef.name())
+    }
+
+    /// Path pattern of the resource, prefixed with patterns of
+    /// enclosing scopes
+    pub fn pattern(&self) -> Option<String> {
+        self.rdef().map(|rdef| {
+            let mut pattern = String::new();
+            self.rmap.scope_prefix(&mut pattern);
+            join_pattern(&mut pattern, rdef.pattern());
+            pattern
+        })
+    }
+}
+
+/// Append path pattern, avoiding double slash
+fn join_pattern(pattern: &mut String, path: &str) {
+    if pattern.ends_with('/') && path.starts_with('/') {
+        pattern.pop();
+    }
+    pattern.push_str(path);
+}
+
 pub(crate) struct ResourceMap {
     root: ResourceDef,
     parent: RefCell<Option<Rc<ResourceMap>>>,
@@ -225,6 +288,14 @@ impl ResourceMap {
         }
     }
 
+    /// Patterns of scopes, application prefix is not included
+    fn scope_prefix(&self, pattern: &mut String) {
+        if let Some(ref parent) = *self.parent.borrow() {
+            parent.scope_prefix(pattern);
+            join_pattern(pattern, self.root.pattern());
+        }
+    }
+
     fn fill_root<U, I>(
         &self, path: &mut String, elements: &mut U,
     ) -> Result<(), UrlGenerationError>
@@ -278,6 +349,8 @@ impl<S: 'static> Router<S> {
             resources: Vec::new(),
             patterns: Vec::new(),
             default: None,
+            tree: PathNode::new(),
+            linear: Vec::new(),
         }
     }
 
@@ -320,6 +393,12 @@ impl<S: 'static> Router<S> {
             }
             rmap.patterns.push((resource.rdef().clone(), None));
         }
+        let idx = self.patterns.len();
+        if let Some(ref segments) = resource.rdef().segments {
+            self.tree.insert(segments, idx, resource.rdef().is_dynamic());
+        } else {
+            self.linear.push(idx);
+        }
         self.patterns
             .push(ResourcePattern::Resource(resource.rdef().clone()));
         self.resources.push(ResourceItem::Resource(resource));
@@ -336,6 +415,7 @@ impl<S: 'static> Router<S> {
             .push(scope.router().rmap.clone());
 
         let filters = scope.take_filters();
+        self.linear.push(self.patterns.len());
         self.patterns
             .push(ResourcePattern::Scope(scope.rdef().clone(), filters));
         self.resources.push(ResourceItem::Scope(scope));
@@ -350,6 +430,7 @@ impl<S: 'static> Router<S> {
             .unwrap()
             .patterns
             .push((rdef.clone(), None));
+        self.linear.push(self.patterns.len());
         self.resources.push(ResourceItem::Handler(hnd));
         self.patterns.push(ResourcePattern::Handler(rdef, filters));
     }
@@ -362,8 +443,10 @@ impl<S: 'static> Router<S> {
         self.default = Some(resource);
     }
 
-    pub(crate) fn finish(&mut self) {
-        for resource in &mut self.resources {
+    pub(crate) fn finish(&mut self, converters: &Converters) {
+        self.resolve(converters);
+
+        for (idx, resource) in self.resources.iter_mut().enumerate() {
             match resource {
                 ResourceItem::Resource(_) => (),
                 ResourceItem::Scope(scope) => {
@@ -372,8 +455,12 @@ impl<S: 'static> Router<S> {
                             scope.default_resource(default.clone());
                         }
                     }
-                    *scope.router().rmap.parent.borrow_mut() = Some(self.rmap.clone());
-                    scope.finish();
+                    scope.finish(converters);
+
+                    let nested = scope.router().rmap.clone();
+                    let rmap = Rc::get_mut(&mut self.rmap).expect("Use after finish");
+                    rmap.patterns[idx].1 = Some(nested.clone());
+                    rmap.nested.push(nested);
                 }
                 ResourceItem::Handler(hnd) => {
                     if !hnd.has_default_resource() {
@@ -381,10 +468,50 @@ impl<S: 'static> Router<S> {
                             hnd.default_resource(default.clone());
                         }
                     }
-                    hnd.finish()
+                    hnd.finish(converters)
                 }
             }
         }
+        for resource in &self.resources {
+            if let ResourceItem::Scope(ref scope) = *resource {
+                *scope.router().rmap.parent.borrow_mut() = Some(self.rmap.clone());
+            }
+        }
+    }
+
+    /// Resolve application converters of resource patterns,
+    /// rebuild prefix tree with resolved patterns
+    fn resolve(&mut self, converters: &Converters) {
+        {
+            let rmap = Rc::get_mut(&mut self.rmap).expect("Use after finish");
+            rmap.root.resolve(converters);
+            for rdef in rmap.named.values_mut() {
+                rdef.resolve(converters);
+            }
+            // scopes are linked again once their resource maps are resolved
+            rmap.nested.clear();
+            for &mut (ref mut rdef, ref mut nested) in &mut rmap.patterns {
+                rdef.resolve(converters);
+                *nested = None;
+            }
+        }
+
+        self.tree = PathNode::new();
+        self.linear.clear();
+        for (idx, pattern) in self.patterns.iter_mut().enumerate() {
+            match *pattern {
+                ResourcePattern::Resource(ref mut rdef) => {
+                    rdef.resolve(converters);
+                    if let Some(ref segments) = rdef.segments {
+                        self.tree.insert(segments, idx, rdef.is_dynamic());
+                        continue;
+                    }
+                }
+                ResourcePattern::Handler(ref mut rdef, _)
+                | ResourcePattern::Scope(ref mut rdef, _) => rdef.resolve(converters),
+            }
+            self.linear.push(idx);
+        }
     }
 
     pub(crate) fn register_external(&mut self, name: &str, rdef: ResourceDef) {
@@ -427,6 +554,30 @@ impl<S: 'static> Router<S> {
         }
     }
 
+    /// Add registered routes to the route table
+    pub(crate) fn route_table(&self, base: &RouteEntry, table: &mut Vec<RouteEntry>) {
+        for (pattern, item) in self.patterns.iter().zip(self.resources.iter()) {
+            match (pattern, item) {
+                (ResourcePattern::Resource(rdef), ResourceItem::Resource(resource)) => {
+                    resource.route_table(base.nested(rdef.pattern(), Vec::new()), table)
+                }
+                (
+                    ResourcePattern::Handler(rdef, filters),
+                    ResourceItem::Handler(hnd),
+                ) => {
+                    let preds = filters.as_ref().map(|f| pred::names(f));
+                    let entry = base.nested(rdef.pattern(), preds.unwrap_or_default());
+                    hnd.route_table(entry, table)
+                }
+                (ResourcePattern::Scope(rdef, filters), ResourceItem::Scope(scope)) => {
+                    let entry = base.nested(rdef.pattern(), pred::names(filters));
+                    scope.route_table(entry, table)
+                }
+                _ => (),
+            }
+        }
+    }
+
     /// Handle request
     pub fn handle(&self, req: &HttpRequest<S>) -> AsyncResult<HttpResponse> {
         let resource = match req.resource().resource {
@@ -445,6 +596,9 @@ impl<S: 'static> Router<S> {
                 if let Some(id) = resource.get_route_id(req) {
                     return resource.handle(id, req);
                 }
+                if let Some(resp) = resource.method_not_allowed(req) {
+                    return resp;
+                }
 
                 if let Some(ref default) = self.default {
                     if let Some(id) = default.get_route_id(req) {
@@ -459,16 +613,23 @@ impl<S: 'static> Router<S> {
     }
 
     /// Query for matched resource
+    ///
+    /// Static and `{param}` patterns are looked up in the prefix tree,
+    /// other patterns registered before the tree match are checked
+    /// one by one, so the first registered pattern still wins.
     pub fn recognize(&self, req: &Request, state: &S, tail: usize) -> ResourceInfo {
         if tail <= req.path().len() {
-            'outer: for (idx, resource) in self.patterns.iter().enumerate() {
-                match resource {
-                    ResourcePattern::Resource(rdef) => {
+            let found = self.tree.recognize(&req.path()[tail..]);
+            let limit = found.as_ref().map_or(self.patterns.len(), |m| m.leaf.idx);
+
+            'outer: for &idx in self.linear.iter().take_while(|idx| **idx < limit) {
+                match self.patterns[idx] {
+                    ResourcePattern::Resource(ref rdef) => {
                         if let Some(params) = rdef.match_with_params(req, tail) {
                             return self.route_info_params(idx as u16, params);
                         }
                     }
-                    ResourcePattern::Handler(rdef, filters) => {
+                    ResourcePattern::Handler(ref rdef, ref filters) => {
                         if let Some(params) = rdef.match_prefix_with_params(req, tail) {
                             if let Some(ref filters) = filters {
                                 for filter in filters {
@@ -480,7 +641,7 @@ impl<S: 'static> Router<S> {
                             return self.route_info_params(idx as u16, params);
                         }
                     }
-                    ResourcePattern::Scope(rdef, filters) => {
+                    ResourcePattern::Scope(ref rdef, ref filters) => {
                         if let Some(params) = rdef.match_prefix_with_params(req, tail) {
                             for filter in filters {
                                 if !filter.check(req, state) {
@@ -492,6 +653,23 @@ impl<S: 'static> Router<S> {
                     }
                 }
             }
+
+            if let Some(m) = found {
+                let mut params = Params::with_url(req.url());
+                if m.leaf.dynamic {
+                    for (name, &(start, end)) in m.leaf.names.iter().zip(&m.segments) {
+                        params.add(
+                            name.clone(),
+                            ParamItem::UrlSegment(
+                                (tail + start) as u16,
+                                (tail + end) as u16,
+                            ),
+                        );
+                    }
+                    params.set_tail(req.path().len() as u16);
+                }
+                return self.route_info_params(m.leaf.idx as u16, params);
+            }
         }
         ResourceInfo {
             prefix: tail as u16,
@@ -502,6 +680,387 @@ impl<S: 'static> Router<S> {
     }
 }
 
+/// Resource registered in the prefix tree
+struct PathLeaf {
+    idx: usize,
+    names: Vec<Rc<String>>,
+    dynamic: bool,
+}
+
+/// Prefix tree match, positions of `{param}` segments in the path
+struct PathMatch<'a> {
+    leaf: &'a PathLeaf,
+    segments: Vec<(usize, usize)>,
+}
+
+/// Node of the prefix tree, one node per path segment
+struct PathNode {
+    statics: HashMap<String, PathNode>,
+    param: Option<Box<PathNode>>,
+    /// `{param:converter}` children, one per converter
+    typed: Vec<(Converter, PathNode)>,
+    leaf: Option<PathLeaf>,
+    /// Lowest resource index in the subtree
+    min_idx: usize,
+}
+
+impl PathNode {
+    fn new() -> PathNode {
+        PathNode {
+            statics: HashMap::new(),
+            param: None,
+            typed: Vec::new(),
+            leaf: None,
+            min_idx: usize::max_value(),
+        }
+    }
+
+    fn insert(&mut self, segments: &[PathSegment], idx: usize, dynamic: bool) {
+        let mut node = self;
+        let mut names = Vec::new();
+        for seg in segments {
+            node.min_idx = min(node.min_idx, idx);
+            node = match *seg {
+                PathSegment::Static(ref s) => {
+                    node.statics.entry(s.clone()).or_insert_with(PathNode::new)
+                }
+                PathSegment::Param(ref name) => {
+                    names.push(name.clone());
+                    node.param.get_or_insert_with(|| Box::new(PathNode::new()))
+                }
+                PathSegment::Typed(ref name, ref conv) => {
+                    names.push(name.clone());
+                    let pos = node.typed.iter().position(|t| t.0.name() == conv.name());
+                    let pos = match pos {
+                        Some(pos) => pos,
+                        None => {
+                            node.typed.push((conv.clone(), PathNode::new()));
+                            node.typed.len() - 1
+                        }
+                    };
+                    &mut node.typed[pos].1
+                }
+            };
+        }
+        node.min_idx = min(node.min_idx, idx);
+        // same pattern registered twice, first resource wins
+        if node.leaf.is_none() {
+            node.leaf = Some(PathLeaf {
+                idx,
+                names,
+                dynamic,
+            });
+        }
+    }
+
+    fn recognize(&self, path: &str) -> Option<PathMatch> {
+        if !path.starts_with('/') {
+            return None;
+        }
+        let mut found = None;
+        self.find(path, 1, &mut Vec::new(), &mut found);
+        found
+    }
+
+    fn find<'a>(
+        &'a self, path: &str, pos: usize, segments: &mut Vec<(usize, usize)>,
+        found: &mut Option<PathMatch<'a>>,
+    ) {
+        if found.as_ref().map_or(false, |m| m.leaf.idx < self.min_idx) {
+            return;
+        }
+        let (end, next) = match path[pos..].find('/') {
+            Some(n) => (pos + n, Some(pos + n + 1)),
+            None => (path.len(), None),
+        };
+        if let Some(node) = self.statics.get(&path[pos..end]) {
+            node.next(path, next, segments, found);
+        }
+        if end > pos {
+            for &(ref conv, ref node) in &self.typed {
+                if conv.is_match(&path[pos..end]) {
+                    segments.push((pos, end));
+                    node.next(path, next, segments, found);
+                    segments.pop();
+                }
+            }
+            if let Some(ref node) = self.param {
+                segments.push((pos, end));
+                node.next(path, next, segments, found);
+                segments.pop();
+            }
+        }
+    }
+
+    fn next<'a>(
+        &'a self, path: &str, next: Option<usize>, segments: &mut Vec<(usize, usize)>,
+        found: &mut Option<PathMatch<'a>>,
+    ) {
+        if let Some(pos) = next {
+            self.find(path, pos, segments, found);
+        } else if let Some(ref leaf) = self.leaf {
+            if found.as_ref().map_or(true, |m| leaf.idx < m.leaf.idx) {
+                *found = Some(PathMatch {
+                    leaf,
+                    segments: segments.clone(),
+                });
+            }
+        }
+    }
+}
+
+/// Route of the application route table
+#[derive(Debug, Clone)]
+pub struct RouteEntry {
+    pub(crate) pattern: String,
+    pub(crate) name: String,
+    pub(crate) methods: Option<Vec<Method>>,
+    pub(crate) predicates: Vec<&'static str>,
+    pub(crate) middlewares: usize,
+    pub(crate) handler: &'static str,
+    #[cfg(feature = "openapi")]
+    pub(crate) operation: Option<Operation>,
+    #[cfg(feature = "openapi")]
+    pub(crate) extractor: Option<ExtractorApi>,
+}
+
+impl RouteEntry {
+    pub(crate) fn new(
+        prefix: &str, middlewares: usize, predicates: Vec<&'static str>,
+    ) -> RouteEntry {
+        RouteEntry {
+            middlewares,
+            predicates,
+            pattern: prefix.to_owned(),
+            name: String::new(),
+            methods: None,
+            handler: "",
+            #[cfg(feature = "openapi")]
+            operation: None,
+            #[cfg(feature = "openapi")]
+            extractor: None,
+        }
+    }
+
+    /// Entry for nested path
+    pub(crate) fn nested(
+        &self, path: &str, predicates: Vec<&'static str>,
+    ) -> RouteEntry {
+        let mut pattern = self.pattern.clone();
+        join_pattern(&mut pattern, path);
+        let mut preds = self.predicates.clone();
+        preds.extend(predicates);
+        RouteEntry::new(&pattern, self.middlewares, preds)
+    }
+
+    /// Full path pattern, including application prefix and scopes
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Resource name, empty if resource is not named
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Methods matched by the route, `None` if route matches any method
+    pub fn methods(&self) -> Option<&[Method]> {
+        self.methods.as_ref().map(|m| m.as_slice())
+    }
+
+    /// Type names of route, scope and application predicates.
+    ///
+    /// Method predicates are not listed, see `methods()`.
+    pub fn predicates(&self) -> &[&'static str] {
+        &self.predicates
+    }
+
+    /// Number of middlewares applied to the route
+    pub fn middlewares(&self) -> usize {
+        self.middlewares
+    }
+
+    /// Type name of the route handler, empty if handler is not set.
+    ///
+    /// Names are available with rustc 1.38 or later, older compilers
+    /// leave them empty.
+    pub fn handler(&self) -> &'static str {
+        self.handler
+    }
+
+    fn methods_str(&self) -> String {
+        match self.methods {
+            Some(ref methods) => methods
+                .iter()
+                .map(|m| m.as_str())
+                .collect::<Vec<_>>()
+                .join(","),
+            None => "*".to_owned(),
+        }
+    }
+}
+
+impl Serialize for RouteEntry {
+    fn serialize<T: Serializer>(&self, serializer: T) -> Result<T::Ok, T::Error> {
+        let methods: Option<Vec<_>> = self
+            .methods
+            .as_ref()
+            .map(|methods| methods.iter().map(|m| m.as_str()).collect());
+        let mut s = serializer.serialize_struct("RouteEntry", 6)?;
+        s.serialize_field("pattern", &self.pattern)?;
+        s.serialize_field("name", &self.name)?;
+        s.serialize_field("methods", &methods)?;
+        s.serialize_field("predicates", &self.predicates)?;
+        s.serialize_field("middlewares", &self.middlewares)?;
+        s.serialize_field("handler", &self.handler)?;
+        s.end()
+    }
+}
+
+/// Flattened route table of the application.
+///
+/// Table lists every route of application resources, scopes, nested
+/// scopes and handlers in registration order. `Display` implementation
+/// renders the table as text, table could be serialized with `serde` or
+/// returned from a handler.
+///
+/// ```rust
+/// # extern crate actix_web;
+/// use actix_web::{http, App, HttpResponse};
+///
+/// fn main() {
+///     let app = App::new()
+///         .prefix("/app")
+///         .resource("/index.html", |r| r.get().f(|_| HttpResponse::Ok()))
+///         .scope("/api", |scope| {
+///             scope.route("/users", http::Method::POST, |_: String| "done")
+///         });
+///
+///     // print route table at startup
+///     let routes = app.routes();
+///     println!("{}", routes);
+///     assert_eq!(routes.iter().next().unwrap().pattern(), "/app/index.html");
+///
+///     // serve it from debug endpoint
+///     let app = app.resource("/_routes", move |r| {
+///         r.get().f(move |_| routes.clone())
+///     });
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RouteTable(
+    Vec<RouteEntry>,
+    // converters of the application, used by openapi documents
+    #[cfg_attr(not(feature = "openapi"), allow(dead_code))] Converters,
+);
+
+impl RouteTable {
+    pub(crate) fn new(entries: Vec<RouteEntry>, converters: Converters) -> RouteTable {
+        RouteTable(entries, converters)
+    }
+
+    /// Find built-in or application converter
+    #[cfg(feature = "openapi")]
+    pub(crate) fn converter(&self, name: &str) -> Option<Converter> {
+        self.1.get(name)
+    }
+
+    /// Iterate over routes
+    pub fn iter(&self) -> slice::Iter<RouteEntry> {
+        self.0.iter()
+    }
+
+    /// Number of routes
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Check if table is empty
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<'a> IntoIterator for &'a RouteTable {
+    type Item = &'a RouteEntry;
+    type IntoIter = slice::Iter<'a, RouteEntry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl Serialize for RouteTable {
+    fn serialize<T: Serializer>(&self, serializer: T) -> Result<T::Ok, T::Error> {
+        serializer.collect_seq(self.0.iter())
+    }
+}
+
+/// Type name without module path and type parameters
+fn short_name(name: &str) -> &str {
+    let name = name.split('<').next().unwrap();
+    name.rsplit("::").next().unwrap()
+}
+
+impl fmt::Display for RouteTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn or_dash(s: String) -> String {
+            if s.is_empty() {
+                "-".to_owned()
+            } else {
+                s
+            }
+        }
+
+        let mut rows = vec![[
+            "METHODS".to_owned(),
+            "PATTERN".to_owned(),
+            "NAME".to_owned(),
+            "MIDDLEWARES".to_owned(),
+            "PREDICATES".to_owned(),
+            "HANDLER".to_owned(),
+        ]];
+        for entry in &self.0 {
+            let preds: Vec<_> = entry.predicates.iter().map(|p| short_name(p)).collect();
+            rows.push([
+                entry.methods_str(),
+                or_dash(entry.pattern.clone()),
+                or_dash(entry.name.clone()),
+                entry.middlewares.to_string(),
+                or_dash(preds.join(",")),
+                or_dash(entry.handler.to_owned()),
+            ]);
+        }
+
+        let mut widths = [0; 6];
+        for row in &rows {
+            for (width, col) in widths.iter_mut().zip(row.iter()) {
+                *width = max(*width, col.len());
+            }
+        }
+        for row in &rows {
+            let cols: Vec<_> = row
+                .iter()
+                .zip(widths.iter())
+                .map(|(col, width)| format!("{:1$}", col, width))
+                .collect();
+            writeln!(f, "{}", cols.join("  ").trim_right())?;
+        }
+        Ok(())
+    }
+}
+
+impl Responder for RouteTable {
+    type Item = HttpResponse;
+    type Error = Error;
+
+    fn respond_to<S>(self, req: &HttpRequest<S>) -> Result<HttpResponse, Error> {
+        Ok(HttpResponse::build_from(req)
+            .content_type("text/plain; charset=utf-8")
+            .body(self.to_string()))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 enum PatternElement {
     Str(String),
@@ -515,6 +1074,14 @@ enum PatternType {
     Dynamic(Regex, Vec<Rc<String>>, usize),
 }
 
+/// Path segment of a pattern which could be matched without regex
+#[derive(Clone, Debug)]
+enum PathSegment {
+    Static(String),
+    Param(Rc<String>),
+    Typed(Rc<String>, Converter),
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 /// Resource type
 pub enum ResourceType {
@@ -536,6 +1103,11 @@ pub struct ResourceDef {
     name: String,
     pattern: String,
     elements: Vec<PatternElement>,
+    segments: Option<Vec<PathSegment>>,
+    converters: Vec<(String, Converter)>,
+    /// Parameter patterns which could name application converters
+    custom: Vec<String>,
+    for_prefix: bool,
 }
 
 impl ResourceDef {
@@ -570,31 +1142,65 @@ impl ResourceDef {
         if slash && !path.starts_with('/') {
             path.insert(0, '/');
         }
-        let (pattern, elements, is_dynamic, len) = ResourceDef::parse(&path, for_prefix);
+        ResourceDef::build(path, for_prefix, &Converters::default())
+    }
+
+    fn build(path: String, for_prefix: bool, app_converters: &Converters) -> Self {
+        let mut converters = Vec::new();
+        let mut custom = Vec::new();
+        let (pattern, elements, is_dynamic, len) = ResourceDef::parse(
+            &path,
+            for_prefix,
+            app_converters,
+            &mut converters,
+            &mut custom,
+        );
 
-        let tp = if is_dynamic {
+        let (tp, segments) = if is_dynamic {
             let re = match Regex::new(&pattern) {
                 Ok(re) => re,
                 Err(err) => panic!("Wrong path pattern: \"{}\" {}", path, err),
             };
             // actix creates one router per thread
-            let names = re
+            let names: Vec<_> = re
                 .capture_names()
                 .filter_map(|name| name.map(|name| Rc::new(name.to_owned())))
                 .collect();
-            PatternType::Dynamic(re, names, len)
+            let segments = if for_prefix {
+                None
+            } else {
+                ResourceDef::parse_segments(&path, &names, &converters)
+            };
+            (PatternType::Dynamic(re, names, len), segments)
         } else if for_prefix {
-            PatternType::Prefix(pattern.clone())
+            (PatternType::Prefix(pattern.clone()), None)
         } else {
-            PatternType::Static(pattern.clone())
+            let segments = ResourceDef::parse_segments(&path, &[], &[]);
+            (PatternType::Static(pattern.clone()), segments)
         };
 
         ResourceDef {
             tp,
             elements,
+            segments,
+            converters,
+            custom,
+            for_prefix,
             name: "".to_string(),
             rtp: ResourceType::Normal,
-            pattern: path.to_owned(),
+            pattern: path,
+        }
+    }
+
+    /// Use converters registered on application for `{name:converter}`
+    /// parameters, pattern is parsed again only if it refers to them
+    pub(crate) fn resolve(&mut self, converters: &Converters) {
+        if self.custom.iter().any(|name| converters.contains(name)) {
+            let mut rdef =
+                ResourceDef::build(self.pattern.clone(), self.for_prefix, converters);
+            rdef.name = mem::replace(&mut self.name, String::new());
+            rdef.rtp = self.rtp;
+            *self = rdef;
         }
     }
 
@@ -613,6 +1219,13 @@ impl ResourceDef {
         self.name = name.to_owned();
     }
 
+    fn is_dynamic(&self) -> bool {
+        match self.tp {
+            PatternType::Dynamic(..) => true,
+            _ => false,
+        }
+    }
+
     /// Path pattern of the resource
     pub fn pattern(&self) -> &str {
         &self.pattern
@@ -622,11 +1235,30 @@ impl ResourceDef {
     pub fn is_match(&self, path: &str) -> bool {
         match self.tp {
             PatternType::Static(ref s) => s == path,
-            PatternType::Dynamic(ref re, _, _) => re.is_match(path),
+            PatternType::Dynamic(ref re, _, _) => if self.converters.is_empty() {
+                re.is_match(path)
+            } else {
+                self.captures(re, path).is_some()
+            },
             PatternType::Prefix(ref s) => path.starts_with(s),
         }
     }
 
+    /// Match path with regex and check values of typed parameters
+    fn captures<'t>(&self, re: &Regex, path: &'t str) -> Option<Captures<'t>> {
+        let captures = re.captures(path)?;
+        let valid = self.converters.iter().all(|&(ref name, ref conv)| {
+            captures
+                .name(name)
+                .map_or(true, |m| conv.is_valid(m.as_str()))
+        });
+        if valid {
+            Some(captures)
+        } else {
+            None
+        }
+    }
+
     fn is_prefix_match(&self, path: &str) -> Option<usize> {
         let plen = path.len();
         let path = if path.is_empty() { "/" } else { path };
@@ -638,7 +1270,7 @@ impl ResourceDef {
                 None
             },
             PatternType::Dynamic(ref re, _, len) => {
-                if let Some(captures) = re.captures(path) {
+                if let Some(captures) = self.captures(re, path) {
                     let mut pos = 0;
                     let mut passed = false;
                     for capture in captures.iter() {
@@ -686,7 +1318,7 @@ impl ResourceDef {
                 Some(Params::with_url(req.url()))
             },
             PatternType::Dynamic(ref re, ref names, _) => {
-                if let Some(captures) = re.captures(path) {
+                if let Some(captures) = self.captures(re, path) {
                     let mut params = Params::with_url(req.url());
                     let mut idx = 0;
                     let mut passed = false;
@@ -736,7 +1368,7 @@ impl ResourceDef {
                 None
             },
             PatternType::Dynamic(ref re, ref names, len) => {
-                if let Some(captures) = re.captures(path) {
+                if let Some(captures) = self.captures(re, path) {
                     let mut params = Params::with_url(req.url());
                     let mut pos = 0;
                     let mut passed = false;
@@ -801,9 +1433,17 @@ impl ResourceDef {
                 for el in &self.elements {
                     match *el {
                         PatternElement::Str(ref s) => path.push_str(s),
-                        PatternElement::Var(_) => {
+                        PatternElement::Var(ref name) => {
                             if let Some(val) = elements.next() {
-                                path.push_str(val.as_ref())
+                                let val = val.as_ref();
+                                for &(ref n, ref conv) in &self.converters {
+                                    if n == name && !conv.is_match(val) {
+                                        return Err(UrlGenerationError::InvalidParam(
+                                            name.clone(),
+                                        ));
+                                    }
+                                }
+                                path.push_str(val)
                             } else {
                                 return Err(UrlGenerationError::NotEnoughElements);
                             }
@@ -815,8 +1455,56 @@ impl ResourceDef {
         Ok(())
     }
 
+    /// Split pattern into static and `{param}` segments.
+    ///
+    /// Returns `None` if pattern contains parameters with custom regex,
+    /// converters which match several segments or parameters mixed with
+    /// static text within one segment.
+    fn parse_segments(
+        path: &str, names: &[Rc<String>], converters: &[(String, Converter)],
+    ) -> Option<Vec<PathSegment>> {
+        if !path.starts_with('/') {
+            return None;
+        }
+        let mut names = names.iter();
+        let mut segments = Vec::new();
+        for seg in path[1..].split('/') {
+            if !seg.contains('{') && !seg.contains('}') {
+                segments.push(PathSegment::Static(seg.to_owned()));
+            } else if seg.starts_with('{') && seg.ends_with('}') {
+                let param = &seg[1..seg.len() - 1];
+                if param.contains(|c| c == '{' || c == '}') {
+                    return None;
+                }
+                let (name, converter) = match param.find(':') {
+                    Some(pos) => {
+                        let name = &param[..pos];
+                        match converters.iter().find(|c| c.0 == name) {
+                            Some(&(_, ref conv)) if conv.is_segment() => {
+                                (name, Some(conv.clone()))
+                            }
+                            _ => return None,
+                        }
+                    }
+                    None => (param, None),
+                };
+                match names.next() {
+                    Some(n) if n.as_str() == name => segments.push(match converter {
+                        Some(conv) => PathSegment::Typed(n.clone(), conv),
+                        None => PathSegment::Param(n.clone()),
+                    }),
+                    _ => return None,
+                }
+            } else {
+                return None;
+            }
+        }
+        Some(segments)
+    }
+
     fn parse(
-        pattern: &str, for_prefix: bool,
+        pattern: &str, for_prefix: bool, app_converters: &Converters,
+        converters: &mut Vec<(String, Converter)>, custom: &mut Vec<String>,
     ) -> (String, Vec<PatternElement>, bool, usize) {
         const DEFAULT_PATTERN: &str = "[^/]+";
 
@@ -836,6 +1524,17 @@ impl ResourceDef {
                 // In parameter segment: `{....}`
                 if ch == '}' {
                     elems.push(PatternElement::Var(param_name.clone()));
+                    let converter = if in_param_pattern {
+                        app_converters.get(param_pattern.trim())
+                    } else {
+                        None
+                    };
+                    if let Some(conv) = converter {
+                        param_pattern = conv.regex().to_owned();
+                        converters.push((param_name.clone(), conv));
+                    } else if in_param_pattern {
+                        custom.push(param_pattern.trim().to_owned());
+                    }
                     re1.push_str(&format!(r"(?P<{}>{})", &param_name, &param_pattern));
 
                     param_name.clear();
@@ -901,7 +1600,10 @@ impl Hash for ResourceDef {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use super::*;
+    use param::ParamConverter;
     use test::TestRequest;
 
     #[test]
@@ -982,6 +1684,62 @@ mod tests {
         assert_eq!(info.resource, ResourceId::Normal(1));
     }
 
+    #[test]
+    fn test_recognizer_order() {
+        let mut router = Router::<()>::default();
+        router.register_resource(Resource::new(ResourceDef::new("/{a}/{b}")));
+        router.register_resource(Resource::new(ResourceDef::new("/user/{id:\\d+}")));
+        router.register_resource(Resource::new(ResourceDef::new("/user/new")));
+        router.register_resource(Resource::new(ResourceDef::new("/files/{name}.{ext}")));
+        router.register_resource(Resource::new(ResourceDef::new("/files/{name}")));
+        router.register_resource(Resource::new(ResourceDef::new("/files/{name}")));
+        router.register_resource(Resource::new(ResourceDef::new("/files/")));
+
+        // first registered pattern wins
+        let req = TestRequest::with_uri("/user/new").finish();
+        let info = router.recognize(&req, &(), 0);
+        assert_eq!(info.resource, ResourceId::Normal(0));
+        assert_eq!(&info.match_info()["a"], "user");
+        assert_eq!(&info.match_info()["b"], "new");
+
+        let req = TestRequest::with_uri("/files/index.html").finish();
+        let info = router.recognize(&req, &(), 0);
+        assert_eq!(info.resource, ResourceId::Normal(0));
+
+        let req = TestRequest::with_uri("/files/index.html/").finish();
+        let info = router.recognize(&req, &(), 0);
+        assert_eq!(info.resource, ResourceId::Default);
+
+        let req = TestRequest::with_uri("/files/").finish();
+        let info = router.recognize(&req, &(), 0);
+        assert_eq!(info.resource, ResourceId::Normal(6));
+        assert!(info.match_info().is_empty());
+
+        let mut router = Router::<()>::default();
+        router.register_resource(Resource::new(ResourceDef::new("/user/{id:\\d+}")));
+        router.register_resource(Resource::new(ResourceDef::new("/user/{name}")));
+        router.register_resource(Resource::new(ResourceDef::new("/user/{name}/")));
+
+        let req = TestRequest::with_uri("/user/123").finish();
+        let info = router.recognize(&req, &(), 0);
+        assert_eq!(info.resource, ResourceId::Normal(0));
+        assert_eq!(&info.match_info()["id"], "123");
+
+        let req = TestRequest::with_uri("/user/bob").finish();
+        let info = router.recognize(&req, &(), 0);
+        assert_eq!(info.resource, ResourceId::Normal(1));
+        assert_eq!(&info.match_info()["name"], "bob");
+        assert_eq!(info.match_info().unprocessed(), "");
+
+        let req = TestRequest::with_uri("/user/bob/").finish();
+        let info = router.recognize(&req, &(), 0);
+        assert_eq!(info.resource, ResourceId::Normal(2));
+
+        let req = TestRequest::with_uri("/user//").finish();
+        let info = router.recognize(&req, &(), 0);
+        assert_eq!(info.resource, ResourceId::Default);
+    }
+
     #[test]
     fn test_recognizer_with_prefix() {
         let mut router = Router::<()>::default();
@@ -1074,6 +1832,156 @@ mod tests {
         assert_eq!(info.get("id").unwrap(), "adahg32");
     }
 
+    #[test]
+    fn test_parse_converters() {
+        let re = ResourceDef::new("/user/{id:u8}/{sign: i8 }");
+        assert!(re.is_match("/user/255/-128"));
+        assert!(!re.is_match("/user/256/0"));
+        assert!(!re.is_match("/user/1/-129"));
+        assert!(!re.is_match("/user/-1/0"));
+        assert!(!re.is_match("/user/profile/0"));
+
+        let req = TestRequest::with_uri("/user/12/-3").finish();
+        let info = re.match_with_params(&req, 0).unwrap();
+        assert_eq!(info.query::<u8>("id").unwrap(), 12);
+        assert_eq!(info.get("sign").unwrap(), "-3");
+
+        let req = TestRequest::with_uri("/user/1024/0").finish();
+        assert!(re.match_with_params(&req, 0).is_none());
+
+        let re = ResourceDef::new("/item/{id:uuid}");
+        assert!(re.is_match("/item/67e55044-10b1-426f-9247-bb680e5fe0c8"));
+        assert!(!re.is_match("/item/67e55044-10b1-426f-9247"));
+
+        let re = ResourceDef::new("/static/{tail:*}");
+        assert!(re.is_match("/static/"));
+        let req = TestRequest::with_uri("/static/css/main.css").finish();
+        let info = re.match_with_params(&req, 0).unwrap();
+        assert_eq!(info.get("tail").unwrap(), "css/main.css");
+
+        let re = ResourceDef::prefix("/{id:u16}");
+        let req = TestRequest::with_uri("/65535/index.html").finish();
+        let info = re.match_prefix_with_params(&req, 0).unwrap();
+        assert_eq!(info.get("id").unwrap(), "65535");
+        let req = TestRequest::with_uri("/65536/index.html").finish();
+        assert!(re.match_prefix_with_params(&req, 0).is_none());
+    }
+
+    #[test]
+    fn test_register_converter() {
+        struct Even;
+
+        impl ParamConverter for Even {
+            fn regex(&self) -> &str {
+                "[0-9]+"
+            }
+
+            fn is_valid(&self, value: &str) -> bool {
+                value.parse::<u64>().map(|v| v % 2 == 0).unwrap_or(false)
+            }
+        }
+
+        let mut router = Router::<()>::new(ResourceDef::prefix(""));
+        let mut resource = Resource::new(ResourceDef::new("/num/{n:even}"));
+        resource.name("even");
+        router.register_resource(resource);
+        router.register_resource(Resource::new(ResourceDef::new("/num/{n}")));
+        assert_eq!(router.linear, vec![0]);
+
+        let mut converters = Converters::default();
+        converters.register("even", Arc::new(Even));
+        router.finish(&converters);
+        assert!(router.linear.is_empty());
+
+        let req = TestRequest::with_uri("/num/10").finish();
+        let info = router.recognize(&req, &(), 0);
+        assert_eq!(info.resource, ResourceId::Normal(0));
+
+        let req = TestRequest::with_uri("/num/11").finish();
+        let info = router.recognize(&req, &(), 0);
+        assert_eq!(info.resource, ResourceId::Normal(1));
+
+        let req = TestRequest::with_uri("/").request();
+        let info = router.default_route_info();
+        let url = info.url_for(&req, "even", &["4"]).unwrap();
+        assert_eq!(url.as_str(), "http://localhost:8080/num/4");
+        assert_eq!(
+            info.url_for(&req, "even", &["5"]),
+            Err(UrlGenerationError::InvalidParam("n".to_owned()))
+        );
+        assert_eq!(
+            info.url_for(&req, "even", &["a4"]),
+            Err(UrlGenerationError::InvalidParam("n".to_owned()))
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_register_builtin_converter() {
+        struct Any;
+
+        impl ParamConverter for Any {
+            fn regex(&self) -> &str {
+                ".*"
+            }
+        }
+
+        Converters::default().register("u64", Arc::new(Any));
+    }
+
+    #[test]
+    fn test_resolve_converters() {
+        struct Hex;
+
+        impl ParamConverter for Hex {
+            fn regex(&self) -> &str {
+                "[0-9a-f]+"
+            }
+        }
+
+        let mut rdef = ResourceDef::external("https://host/{id:hex}/{name:[a-z]+}");
+        rdef.name = "ext".to_owned();
+        rdef.resolve(&Converters::default());
+        assert!(rdef.converters.is_empty());
+
+        let mut converters = Converters::default();
+        converters.register("hex", Arc::new(Hex));
+        rdef.resolve(&converters);
+        assert_eq!(rdef.name(), "ext");
+        assert_eq!(rdef.rtype(), ResourceType::External);
+        assert_eq!(rdef.converters.len(), 1);
+        assert_eq!(rdef.custom, vec!["[a-z]+".to_owned()]);
+    }
+
+    #[test]
+    fn test_recognizer_typed_segments() {
+        let mut router = Router::<()>::new(ResourceDef::prefix(""));
+        router.register_resource(Resource::new(ResourceDef::new("/item/{id:u8}")));
+        router.register_resource(Resource::new(ResourceDef::new("/item/{id:uuid}")));
+        router.register_resource(Resource::new(ResourceDef::new("/item/{id}")));
+        router.register_resource(Resource::new(ResourceDef::new("/file/{tail:*}")));
+        assert_eq!(router.linear, vec![3]);
+
+        let req = TestRequest::with_uri("/item/12").finish();
+        let info = router.recognize(&req, &(), 0);
+        assert_eq!(info.resource, ResourceId::Normal(0));
+        assert_eq!(info.match_info().get("id").unwrap(), "12");
+
+        let req = TestRequest::with_uri("/item/67e55044-10b1-426f-9247-bb680e5fe0c8")
+            .finish();
+        let info = router.recognize(&req, &(), 0);
+        assert_eq!(info.resource, ResourceId::Normal(1));
+
+        let req = TestRequest::with_uri("/item/256").finish();
+        let info = router.recognize(&req, &(), 0);
+        assert_eq!(info.resource, ResourceId::Normal(2));
+        assert_eq!(info.match_info().get("id").unwrap(), "256");
+
+        let req = TestRequest::with_uri("/file/css/main.css").finish();
+        let info = router.recognize(&req, &(), 0);
+        assert_eq!(info.resource, ResourceId::Normal(3));
+    }
+
     #[test]
     fn test_resource_prefix() {
         let re = ResourceDef::prefix("/name");
@@ -1165,7 +2073,7 @@ mod tests {
         let scope = Scope::new("/test2")
             .nested("/test10", |s| s.resource("/name", |r| r.name("r2")));
         router.register_scope(scope);
-        router.finish();
+        router.finish(&Converters::default());
 
         let req = TestRequest::with_uri("/test").request();
         {
@@ -1219,7 +2127,7 @@ mod tests {
             s.resource("/{name3}/test/index.{ext}", |r| r.name("r2"))
         });
         router.register_scope(scope);
-        router.finish();
+        router.finish(&Converters::default());
 
         let req = TestRequest::with_uri("/test").request();
         {
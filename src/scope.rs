@@ -10,12 +10,13 @@ use http::Method;
 use httprequest::HttpRequest;
 use httpresponse::HttpResponse;
 use middleware::{
-    Finished as MiddlewareFinished, Middleware, Response as MiddlewareResponse,
-    Started as MiddlewareStarted,
+    with_deadline, Finished as MiddlewareFinished, Middleware,
+    Response as MiddlewareResponse, Started as MiddlewareStarted,
 };
+use param::Converters;
 use pred::Predicate;
 use resource::{DefaultResource, Resource};
-use router::{ResourceDef, Router};
+use router::{ResourceDef, RouteEntry, Router};
 use server::Request;
 use with::WithFactory;
 
@@ -329,10 +330,15 @@ impl<S: 'static> RouteHandler<S> for Scope<S> {
             .register_default_resource(default);
     }
 
-    fn finish(&mut self) {
+    fn finish(&mut self, converters: &Converters) {
         Rc::get_mut(&mut self.router)
             .expect("Can not use after configuration")
-            .finish();
+            .finish(converters);
+    }
+
+    fn route_table(&self, mut entry: RouteEntry, table: &mut Vec<RouteEntry>) {
+        entry.middlewares += self.middlewares.len();
+        self.router.route_table(&entry, table);
     }
 }
 
@@ -346,6 +352,14 @@ impl<S: 'static, S2: 'static> RouteHandler<S2> for Wrapper<S> {
         let req = req.with_state(Rc::clone(&self.state));
         self.scope.handle(&req)
     }
+
+    fn finish(&mut self, converters: &Converters) {
+        self.scope.finish(converters)
+    }
+
+    fn route_table(&self, entry: RouteEntry, table: &mut Vec<RouteEntry>) {
+        self.scope.route_table(entry, table)
+    }
 }
 
 struct FiltersWrapper<S: 'static> {
@@ -362,6 +376,10 @@ impl<S: 'static, S2: 'static> Predicate<S2> for FiltersWrapper<S> {
         }
         true
     }
+
+    fn names(&self) -> Vec<&'static str> {
+        self.filters.iter().flat_map(|f| f.names()).collect()
+    }
 }
 
 /// Compose resource level middlewares with route handler.
@@ -528,7 +546,7 @@ impl<S: 'static> WaitingResponse<S> {
             AsyncResultItem::Ok(resp) => RunMiddlewares::init(info, resp),
             AsyncResultItem::Err(err) => RunMiddlewares::init(info, err.into()),
             AsyncResultItem::Future(fut) => ComposeState::Handler(WaitingResponse {
-                fut,
+                fut: with_deadline(&info.req, fut),
                 _s: PhantomData,
             }),
         }
@@ -800,7 +818,7 @@ mod tests {
             .method(Method::POST)
             .request();
         let resp = app.run(req);
-        assert_eq!(resp.as_msg().status(), StatusCode::NOT_FOUND);
+        assert_eq!(resp.as_msg().status(), StatusCode::METHOD_NOT_ALLOWED);
     }
 
     #[test]
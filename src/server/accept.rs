@@ -1,6 +1,6 @@
 use std::sync::mpsc as sync_mpsc;
 use std::time::{Duration, Instant};
-use std::{io, net, thread};
+use std::{io, thread};
 
 use futures::{sync::mpsc, Future};
 use mio;
@@ -9,6 +9,7 @@ use tokio_timer::Delay;
 
 use actix::{msgs::Execute, Arbiter, System};
 
+use super::listener::{ListenerAddr, MioListener, StdListener, StdStream};
 use super::server::ServerCommand;
 use super::worker::{Conn, WorkerClient};
 use super::Token;
@@ -21,10 +22,10 @@ pub(crate) enum Command {
 }
 
 struct ServerSocketInfo {
-    addr: net::SocketAddr,
+    addr: ListenerAddr,
     token: Token,
     handler: Token,
-    sock: mio::net::TcpListener,
+    sock: MioListener,
     timeout: Option<Instant>,
 }
 
@@ -87,7 +88,7 @@ impl AcceptLoop {
     }
 
     pub(crate) fn start(
-        &mut self, socks: Vec<Vec<(Token, net::TcpListener)>>,
+        &mut self, socks: Vec<Vec<(Token, StdListener)>>,
         workers: Vec<WorkerClient>,
     ) -> mpsc::UnboundedReceiver<ServerCommand> {
         let (tx, rx) = self.srv.take().expect("Can not re-use AcceptInfo");
@@ -137,7 +138,7 @@ impl Accept {
     #![cfg_attr(feature = "cargo-clippy", allow(too_many_arguments))]
     pub(crate) fn start(
         rx: sync_mpsc::Receiver<Command>, cmd_reg: mio::Registration,
-        notify_reg: mio::Registration, socks: Vec<Vec<(Token, net::TcpListener)>>,
+        notify_reg: mio::Registration, socks: Vec<Vec<(Token, StdListener)>>,
         srv: mpsc::UnboundedSender<ServerCommand>, workers: Vec<WorkerClient>,
     ) {
         let sys = System::current();
@@ -174,7 +175,7 @@ impl Accept {
     }
 
     fn new(
-        rx: sync_mpsc::Receiver<Command>, socks: Vec<Vec<(Token, net::TcpListener)>>,
+        rx: sync_mpsc::Receiver<Command>, socks: Vec<Vec<(Token, StdListener)>>,
         workers: Vec<WorkerClient>, srv: mpsc::UnboundedSender<ServerCommand>,
     ) -> Accept {
         // Create a poll instance
@@ -187,9 +188,8 @@ impl Accept {
         let mut sockets = Slab::new();
         for (idx, srv_socks) in socks.into_iter().enumerate() {
             for (hnd_token, lst) in srv_socks {
-                let addr = lst.local_addr().unwrap();
-                let server = mio::net::TcpListener::from_std(lst)
-                    .expect("Can not create mio::net::TcpListener");
+                let addr = lst.local_addr();
+                let server = lst.into_mio().expect("Can not create mio listener");
 
                 let entry = sockets.vacant_entry();
                 let token = entry.key();
@@ -365,7 +365,7 @@ impl Accept {
         }
     }
 
-    fn accept_one(&mut self, mut msg: Conn<net::TcpStream>) {
+    fn accept_one(&mut self, mut msg: Conn<StdStream>) {
         if self.backpressure {
             while !self.workers.is_empty() {
                 match self.workers[self.next].send(msg) {
@@ -426,12 +426,12 @@ impl Accept {
     fn accept(&mut self, token: usize) {
         loop {
             let msg = if let Some(info) = self.sockets.get_mut(token) {
-                match info.sock.accept_std() {
-                    Ok((io, addr)) => Conn {
+                match info.sock.accept() {
+                    Ok((io, peer)) => Conn {
                         io,
                         token: info.token,
                         handler: info.handler,
-                        peer: Some(addr),
+                        peer,
                     },
                     Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => return,
                     Err(ref e) if connection_error(e) => continue,
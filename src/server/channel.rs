@@ -79,7 +79,7 @@ where
     fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
         if self.node.is_some() {
             let el = self as *mut _;
-            self.node = Some(Node::new(el));
+            self.node = Some(Node::new(el, shutdown_channel::<T, H>));
             let _ = match self.proto {
                 Some(HttpProtocol::H1(ref mut h1)) => {
                     self.node.as_mut().map(|n| h1.settings().head().insert(n))
@@ -167,18 +167,36 @@ where
     }
 }
 
+/// Shut down channel, `el` points to `HttpChannel<T, H>`
+unsafe fn shutdown_channel<T, H>(el: *mut ())
+where
+    T: IoStream,
+    H: HttpHandler + 'static,
+{
+    let ch = &mut *(el as *mut HttpChannel<T, H>);
+    ch.shutdown();
+}
+
+/// Node of the channels list.
+///
+/// Channels of different stream types share one list, so node keeps
+/// shutdown function of its own channel type. Layout does not depend
+/// on `T`, nodes are traversed as `Node<()>`.
+#[repr(C)]
 pub(crate) struct Node<T> {
     next: Option<*mut Node<T>>,
     prev: Option<*mut Node<T>>,
     element: *mut T,
+    shutdown: Option<unsafe fn(*mut ())>,
 }
 
 impl<T> Node<T> {
-    fn new(el: *mut T) -> Self {
+    fn new(el: *mut T, shutdown: unsafe fn(*mut ())) -> Self {
         Node {
             next: None,
             prev: None,
             element: el,
+            shutdown: Some(shutdown),
         }
     }
 
@@ -190,9 +208,11 @@ impl<T> Node<T> {
                 let n = next2.as_mut().unwrap();
                 n.prev = Some(next);
             }
+            let prev_next = self.next.take();
             self.next = Some(next);
 
             let next: &mut Node<T> = &mut *next;
+            next.next = prev_next;
             next.prev = Some(self as *mut _);
         }
     }
@@ -206,6 +226,9 @@ impl<T> Node<T> {
             if let Some(ref mut prev) = prev {
                 prev.as_mut().unwrap().next = next;
             }
+            if let Some(next) = next {
+                next.as_mut().unwrap().prev = prev;
+            }
         }
     }
 }
@@ -216,14 +239,12 @@ impl Node<()> {
             next: None,
             prev: None,
             element: ptr::null_mut(),
+            shutdown: None,
         }
     }
 
-    pub(crate) fn traverse<T, H>(&self)
-    where
-        T: IoStream,
-        H: HttpHandler + 'static,
-    {
+    /// Shut down all channels of the list
+    pub(crate) fn traverse(&self) {
         let mut next = self.next.as_ref();
         loop {
             if let Some(n) = next {
@@ -232,9 +253,9 @@ impl Node<()> {
                     next = n.next.as_ref();
 
                     if !n.element.is_null() {
-                        let ch: &mut HttpChannel<T, H> =
-                            &mut *(&mut *(n.element as *mut _) as *mut () as *mut _);
-                        ch.shutdown();
+                        if let Some(shutdown) = n.shutdown {
+                            shutdown(n.element);
+                        }
                     }
                 }
             } else {
@@ -326,3 +347,37 @@ where
         self.io.write_buf(buf)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    unsafe fn close_counter(el: *mut ()) {
+        *(el as *mut u32) += 1;
+    }
+
+    unsafe fn close_name(el: *mut ()) {
+        (&mut *(el as *mut String)).push_str(" closed");
+    }
+
+    #[test]
+    fn test_traverse_mixed_nodes() {
+        let mut counter = 0u32;
+        let mut name = "uds".to_owned();
+        let mut removed = 0u32;
+
+        let mut head = Node::head();
+        let mut n1 = Node::new(&mut counter as *mut _, close_counter);
+        let mut n2 = Node::new(&mut name as *mut _, close_name);
+        let mut n3 = Node::new(&mut removed as *mut _, close_counter);
+        head.insert(&mut n3);
+        head.insert(&mut n2);
+        head.insert(&mut n1);
+        n3.remove();
+
+        head.traverse();
+        assert_eq!(counter, 1);
+        assert_eq!(name, "uds closed");
+        assert_eq!(removed, 0);
+    }
+}
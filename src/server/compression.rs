@@ -0,0 +1,301 @@
+use http::header::{CONTENT_LENGTH, CONTENT_TYPE};
+
+use body::Body;
+use header::ContentEncoding;
+use httpresponse::HttpResponse;
+
+/// Types of already compressed content, excluded from compression
+/// by default
+const COMPRESSED_TYPES: &[&str] = &[
+    "image/png",
+    "image/jpeg",
+    "image/gif",
+    "image/webp",
+    "audio/*",
+    "video/*",
+    "font/woff",
+    "font/woff2",
+    "application/zip",
+    "application/gzip",
+    "application/x-gzip",
+    "application/x-bzip2",
+    "application/x-7z-compressed",
+    "application/x-rar-compressed",
+];
+
+/// Response compression policy.
+///
+/// Policy applies to responses with `ContentEncoding::Auto` encoding,
+/// it defines which responses get compressed and which encoding is
+/// selected. Compression levels apply to explicitly encoded responses
+/// as well.
+///
+/// By default responses smaller than 96 bytes and responses with already
+/// compressed content types (images, audio, video, archives) are sent
+/// as is.
+///
+/// ```rust
+/// # extern crate actix_web;
+/// use actix_web::http::ContentEncoding;
+/// use actix_web::server::CompressionPolicy;
+/// use actix_web::{App, HttpResponse};
+///
+/// fn main() {
+///     let app = App::new()
+///         .compression(
+///             CompressionPolicy::new()
+///                 .level(ContentEncoding::Gzip, 6)
+///                 .min_size(1024)
+///                 .include("text/*")
+///                 .include("application/json")
+///                 .preference(&[ContentEncoding::Gzip, ContentEncoding::Br]),
+///         )
+///         .resource("/", |r| r.f(|_| HttpResponse::Ok()))
+///         .finish();
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CompressionPolicy {
+    levels: Vec<(ContentEncoding, u32)>,
+    min_size: usize,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    preference: Vec<ContentEncoding>,
+}
+
+impl Default for CompressionPolicy {
+    fn default() -> CompressionPolicy {
+        CompressionPolicy::new()
+    }
+}
+
+impl CompressionPolicy {
+    /// Create default compression policy.
+    ///
+    /// Fastest level is used for `gzip` and `deflate`, level 3 for `br`
+    /// and `zstd`. Encodings are preferred in `br`, `zstd`, `gzip`, `deflate`
+    /// order.
+    pub fn new() -> CompressionPolicy {
+        let mut levels = Vec::new();
+        let mut preference = Vec::new();
+        #[cfg(feature = "brotli")]
+        {
+            levels.push((ContentEncoding::Br, 3));
+            preference.push(ContentEncoding::Br);
+        }
+        #[cfg(feature = "zstd")]
+        {
+            levels.push((ContentEncoding::Zstd, 3));
+            preference.push(ContentEncoding::Zstd);
+        }
+        #[cfg(feature = "flate2")]
+        {
+            levels.push((ContentEncoding::Gzip, 1));
+            levels.push((ContentEncoding::Deflate, 1));
+            preference.push(ContentEncoding::Gzip);
+            preference.push(ContentEncoding::Deflate);
+        }
+
+        CompressionPolicy {
+            levels,
+            preference,
+            min_size: 96,
+            include: Vec::new(),
+            exclude: COMPRESSED_TYPES.iter().map(|s| (*s).to_owned()).collect(),
+        }
+    }
+
+    /// Set compression level of the encoding.
+    ///
+    /// Levels range from 0 to 9 for `gzip` and `deflate`, from 0 to 11
+    /// for `br` and from 1 to 22 for `zstd`.
+    ///
+    /// Panics if level is out of range.
+    pub fn level(mut self, encoding: ContentEncoding, level: u32) -> Self {
+        if let Some(item) = self.levels.iter_mut().find(|item| item.0 == encoding) {
+            let (min, max) = match encoding {
+                #[cfg(feature = "brotli")]
+                ContentEncoding::Br => (0, 11),
+                #[cfg(feature = "zstd")]
+                ContentEncoding::Zstd => (1, 22),
+                _ => (0, 9),
+            };
+            assert!(
+                level >= min && level <= max,
+                "Compression level is out of range"
+            );
+            item.1 = level;
+        }
+        self
+    }
+
+    /// Set minimal size of the response body.
+    ///
+    /// Smaller responses are not compressed. Size of streaming responses
+    /// is taken from `Content-Length` header, if present. By default
+    /// minimal size is 96 bytes.
+    pub fn min_size(mut self, size: usize) -> Self {
+        self.min_size = size;
+        self
+    }
+
+    /// Compress responses of the content type.
+    ///
+    /// Pattern is either full content type, i.e. `application/json`,
+    /// or type with wildcard subtype, i.e. `text/*`. If any type is
+    /// included, only responses with included content types are compressed.
+    /// Included types take precedence over excluded types.
+    pub fn include(mut self, pattern: &str) -> Self {
+        self.include.push(pattern.trim().to_lowercase());
+        self
+    }
+
+    /// Do not compress responses of the content type.
+    ///
+    /// Pattern syntax is the same as for `include()` method.
+    pub fn exclude(mut self, pattern: &str) -> Self {
+        self.exclude.push(pattern.trim().to_lowercase());
+        self
+    }
+
+    /// Set server preference order of encodings.
+    ///
+    /// Preference is used if client accepts several encodings with the same
+    /// quality. Encodings which are not listed are never selected during
+    /// negotiation.
+    pub fn preference(mut self, encodings: &[ContentEncoding]) -> Self {
+        self.preference = encodings
+            .iter()
+            .filter(|enc| enc.is_compression())
+            .cloned()
+            .collect();
+        self
+    }
+
+    /// Compression level of the encoding
+    pub(crate) fn get_level(&self, encoding: ContentEncoding) -> u32 {
+        self.levels
+            .iter()
+            .find(|item| item.0 == encoding)
+            .map_or(0, |item| item.1)
+    }
+
+    /// Encodings in server preference order
+    pub(crate) fn get_preference(&self) -> &[ContentEncoding] {
+        &self.preference
+    }
+
+    /// Check size and content type of the response
+    pub(crate) fn is_compressible(&self, resp: &HttpResponse) -> bool {
+        let size = match *resp.body() {
+            Body::Empty => Some(0),
+            Body::Binary(ref bin) => Some(bin.len()),
+            _ => resp
+                .headers()
+                .get(CONTENT_LENGTH)
+                .and_then(|len| len.to_str().ok())
+                .and_then(|len| len.parse::<usize>().ok()),
+        };
+        if size.map_or(false, |size| size < self.min_size) {
+            return false;
+        }
+
+        let ctype = resp
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|val| val.to_str().ok())
+            .map(|val| val.split(';').next().unwrap().trim().to_lowercase())
+            .unwrap_or_default();
+        if self.include.iter().any(|p| mime_matches(p, &ctype)) {
+            true
+        } else if !self.include.is_empty() {
+            false
+        } else {
+            !self.exclude.iter().any(|p| mime_matches(p, &ctype))
+        }
+    }
+}
+
+fn mime_matches(pattern: &str, ctype: &str) -> bool {
+    if pattern == "*/*" {
+        true
+    } else if pattern.ends_with("/*") {
+        let prefix = &pattern[..pattern.len() - 1];
+        ctype.starts_with(prefix)
+    } else {
+        pattern == ctype
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::header;
+
+    fn resp(ctype: &str, body: &'static str) -> HttpResponse {
+        HttpResponse::Ok().content_type(ctype).body(body)
+    }
+
+    const BODY: &str = "01234567890123456789012345678901234567890123456789\
+                        01234567890123456789012345678901234567890123456789";
+
+    #[test]
+    fn test_min_size() {
+        let policy = CompressionPolicy::new();
+        assert!(!policy.is_compressible(&resp("text/plain", "text")));
+        assert!(policy.is_compressible(&resp("text/plain", BODY)));
+
+        let policy = CompressionPolicy::new().min_size(1024);
+        assert!(!policy.is_compressible(&resp("text/plain", BODY)));
+
+        let resp = HttpResponse::Ok()
+            .header(header::CONTENT_LENGTH, "10")
+            .streaming(::futures::stream::empty::<::bytes::Bytes, ::error::Error>());
+        assert!(!CompressionPolicy::new().is_compressible(&resp));
+    }
+
+    #[test]
+    fn test_content_types() {
+        let policy = CompressionPolicy::new();
+        assert!(policy.is_compressible(&resp("text/html; charset=utf-8", BODY)));
+        assert!(!policy.is_compressible(&resp("image/png", BODY)));
+        assert!(!policy.is_compressible(&resp("Video/MP4", BODY)));
+        assert!(!policy.is_compressible(&resp("application/zip", BODY)));
+
+        let policy = CompressionPolicy::new()
+            .include("text/*")
+            .include("image/png")
+            .exclude("text/csv");
+        assert!(policy.is_compressible(&resp("text/html", BODY)));
+        assert!(policy.is_compressible(&resp("image/png", BODY)));
+        assert!(!policy.is_compressible(&resp("application/json", BODY)));
+        assert!(!policy.is_compressible(&resp("image/jpeg", BODY)));
+
+        let policy = CompressionPolicy::new().exclude("application/*");
+        assert!(!policy.is_compressible(&resp("application/json", BODY)));
+        assert!(policy.is_compressible(&resp("text/plain", BODY)));
+    }
+
+    #[test]
+    fn test_levels() {
+        let policy = CompressionPolicy::new()
+            .level(ContentEncoding::Gzip, 9)
+            .level(ContentEncoding::Br, 11);
+        assert_eq!(policy.get_level(ContentEncoding::Gzip), 9);
+        assert_eq!(policy.get_level(ContentEncoding::Deflate), 1);
+        assert_eq!(policy.get_level(ContentEncoding::Br), 11);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_level_out_of_range() {
+        let _ = CompressionPolicy::new().level(ContentEncoding::Gzip, 10);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    #[should_panic]
+    fn test_zstd_level_out_of_range() {
+        let _ = CompressionPolicy::new().level(ContentEncoding::Zstd, 0);
+    }
+}
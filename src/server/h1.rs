@@ -8,6 +8,7 @@ use futures::{Async, Future, Poll};
 use tokio_timer::Delay;
 
 use error::{Error, PayloadError};
+use extensions::Extensions;
 use http::{StatusCode, Version};
 use payload::{Payload, PayloadStatus, PayloadWriter};
 
@@ -45,6 +46,7 @@ pub(crate) struct Http1<T: IoStream, H: HttpHandler + 'static> {
     settings: Rc<WorkerSettings<H>>,
     addr: Option<SocketAddr>,
     stream: H1Writer<T, H>,
+    extensions: Option<Rc<Extensions>>,
     decoder: H1Decoder,
     payload: Option<PayloadType>,
     buf: BytesMut,
@@ -94,6 +96,7 @@ where
     ) -> Self {
         Http1 {
             flags: Flags::KEEPALIVE,
+            extensions: stream.extensions(),
             stream: H1Writer::new(stream, Rc::clone(&settings)),
             decoder: H1Decoder::new(),
             payload: None,
@@ -369,8 +372,7 @@ where
                     }
 
                     // stream extensions
-                    msg.inner_mut().stream_extensions =
-                        self.stream.get_mut().extensions();
+                    msg.inner_mut().stream_extensions = self.extensions.clone();
 
                     // set remote addr
                     msg.inner_mut().addr = self.addr;
@@ -1,5 +1,7 @@
 // #![cfg_attr(feature = "cargo-clippy", allow(redundant_field_names))]
 
+use std::cmp;
+use std::fs::File;
 use std::io::{self, Write};
 use std::rc::Rc;
 
@@ -7,11 +9,12 @@ use bytes::{BufMut, BytesMut};
 use futures::{Async, Poll};
 use tokio_io::AsyncWrite;
 
+use super::compression::CompressionPolicy;
 use super::helpers;
 use super::output::{Output, ResponseInfo, ResponseLength};
 use super::settings::WorkerSettings;
 use super::Request;
-use super::{Writer, WriterState, MAX_WRITE_BUFFER_SIZE};
+use super::{IoStream, Writer, WriterState, MAX_WRITE_BUFFER_SIZE};
 use body::{Binary, Body};
 use header::ContentEncoding;
 use http::header::{
@@ -97,7 +100,7 @@ impl<T: AsyncWrite, H: 'static> Drop for H1Writer<T, H> {
     }
 }
 
-impl<T: AsyncWrite, H: 'static> Writer for H1Writer<T, H> {
+impl<T: IoStream, H: 'static> Writer for H1Writer<T, H> {
     #[inline]
     fn written(&self) -> u64 {
         self.written
@@ -115,10 +118,12 @@ impl<T: AsyncWrite, H: 'static> Writer for H1Writer<T, H> {
 
     fn start(
         &mut self, req: &Request, msg: &mut HttpResponse, encoding: ContentEncoding,
+        compression: &CompressionPolicy,
     ) -> io::Result<WriterState> {
         // prepare task
         let mut info = ResponseInfo::new(req.inner.method == Method::HEAD);
-        self.buffer.for_server(&mut info, &req.inner, msg, encoding);
+        self.buffer
+            .for_server(&mut info, &req.inner, msg, encoding, compression);
         if msg.keep_alive().unwrap_or_else(|| req.keep_alive()) {
             self.flags = Flags::STARTED | Flags::KEEPALIVE;
         } else {
@@ -313,6 +318,39 @@ impl<T: AsyncWrite, H: 'static> Writer for H1Writer<T, H> {
         }
     }
 
+    fn can_send_file(&self) -> bool {
+        !self.flags.contains(Flags::UPGRADE)
+            && self.buffer.plain_length().is_some()
+            && self.stream.can_send_file()
+    }
+
+    fn send_file(
+        &mut self, file: &File, offset: u64, len: u64,
+    ) -> Poll<usize, io::Error> {
+        // response head and previously buffered data go first
+        if !self.buffer.is_empty() {
+            let written =
+                Self::write_data(&mut self.stream, self.buffer.as_ref().as_ref())?;
+            let _ = self.buffer.split_to(written);
+            if !self.buffer.is_empty() {
+                return Ok(Async::NotReady);
+            }
+        }
+
+        let len = match self.buffer.plain_length() {
+            Some(rem) => cmp::min(rem, len),
+            None => len,
+        };
+        let len = cmp::min(len, usize::max_value() as u64) as usize;
+        let n = try_ready!(self.stream.send_file(file, offset, len));
+        if n == 0 {
+            return Err(io::Error::new(io::ErrorKind::WriteZero, ""));
+        }
+        self.written += n as u64;
+        self.buffer.advance(n as u64);
+        Ok(Async::Ready(n))
+    }
+
     #[inline]
     fn poll_completed(&mut self, shutdown: bool) -> Poll<(), io::Error> {
         if !self.buffer.is_empty() {
@@ -337,7 +375,7 @@ impl<T: AsyncWrite, H: 'static> Writer for H1Writer<T, H> {
         }
         if shutdown {
             self.stream.poll_flush()?;
-            self.stream.shutdown()
+            AsyncWrite::shutdown(&mut self.stream)
         } else {
             self.stream.poll_flush()
         }
@@ -10,6 +10,7 @@ use std::{cmp, io};
 
 use http::{HttpTryFrom, Method, Version};
 
+use super::compression::CompressionPolicy;
 use super::helpers;
 use super::message::Request;
 use super::output::{Output, ResponseInfo, ResponseLength};
@@ -88,11 +89,13 @@ impl<H: 'static> Writer for H2Writer<H> {
 
     fn start(
         &mut self, req: &Request, msg: &mut HttpResponse, encoding: ContentEncoding,
+        compression: &CompressionPolicy,
     ) -> io::Result<WriterState> {
         // prepare response
         self.flags.insert(Flags::STARTED);
         let mut info = ResponseInfo::new(req.inner.method == Method::HEAD);
-        self.buffer.for_server(&mut info, &req.inner, msg, encoding);
+        self.buffer
+            .for_server(&mut info, &req.inner, msg, encoding, compression);
 
         let mut has_date = false;
         let mut resp = Response::new(());
@@ -1,10 +1,17 @@
-use std::marker::PhantomData;
 use std::rc::Rc;
 use std::sync::Arc;
 use std::{io, mem, net, time};
 
+#[cfg(all(unix, feature = "uds"))]
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+#[cfg(all(unix, feature = "uds"))]
+use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(all(unix, feature = "uds"))]
+use std::{fs, path::Path};
+
 use actix::{Actor, Addr, Arbiter, AsyncContext, Context, Handler, System};
 
+use futures::future::{self, Either};
 use futures::{Future, Stream};
 use net2::{TcpBuilder, TcpStreamExt};
 use num_cpus;
@@ -22,6 +29,8 @@ use openssl::ssl::SslAcceptorBuilder;
 use rustls::ServerConfig;
 
 use super::channel::{HttpChannel, WrapperStream};
+use super::listener::{StdListener, StdStream};
+use super::proxy::{IpNet, ProxyProtocol, ProxyStream};
 use super::server::{Connections, Server, Service, ServiceHandler};
 use super::settings::{ServerSettings, WorkerSettings};
 use super::worker::{Conn, Socket};
@@ -50,8 +59,12 @@ where
     no_signals: bool,
     maxconn: usize,
     maxconnrate: usize,
+    #[cfg(all(unix, feature = "uds"))]
+    uds_mode: Option<u32>,
+    proxy: Option<ProxyProtocol>,
+    trusted_proxies: Option<Vec<IpNet>>,
     sockets: Vec<Socket>,
-    handlers: Vec<Box<IoStreamHandler<H::Handler, net::TcpStream>>>,
+    handlers: Vec<Box<IoStreamHandler<H::Handler>>>,
 }
 
 impl<H> HttpServer<H>
@@ -78,6 +91,10 @@ where
             no_signals: false,
             maxconn: 102_400,
             maxconnrate: 256,
+            #[cfg(all(unix, feature = "uds"))]
+            uds_mode: None,
+            proxy: None,
+            trusted_proxies: None,
             // settings: None,
             sockets: Vec::new(),
             handlers: Vec::new(),
@@ -108,6 +125,53 @@ where
         self
     }
 
+    /// Set permissions of the unix domain socket files.
+    ///
+    /// By default socket file gets permissions according to the process umask.
+    ///
+    /// This method should be called before `bind_uds()` method call.
+    #[cfg(all(unix, feature = "uds"))]
+    pub fn uds_permissions(mut self, mode: u32) -> Self {
+        self.uds_mode = Some(mode);
+        self
+    }
+
+    /// Accept PROXY protocol headers on tcp listeners.
+    ///
+    /// Trusted peers must send PROXY protocol header, version 1 or 2,
+    /// before tls handshake or http request. Source address from the
+    /// header is used as peer address of the request.
+    ///
+    /// This method should be called before `bind()` or `listen()` method call.
+    pub fn proxy_protocol(mut self, proxy: ProxyProtocol) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Trust forwarded headers from proxies of the network.
+    ///
+    /// Network is an ip address with optional prefix length, i.e.
+    /// `10.0.0.0/8` or `::1`. Method could be called multiple times.
+    ///
+    /// By default `Forwarded` and `X-Forwarded-*` headers are accepted from
+    /// any peer. Once trusted proxies are configured, these headers are used
+    /// by [ConnectionInfo](./dev/struct.ConnectionInfo.html) only if
+    /// request's peer is one of trusted proxies, and forwarded addresses
+    /// are walked from right to left, skipping trusted proxies.
+    ///
+    /// # Panics
+    ///
+    /// Panics if network can not be parsed.
+    pub fn trusted_proxy(mut self, net: &str) -> Self {
+        let net = net
+            .parse()
+            .unwrap_or_else(|_| panic!("Can not parse network: {}", net));
+        self.trusted_proxies
+            .get_or_insert_with(Vec::new)
+            .push(net);
+        self
+    }
+
     /// Sets the maximum per-worker number of concurrent connections.
     ///
     /// All socket listeners will stop accepting connections when this limit is reached
@@ -185,9 +249,9 @@ where
         self
     }
 
-    /// Get addresses of bound sockets.
+    /// Get addresses of bound tcp sockets.
     pub fn addrs(&self) -> Vec<net::SocketAddr> {
-        self.sockets.iter().map(|s| s.addr).collect()
+        self.sockets.iter().filter_map(|s| s.addr.tcp()).collect()
     }
 
     /// Get addresses of bound sockets and the scheme for it.
@@ -199,7 +263,7 @@ where
     pub fn addrs_with_scheme(&self) -> Vec<(net::SocketAddr, &str)> {
         self.handlers
             .iter()
-            .map(|s| (s.addr(), s.scheme()))
+            .filter_map(|s| s.addr().map(|addr| (addr, s.scheme())))
             .collect()
     }
 
@@ -207,12 +271,32 @@ where
     ///
     /// HttpServer does not change any configuration for TcpListener,
     /// it needs to be configured before passing it to listen() method.
-    pub fn listen(mut self, lst: net::TcpListener) -> Self {
-        let token = Token(self.handlers.len());
+    pub fn listen(self, lst: net::TcpListener) -> Self {
         let addr = lst.local_addr().unwrap();
+        self.listen_std(StdListener::Tcp(lst), Some(addr))
+    }
+
+    #[cfg(all(unix, feature = "uds"))]
+    /// Use unix domain socket listener for accepting incoming connection
+    /// requests
+    ///
+    /// Connections are distributed between workers the same way as tcp
+    /// connections. HttpServer does not change any configuration for
+    /// UnixListener, it needs to be configured before passing it to
+    /// listen_uds() method.
+    pub fn listen_uds(self, lst: UnixListener) -> Self {
+        self.listen_std(StdListener::Uds(lst), None)
+    }
+
+    fn listen_std(mut self, lst: StdListener, addr: Option<net::SocketAddr>) -> Self {
+        let token = Token(self.handlers.len());
         self.handlers
-            .push(Box::new(SimpleHandler::new(lst.local_addr().unwrap())));
-        self.sockets.push(Socket { lst, addr, token });
+            .push(Box::new(SimpleHandler::new(addr, self.proxy.clone())));
+        self.sockets.push(Socket {
+            addr: lst.local_addr(),
+            lst,
+            token,
+        });
 
         self
     }
@@ -221,12 +305,14 @@ where
     /// Use listener for accepting incoming connection requests
     pub fn listen_with<A>(mut self, lst: net::TcpListener, acceptor: A) -> Self
     where
-        A: AcceptorService<TcpStream> + Send + 'static,
+        A: AcceptorService<ProxyStream<TcpStream>> + Send + 'static,
     {
         let token = Token(self.handlers.len());
-        let addr = lst.local_addr().unwrap();
+        let lst = StdListener::Tcp(lst);
+        let addr = lst.local_addr();
         self.handlers.push(Box::new(StreamHandler::new(
-            lst.local_addr().unwrap(),
+            addr.tcp(),
+            self.proxy.clone(),
             acceptor,
         )));
         self.sockets.push(Socket { lst, addr, token });
@@ -288,31 +374,43 @@ where
         let sockets = self.bind2(addr)?;
 
         for lst in sockets {
-            let token = Token(self.handlers.len());
             let addr = lst.local_addr().unwrap();
-            self.handlers
-                .push(Box::new(SimpleHandler::new(lst.local_addr().unwrap())));
-            self.sockets.push(Socket { lst, addr, token })
+            self = self.listen_std(StdListener::Tcp(lst), Some(addr));
         }
 
         Ok(self)
     }
 
+    #[cfg(all(unix, feature = "uds"))]
+    /// The unix domain socket path to bind
+    ///
+    /// Socket file left by a process which is not running anymore gets
+    /// removed, if socket is still in use `AddrInUse` error is returned.
+    /// Use `uds_permissions()` to restrict access to the socket file.
+    ///
+    /// To bind multiple sockets this method can be called multiple times.
+    pub fn bind_uds<P: AsRef<Path>>(self, path: P) -> io::Result<Self> {
+        let lst = create_uds_listener(path.as_ref(), self.uds_mode)?;
+        Ok(self.listen_uds(lst))
+    }
+
     /// Start listening for incoming connections with supplied acceptor.
     #[doc(hidden)]
     #[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
     pub fn bind_with<S, A>(mut self, addr: S, acceptor: A) -> io::Result<Self>
     where
         S: net::ToSocketAddrs,
-        A: AcceptorService<TcpStream> + Send + 'static,
+        A: AcceptorService<ProxyStream<TcpStream>> + Send + 'static,
     {
         let sockets = self.bind2(addr)?;
 
         for lst in sockets {
             let token = Token(self.handlers.len());
-            let addr = lst.local_addr().unwrap();
+            let lst = StdListener::Tcp(lst);
+            let addr = lst.local_addr();
             self.handlers.push(Box::new(StreamHandler::new(
-                lst.local_addr().unwrap(),
+                addr.tcp(),
+                self.proxy.clone(),
                 acceptor.clone(),
             )));
             self.sockets.push(Socket { lst, addr, token })
@@ -403,10 +501,10 @@ where
     }
 }
 
-impl<H: IntoHttpHandler> Into<(Box<Service>, Vec<(Token, net::TcpListener)>)>
+impl<H: IntoHttpHandler> Into<(Box<Service>, Vec<(Token, StdListener)>)>
     for HttpServer<H>
 {
-    fn into(mut self) -> (Box<Service>, Vec<(Token, net::TcpListener)>) {
+    fn into(mut self) -> (Box<Service>, Vec<(Token, StdListener)>) {
         let sockets: Vec<_> = mem::replace(&mut self.sockets, Vec::new())
             .into_iter()
             .map(|item| (item.token, item.lst))
@@ -417,6 +515,7 @@ impl<H: IntoHttpHandler> Into<(Box<Service>, Vec<(Token, net::TcpListener)>)>
                 factory: self.factory,
                 host: self.host,
                 keep_alive: self.keep_alive,
+                trusted_proxies: self.trusted_proxies,
                 handlers: self.handlers,
             }),
             sockets,
@@ -428,7 +527,8 @@ struct HttpService<H: IntoHttpHandler> {
     factory: Arc<Fn() -> Vec<H> + Send + Sync>,
     host: Option<String>,
     keep_alive: KeepAlive,
-    handlers: Vec<Box<IoStreamHandler<H::Handler, net::TcpStream>>>,
+    trusted_proxies: Option<Vec<IpNet>>,
+    handlers: Vec<Box<IoStreamHandler<H::Handler>>>,
 }
 
 impl<H: IntoHttpHandler + 'static> Service for HttpService<H> {
@@ -437,13 +537,15 @@ impl<H: IntoHttpHandler + 'static> Service for HttpService<H> {
             factory: self.factory.clone(),
             host: self.host.clone(),
             keep_alive: self.keep_alive,
+            trusted_proxies: self.trusted_proxies.clone(),
             handlers: self.handlers.iter().map(|v| v.clone()).collect(),
         })
     }
 
     fn create(&self, conns: Connections) -> Box<ServiceHandler> {
         let addr = self.handlers[0].addr();
-        let s = ServerSettings::new(Some(addr), &self.host, false);
+        let s = ServerSettings::new(addr, &self.host, false)
+            .with_trusted_proxies(self.trusted_proxies.clone());
         let apps: Vec<_> = (*self.factory)()
             .into_iter()
             .map(|h| h.into_handler())
@@ -541,7 +643,8 @@ impl<H: IntoHttpHandler> HttpServer<H> {
     {
         // set server settings
         let addr: net::SocketAddr = "127.0.0.1:8080".parse().unwrap();
-        let srv_settings = ServerSettings::new(Some(addr), &self.host, secure);
+        let srv_settings = ServerSettings::new(Some(addr), &self.host, secure)
+            .with_trusted_proxies(self.trusted_proxies.clone());
         let apps: Vec<_> = (*self.factory)()
             .into_iter()
             .map(|h| h.into_handler())
@@ -598,13 +701,13 @@ where
     H: HttpHandler + 'static,
 {
     settings: Rc<WorkerSettings<H>>,
-    handlers: Vec<Box<IoStreamHandler<H, net::TcpStream>>>,
+    handlers: Vec<Box<IoStreamHandler<H>>>,
     tcp_ka: Option<time::Duration>,
 }
 
 impl<H: HttpHandler + 'static> HttpServiceHandler<H> {
     fn new(
-        apps: Vec<H>, handlers: Vec<Box<IoStreamHandler<H, net::TcpStream>>>,
+        apps: Vec<H>, handlers: Vec<Box<IoStreamHandler<H>>>,
         keep_alive: KeepAlive, settings: ServerSettings, conns: Connections,
     ) -> HttpServiceHandler<H> {
         let tcp_ka = if let KeepAlive::Tcp(val) = keep_alive {
@@ -626,56 +729,44 @@ impl<H> ServiceHandler for HttpServiceHandler<H>
 where
     H: HttpHandler + 'static,
 {
-    fn handle(
-        &mut self, token: Token, io: net::TcpStream, peer: Option<net::SocketAddr>,
-    ) {
-        if self.tcp_ka.is_some() && io.set_keepalive(self.tcp_ka).is_err() {
-            error!("Can not set socket keep-alive option");
+    fn handle(&mut self, token: Token, io: StdStream, peer: Option<net::SocketAddr>) {
+        match io {
+            StdStream::Tcp(ref io) => {
+                if self.tcp_ka.is_some() && io.set_keepalive(self.tcp_ka).is_err() {
+                    error!("Can not set socket keep-alive option");
+                }
+            }
+            #[cfg(all(unix, feature = "uds"))]
+            StdStream::Uds(_) => (),
         }
         self.handlers[token.0].handle(Rc::clone(&self.settings), io, peer);
     }
 
     fn shutdown(&self, force: bool) {
         if force {
-            self.settings.head().traverse::<TcpStream, H>();
+            self.settings.head().traverse();
         }
     }
 }
 
-struct SimpleHandler<Io> {
-    addr: net::SocketAddr,
-    io: PhantomData<Io>,
+#[derive(Clone)]
+struct SimpleHandler {
+    addr: Option<net::SocketAddr>,
+    proxy: Option<ProxyProtocol>,
 }
 
-impl<Io: IntoAsyncIo> Clone for SimpleHandler<Io> {
-    fn clone(&self) -> Self {
-        SimpleHandler {
-            addr: self.addr,
-            io: PhantomData,
-        }
+impl SimpleHandler {
+    fn new(addr: Option<net::SocketAddr>, proxy: Option<ProxyProtocol>) -> Self {
+        SimpleHandler { addr, proxy }
     }
 }
 
-impl<Io: IntoAsyncIo> SimpleHandler<Io> {
-    fn new(addr: net::SocketAddr) -> Self {
-        SimpleHandler {
-            addr,
-            io: PhantomData,
-        }
-    }
-}
-
-impl<H, Io> IoStreamHandler<H, Io> for SimpleHandler<Io>
-where
-    H: HttpHandler,
-    Io: IntoAsyncIo + Send + 'static,
-    Io::Io: IoStream,
-{
-    fn addr(&self) -> net::SocketAddr {
+impl<H: HttpHandler> IoStreamHandler<H> for SimpleHandler {
+    fn addr(&self) -> Option<net::SocketAddr> {
         self.addr
     }
 
-    fn clone(&self) -> Box<IoStreamHandler<H, Io>> {
+    fn clone(&self) -> Box<IoStreamHandler<H>> {
         Box::new(Clone::clone(self))
     }
 
@@ -683,58 +774,93 @@ where
         "http"
     }
 
-    fn handle(&self, h: Rc<WorkerSettings<H>>, io: Io, peer: Option<net::SocketAddr>) {
-        let mut io = match io.into_async_io() {
-            Ok(io) => io,
-            Err(err) => {
-                trace!("Failed to create async io: {}", err);
-                return;
+    fn handle(
+        &self, h: Rc<WorkerSettings<H>>, io: StdStream, peer: Option<net::SocketAddr>,
+    ) {
+        match io {
+            StdStream::Tcp(io) => {
+                let io = match io.into_async_io() {
+                    Ok(io) => io,
+                    Err(err) => {
+                        trace!("Failed to create async io: {}", err);
+                        return;
+                    }
+                };
+                let _ = io.set_nodelay(true);
+
+                if let Some(proxy) = trusted_proxy(&self.proxy, peer) {
+                    let rate = h.connection_rate();
+                    current_thread::spawn(proxy.read_header(io).then(move |res| {
+                        drop(rate);
+                        match res {
+                            Ok((io, addr)) => current_thread::spawn(HttpChannel::new(
+                                h,
+                                io,
+                                addr.or(peer),
+                            )),
+                            Err(err) => trace!("Can not read PROXY header: {}", err),
+                        }
+                        Ok(())
+                    }));
+                } else {
+                    let io = ProxyStream::new(io);
+                    current_thread::spawn(HttpChannel::new(h, io, peer));
+                }
             }
-        };
-        let _ = io.set_nodelay(true);
-
-        current_thread::spawn(HttpChannel::new(h, io, peer));
+            #[cfg(all(unix, feature = "uds"))]
+            StdStream::Uds(io) => {
+                let io = match io.into_async_io() {
+                    Ok(io) => io,
+                    Err(err) => {
+                        trace!("Failed to create async io: {}", err);
+                        return;
+                    }
+                };
+
+                current_thread::spawn(HttpChannel::new(h, io, peer));
+            }
+        }
     }
 }
 
-struct StreamHandler<A, Io> {
+struct StreamHandler<A> {
     acceptor: A,
-    addr: net::SocketAddr,
-    io: PhantomData<Io>,
+    addr: Option<net::SocketAddr>,
+    proxy: Option<ProxyProtocol>,
 }
 
-impl<Io: IntoAsyncIo, A: AcceptorService<Io::Io>> StreamHandler<A, Io> {
-    fn new(addr: net::SocketAddr, acceptor: A) -> Self {
+impl<A: AcceptorService<ProxyStream<TcpStream>>> StreamHandler<A> {
+    fn new(
+        addr: Option<net::SocketAddr>, proxy: Option<ProxyProtocol>, acceptor: A,
+    ) -> Self {
         StreamHandler {
             addr,
+            proxy,
             acceptor,
-            io: PhantomData,
         }
     }
 }
 
-impl<Io: IntoAsyncIo, A: AcceptorService<Io::Io>> Clone for StreamHandler<A, Io> {
+impl<A: AcceptorService<ProxyStream<TcpStream>>> Clone for StreamHandler<A> {
     fn clone(&self) -> Self {
         StreamHandler {
             addr: self.addr,
+            proxy: self.proxy.clone(),
             acceptor: self.acceptor.clone(),
-            io: PhantomData,
         }
     }
 }
 
-impl<H, Io, A> IoStreamHandler<H, Io> for StreamHandler<A, Io>
+impl<H, A> IoStreamHandler<H> for StreamHandler<A>
 where
     H: HttpHandler,
-    Io: IntoAsyncIo + Send + 'static,
-    Io::Io: IoStream,
-    A: AcceptorService<Io::Io> + Send + 'static,
+    A: AcceptorService<ProxyStream<TcpStream>> + Send + 'static,
 {
-    fn addr(&self) -> net::SocketAddr {
+    fn addr(&self) -> Option<net::SocketAddr> {
         self.addr
     }
 
-    fn clone(&self) -> Box<IoStreamHandler<H, Io>> {
+    fn clone(&self) -> Box<IoStreamHandler<H>> {
         Box::new(Clone::clone(self))
     }
 
@@ -742,8 +868,18 @@ where
         self.acceptor.scheme()
     }
 
-    fn handle(&self, h: Rc<WorkerSettings<H>>, io: Io, peer: Option<net::SocketAddr>) {
-        let mut io = match io.into_async_io() {
+    fn handle(
+        &self, h: Rc<WorkerSettings<H>>, io: StdStream, peer: Option<net::SocketAddr>,
+    ) {
+        let io = match io {
+            StdStream::Tcp(io) => io,
+            #[cfg(all(unix, feature = "uds"))]
+            StdStream::Uds(_) => {
+                error!("Acceptor service does not support unix domain sockets");
+                return;
+            }
+        };
+        let io = match io.into_async_io() {
             Ok(io) => io,
             Err(err) => {
                 trace!("Failed to create async io: {}", err);
@@ -753,27 +889,37 @@ where
         let _ = io.set_nodelay(true);
 
         let rate = h.connection_rate();
-        current_thread::spawn(self.acceptor.accept(io).then(move |res| {
-            drop(rate);
-            match res {
-                Ok(io) => current_thread::spawn(HttpChannel::new(h, io, peer)),
-                Err(err) => trace!("Can not establish connection: {}", err),
-            }
-            Ok(())
-        }))
+        let acceptor = self.acceptor.clone();
+        let header = if let Some(proxy) = trusted_proxy(&self.proxy, peer) {
+            let header = proxy.read_header(io);
+            Either::A(header.map(move |(io, addr)| (io, addr.or(peer))))
+        } else {
+            Either::B(future::ok((ProxyStream::new(io), peer)))
+        };
+        current_thread::spawn(
+            header
+                .and_then(move |(io, peer)| {
+                    acceptor.accept(io).map(move |io| (io, peer))
+                }).then(move |res| {
+                    drop(rate);
+                    match res {
+                        Ok((io, peer)) => {
+                            current_thread::spawn(HttpChannel::new(h, io, peer))
+                        }
+                        Err(err) => trace!("Can not establish connection: {}", err),
+                    }
+                    Ok(())
+                }),
+        )
     }
 }
 
-impl<H, Io: 'static> IoStreamHandler<H, Io> for Box<IoStreamHandler<H, Io>>
-where
-    H: HttpHandler,
-    Io: IntoAsyncIo,
-{
-    fn addr(&self) -> net::SocketAddr {
+impl<H: HttpHandler> IoStreamHandler<H> for Box<IoStreamHandler<H>> {
+    fn addr(&self) -> Option<net::SocketAddr> {
         self.as_ref().addr()
     }
 
-    fn clone(&self) -> Box<IoStreamHandler<H, Io>> {
+    fn clone(&self) -> Box<IoStreamHandler<H>> {
         self.as_ref().clone()
     }
 
@@ -781,22 +927,36 @@ where
         self.as_ref().scheme()
     }
 
-    fn handle(&self, h: Rc<WorkerSettings<H>>, io: Io, peer: Option<net::SocketAddr>) {
+    fn handle(
+        &self, h: Rc<WorkerSettings<H>>, io: StdStream, peer: Option<net::SocketAddr>,
+    ) {
         self.as_ref().handle(h, io, peer)
     }
 }
 
-trait IoStreamHandler<H, Io>: Send
+trait IoStreamHandler<H>: Send
 where
     H: HttpHandler,
 {
-    fn clone(&self) -> Box<IoStreamHandler<H, Io>>;
+    fn clone(&self) -> Box<IoStreamHandler<H>>;
 
-    fn addr(&self) -> net::SocketAddr;
+    fn addr(&self) -> Option<net::SocketAddr>;
 
     fn scheme(&self) -> &'static str;
 
-    fn handle(&self, h: Rc<WorkerSettings<H>>, io: Io, peer: Option<net::SocketAddr>);
+    fn handle(
+        &self, h: Rc<WorkerSettings<H>>, io: StdStream, peer: Option<net::SocketAddr>,
+    );
+}
+
+/// PROXY protocol settings, if connection starts with PROXY protocol header
+fn trusted_proxy(
+    proxy: &Option<ProxyProtocol>, peer: Option<net::SocketAddr>,
+) -> Option<&ProxyProtocol> {
+    match *proxy {
+        Some(ref proxy) if proxy.is_trusted(peer) => Some(proxy),
+        _ => None,
+    }
 }
 
 fn create_tcp_listener(
@@ -810,3 +970,29 @@ fn create_tcp_listener(
     builder.bind(addr)?;
     Ok(builder.listen(backlog)?)
 }
+
+#[cfg(all(unix, feature = "uds"))]
+fn create_uds_listener(path: &Path, mode: Option<u32>) -> io::Result<UnixListener> {
+    // remove socket file left by a process that is not running anymore
+    if let Ok(md) = fs::symlink_metadata(path) {
+        if md.file_type().is_socket() {
+            match UnixStream::connect(path) {
+                Ok(_) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::AddrInUse,
+                        "Unix domain socket is in use",
+                    ))
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::ConnectionRefused => {
+                    fs::remove_file(path)?
+                }
+                Err(_) => (),
+            }
+        }
+    }
+    let lst = UnixListener::bind(path)?;
+    if let Some(mode) = mode {
+        fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+    }
+    Ok(lst)
+}
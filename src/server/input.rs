@@ -1,4 +1,5 @@
 use std::io::{self, Write};
+use std::{cmp, fmt};
 
 #[cfg(feature = "brotli")]
 use brotli2::write::BrotliDecoder;
@@ -9,14 +10,13 @@ use flate2::write::{GzDecoder, ZlibDecoder};
 use header::ContentEncoding;
 use http::header::{HeaderMap, CONTENT_ENCODING};
 use payload::{PayloadSender, PayloadStatus, PayloadWriter};
+#[cfg(feature = "zstd")]
+use zstd::stream::write::Decoder as ZstdDecoder;
 
-pub(crate) enum PayloadType {
-    Sender(PayloadSender),
-    Encoding(Box<EncodedPayload>),
-}
+pub(crate) struct PayloadType(PayloadSender);
 
 impl PayloadType {
-    #[cfg(any(feature = "brotli", feature = "flate2"))]
+    #[cfg(any(feature = "brotli", feature = "flate2", feature = "zstd"))]
     pub fn new(headers: &HeaderMap, sender: PayloadSender) -> PayloadType {
         // check content-encoding
         let enc = if let Some(enc) = headers.get(CONTENT_ENCODING) {
@@ -29,111 +29,130 @@ impl PayloadType {
             ContentEncoding::Auto
         };
 
-        match enc {
-            ContentEncoding::Auto | ContentEncoding::Identity => {
-                PayloadType::Sender(sender)
-            }
-            _ => PayloadType::Encoding(Box::new(EncodedPayload::new(sender, enc))),
+        if enc.is_compression() {
+            sender.set_decoder(PayloadDecoder::new(enc));
         }
+        PayloadType(sender)
     }
 
-    #[cfg(not(any(feature = "brotli", feature = "flate2")))]
+    #[cfg(not(any(feature = "brotli", feature = "flate2", feature = "zstd")))]
     pub fn new(headers: &HeaderMap, sender: PayloadSender) -> PayloadType {
-        PayloadType::Sender(sender)
+        PayloadType(sender)
     }
 }
 
 impl PayloadWriter for PayloadType {
     #[inline]
     fn set_error(&mut self, err: PayloadError) {
-        match *self {
-            PayloadType::Sender(ref mut sender) => sender.set_error(err),
-            PayloadType::Encoding(ref mut enc) => enc.set_error(err),
-        }
+        self.0.set_error(err)
     }
 
     #[inline]
     fn feed_eof(&mut self) {
-        match *self {
-            PayloadType::Sender(ref mut sender) => sender.feed_eof(),
-            PayloadType::Encoding(ref mut enc) => enc.feed_eof(),
-        }
+        self.0.feed_eof()
     }
 
     #[inline]
     fn feed_data(&mut self, data: Bytes) {
-        match *self {
-            PayloadType::Sender(ref mut sender) => sender.feed_data(data),
-            PayloadType::Encoding(ref mut enc) => enc.feed_data(data),
-        }
+        self.0.feed_data(data)
     }
 
     #[inline]
     fn need_read(&self) -> PayloadStatus {
-        match *self {
-            PayloadType::Sender(ref sender) => sender.need_read(),
-            PayloadType::Encoding(ref enc) => enc.need_read(),
-        }
+        self.0.need_read()
     }
 }
 
-/// Payload wrapper with content decompression support
-pub(crate) struct EncodedPayload {
-    inner: PayloadSender,
-    error: bool,
-    payload: PayloadStream,
+/// Limits of decoded request payload
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct DecodeLimits {
+    /// Maximum size of decoded payload
+    pub max_size: Option<usize>,
+    /// Maximum ratio of decoded and encoded sizes
+    pub max_ratio: Option<usize>,
 }
 
-impl EncodedPayload {
-    pub fn new(inner: PayloadSender, enc: ContentEncoding) -> EncodedPayload {
-        EncodedPayload {
-            inner,
-            error: false,
-            payload: PayloadStream::new(enc),
-        }
-    }
+/// Compression ratio is not checked for smaller payloads
+const RATIO_THRESHOLD: usize = 65_536;
+
+/// Request payload decoder.
+///
+/// Decoder stops as soon as decoded data exceeds the limits.
+pub(crate) struct PayloadDecoder {
+    stream: PayloadStream,
+    limits: DecodeLimits,
+    encoded: usize,
+    decoded: usize,
 }
 
-impl PayloadWriter for EncodedPayload {
-    fn set_error(&mut self, err: PayloadError) {
-        self.inner.set_error(err)
+impl fmt::Debug for PayloadDecoder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "PayloadDecoder(encoded: {}, decoded: {})",
+            self.encoded, self.decoded
+        )
     }
+}
 
-    fn feed_eof(&mut self) {
-        if !self.error {
-            match self.payload.feed_eof() {
-                Err(err) => {
-                    self.error = true;
-                    self.set_error(PayloadError::Io(err));
-                }
-                Ok(value) => {
-                    if let Some(b) = value {
-                        self.inner.feed_data(b);
-                    }
-                    self.inner.feed_eof();
-                }
-            }
+impl PayloadDecoder {
+    pub fn new(enc: ContentEncoding) -> PayloadDecoder {
+        PayloadDecoder {
+            stream: PayloadStream::new(enc),
+            limits: DecodeLimits::default(),
+            encoded: 0,
+            decoded: 0,
         }
     }
 
-    fn feed_data(&mut self, data: Bytes) {
-        if self.error {
-            return;
-        }
+    pub fn set_limits(&mut self, limits: DecodeLimits) {
+        self.limits = limits;
+    }
 
-        match self.payload.feed_data(data) {
-            Ok(Some(b)) => self.inner.feed_data(b),
-            Ok(None) => (),
-            Err(e) => {
-                self.error = true;
-                self.set_error(e.into());
-            }
+    /// Is any data fed to decoder
+    pub fn is_started(&self) -> bool {
+        self.encoded > 0
+    }
+
+    pub fn feed_data(&mut self, data: Bytes) -> Result<Option<Bytes>, PayloadError> {
+        self.encoded += data.len();
+        self.set_budget();
+        let res = self.stream.feed_data(data);
+        self.result(res)
+    }
+
+    pub fn feed_eof(&mut self) -> Result<Option<Bytes>, PayloadError> {
+        self.set_budget();
+        let res = self.stream.feed_eof();
+        self.result(res)
+    }
+
+    /// Limit amount of data decoder could produce
+    fn set_budget(&mut self) {
+        let mut max = self.limits.max_size.unwrap_or(usize::max_value());
+        if let Some(ratio) = self.limits.max_ratio {
+            let by_ratio = cmp::max(self.encoded.saturating_mul(ratio), RATIO_THRESHOLD);
+            max = cmp::min(max, by_ratio);
+        }
+        if let Some(writer) = self.stream.writer() {
+            writer.budget = max.saturating_sub(self.decoded);
         }
     }
 
-    #[inline]
-    fn need_read(&self) -> PayloadStatus {
-        self.inner.need_read()
+    fn result(
+        &mut self, res: io::Result<Option<Bytes>>,
+    ) -> Result<Option<Bytes>, PayloadError> {
+        let chunk = match res {
+            Ok(chunk) => chunk,
+            Err(_) if self.stream.writer().map_or(false, |w| w.overflow) => {
+                return Err(PayloadError::Overflow)
+            }
+            Err(err) => return Err(err.into()),
+        };
+        if let Some(ref chunk) = chunk {
+            self.decoded += chunk.len();
+        }
+        Ok(chunk)
     }
 }
 
@@ -144,17 +163,23 @@ pub(crate) enum Decoder {
     Gzip(Box<GzDecoder<Writer>>),
     #[cfg(feature = "brotli")]
     Br(Box<BrotliDecoder<Writer>>),
+    #[cfg(feature = "zstd")]
+    Zstd(Box<ZstdDecoder<Writer>>),
     Identity,
 }
 
 pub(crate) struct Writer {
     buf: BytesMut,
+    budget: usize,
+    overflow: bool,
 }
 
 impl Writer {
     fn new() -> Writer {
         Writer {
             buf: BytesMut::with_capacity(8192),
+            budget: usize::max_value(),
+            overflow: false,
         }
     }
     fn take(&mut self) -> Bytes {
@@ -164,6 +189,14 @@ impl Writer {
 
 impl io::Write for Writer {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.len() > self.budget {
+            self.overflow = true;
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "Decoded payload is too large",
+            ));
+        }
+        self.budget -= buf.len();
         self.buf.extend_from_slice(buf);
         Ok(buf.len())
     }
@@ -192,6 +225,10 @@ impl PayloadStream {
             ContentEncoding::Gzip => {
                 Decoder::Gzip(Box::new(GzDecoder::new(Writer::new())))
             }
+            #[cfg(feature = "zstd")]
+            ContentEncoding::Zstd => Decoder::Zstd(Box::new(
+                ZstdDecoder::new(Writer::new()).expect("Can not create zstd decoder"),
+            )),
             _ => Decoder::Identity,
         };
         PayloadStream { decoder }
@@ -199,6 +236,20 @@ impl PayloadStream {
 }
 
 impl PayloadStream {
+    fn writer(&mut self) -> Option<&mut Writer> {
+        match self.decoder {
+            #[cfg(feature = "brotli")]
+            Decoder::Br(ref mut decoder) => Some(decoder.get_mut()),
+            #[cfg(feature = "zstd")]
+            Decoder::Zstd(ref mut decoder) => Some(decoder.get_mut()),
+            #[cfg(feature = "flate2")]
+            Decoder::Gzip(ref mut decoder) => Some(decoder.get_mut()),
+            #[cfg(feature = "flate2")]
+            Decoder::Deflate(ref mut decoder) => Some(decoder.get_mut()),
+            Decoder::Identity => None,
+        }
+    }
+
     pub fn feed_eof(&mut self) -> io::Result<Option<Bytes>> {
         match self.decoder {
             #[cfg(feature = "brotli")]
@@ -213,6 +264,18 @@ impl PayloadStream {
                 }
                 Err(e) => Err(e),
             },
+            #[cfg(feature = "zstd")]
+            Decoder::Zstd(ref mut decoder) => match decoder.flush() {
+                Ok(_) => {
+                    let b = decoder.get_mut().take();
+                    if !b.is_empty() {
+                        Ok(Some(b))
+                    } else {
+                        Ok(None)
+                    }
+                }
+                Err(e) => Err(e),
+            },
             #[cfg(feature = "flate2")]
             Decoder::Gzip(ref mut decoder) => match decoder.try_finish() {
                 Ok(_) => {
@@ -256,6 +319,19 @@ impl PayloadStream {
                 }
                 Err(e) => Err(e),
             },
+            #[cfg(feature = "zstd")]
+            Decoder::Zstd(ref mut decoder) => match decoder.write_all(&data) {
+                Ok(_) => {
+                    decoder.flush()?;
+                    let b = decoder.get_mut().take();
+                    if !b.is_empty() {
+                        Ok(Some(b))
+                    } else {
+                        Ok(None)
+                    }
+                }
+                Err(e) => Err(e),
+            },
             #[cfg(feature = "flate2")]
             Decoder::Gzip(ref mut decoder) => match decoder.write_all(&data) {
                 Ok(_) => {
@@ -0,0 +1,144 @@
+use std::{fmt, io, net};
+
+#[cfg(all(unix, feature = "uds"))]
+use std::os::unix::io::AsRawFd;
+#[cfg(all(unix, feature = "uds"))]
+use std::os::unix::net as unix;
+#[cfg(all(unix, feature = "uds"))]
+use std::path::PathBuf;
+
+use mio;
+
+#[doc(hidden)]
+/// Listening socket of the server
+pub enum StdListener {
+    /// Tcp listener
+    Tcp(net::TcpListener),
+    /// Unix domain socket listener
+    #[cfg(all(unix, feature = "uds"))]
+    Uds(unix::UnixListener),
+}
+
+#[doc(hidden)]
+/// Connection accepted by the server
+pub enum StdStream {
+    /// Tcp connection
+    Tcp(net::TcpStream),
+    /// Unix domain socket connection
+    #[cfg(all(unix, feature = "uds"))]
+    Uds(unix::UnixStream),
+}
+
+/// Address of the listening socket
+#[derive(Clone, Debug)]
+pub(crate) enum ListenerAddr {
+    Tcp(net::SocketAddr),
+    #[cfg(all(unix, feature = "uds"))]
+    Uds(PathBuf),
+}
+
+impl ListenerAddr {
+    /// Socket address of the tcp listener
+    pub fn tcp(&self) -> Option<net::SocketAddr> {
+        match *self {
+            ListenerAddr::Tcp(addr) => Some(addr),
+            #[cfg(all(unix, feature = "uds"))]
+            ListenerAddr::Uds(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for ListenerAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ListenerAddr::Tcp(ref addr) => write!(f, "http://{}", addr),
+            #[cfg(all(unix, feature = "uds"))]
+            ListenerAddr::Uds(ref path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+impl StdListener {
+    pub(crate) fn local_addr(&self) -> ListenerAddr {
+        match *self {
+            StdListener::Tcp(ref lst) => ListenerAddr::Tcp(lst.local_addr().unwrap()),
+            #[cfg(all(unix, feature = "uds"))]
+            StdListener::Uds(ref lst) => ListenerAddr::Uds(
+                lst.local_addr()
+                    .ok()
+                    .and_then(|addr| addr.as_pathname().map(|p| p.to_owned()))
+                    .unwrap_or_default(),
+            ),
+        }
+    }
+
+    pub(crate) fn into_mio(self) -> io::Result<MioListener> {
+        match self {
+            StdListener::Tcp(lst) => {
+                Ok(MioListener::Tcp(mio::net::TcpListener::from_std(lst)?))
+            }
+            #[cfg(all(unix, feature = "uds"))]
+            StdListener::Uds(lst) => {
+                lst.set_nonblocking(true)?;
+                Ok(MioListener::Uds(lst))
+            }
+        }
+    }
+}
+
+/// Listening socket registered in accept loop
+pub(crate) enum MioListener {
+    Tcp(mio::net::TcpListener),
+    #[cfg(all(unix, feature = "uds"))]
+    Uds(unix::UnixListener),
+}
+
+impl MioListener {
+    pub(crate) fn accept(&self) -> io::Result<(StdStream, Option<net::SocketAddr>)> {
+        match *self {
+            MioListener::Tcp(ref lst) => lst
+                .accept_std()
+                .map(|(io, addr)| (StdStream::Tcp(io), Some(addr))),
+            #[cfg(all(unix, feature = "uds"))]
+            MioListener::Uds(ref lst) => {
+                lst.accept().map(|(io, _)| (StdStream::Uds(io), None))
+            }
+        }
+    }
+}
+
+impl mio::Evented for MioListener {
+    fn register(
+        &self, poll: &mio::Poll, token: mio::Token, interest: mio::Ready,
+        opts: mio::PollOpt,
+    ) -> io::Result<()> {
+        match *self {
+            MioListener::Tcp(ref lst) => lst.register(poll, token, interest, opts),
+            #[cfg(all(unix, feature = "uds"))]
+            MioListener::Uds(ref lst) => mio::unix::EventedFd(&lst.as_raw_fd())
+                .register(poll, token, interest, opts),
+        }
+    }
+
+    fn reregister(
+        &self, poll: &mio::Poll, token: mio::Token, interest: mio::Ready,
+        opts: mio::PollOpt,
+    ) -> io::Result<()> {
+        match *self {
+            MioListener::Tcp(ref lst) => lst.reregister(poll, token, interest, opts),
+            #[cfg(all(unix, feature = "uds"))]
+            MioListener::Uds(ref lst) => mio::unix::EventedFd(&lst.as_raw_fd())
+                .reregister(poll, token, interest, opts),
+        }
+    }
+
+    fn deregister(&self, poll: &mio::Poll) -> io::Result<()> {
+        match *self {
+            MioListener::Tcp(ref lst) => lst.deregister(poll),
+            #[cfg(all(unix, feature = "uds"))]
+            MioListener::Uds(ref lst) => {
+                mio::unix::EventedFd(&lst.as_raw_fd()).deregister(poll)
+            }
+        }
+    }
+}
@@ -106,6 +106,7 @@
 //!     let _ = sys.run();
 //!}
 //! ```
+use std::fs::File;
 use std::net::Shutdown;
 use std::rc::Rc;
 use std::{io, net, time};
@@ -118,6 +119,7 @@ use tokio_tcp::TcpStream;
 
 pub(crate) mod accept;
 mod channel;
+mod compression;
 mod error;
 pub(crate) mod h1;
 pub(crate) mod h1decoder;
@@ -127,8 +129,12 @@ mod h2writer;
 pub(crate) mod helpers;
 mod http;
 pub(crate) mod input;
+mod listener;
 pub(crate) mod message;
 pub(crate) mod output;
+pub(crate) mod proxy;
+#[cfg(target_os = "linux")]
+mod sendfile;
 mod server;
 pub(crate) mod settings;
 mod ssl;
@@ -136,10 +142,16 @@ mod worker;
 
 use actix::Message;
 
+pub use self::compression::CompressionPolicy;
 pub use self::message::Request;
 
 pub use self::http::HttpServer;
 #[doc(hidden)]
+pub use self::listener::{StdListener, StdStream};
+pub use self::proxy::ProxyProtocol;
+#[doc(hidden)]
+pub use self::proxy::ProxyStream;
+#[doc(hidden)]
 pub use self::server::{
     ConnectionRateTag, ConnectionTag, Connections, Server, Service, ServiceHandler,
 };
@@ -336,6 +348,15 @@ impl IntoAsyncIo for net::TcpStream {
     }
 }
 
+#[cfg(all(unix, feature = "uds"))]
+impl IntoAsyncIo for ::std::os::unix::net::UnixStream {
+    type Io = ::tokio_uds::UnixStream;
+
+    fn into_async_io(self) -> Result<Self::Io, io::Error> {
+        ::tokio_uds::UnixStream::from_std(self, &Handle::default())
+    }
+}
+
 #[doc(hidden)]
 /// Trait implemented by types that could accept incomming socket connections.
 pub trait AcceptorService<Io: AsyncRead + AsyncWrite>: Clone {
@@ -372,6 +393,7 @@ pub trait Writer {
 
     fn start(
         &mut self, req: &Request, resp: &mut HttpResponse, encoding: ContentEncoding,
+        compression: &CompressionPolicy,
     ) -> io::Result<WriterState>;
 
     fn write(&mut self, payload: &Binary) -> io::Result<WriterState>;
@@ -379,6 +401,20 @@ pub trait Writer {
     fn write_eof(&mut self) -> io::Result<WriterState>;
 
     fn poll_completed(&mut self, shutdown: bool) -> Poll<(), io::Error>;
+
+    /// Can file body of the started response be sent with `send_file()`
+    fn can_send_file(&self) -> bool {
+        false
+    }
+
+    /// Send part of the file directly to the stream,
+    /// returns number of bytes sent
+    fn send_file(&mut self, _: &File, _: u64, _: u64) -> Poll<usize, io::Error> {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            "Sending files is not supported",
+        ))
+    }
 }
 
 #[doc(hidden)]
@@ -390,6 +426,21 @@ pub trait IoStream: AsyncRead + AsyncWrite + 'static {
 
     fn set_linger(&mut self, dur: Option<time::Duration>) -> io::Result<()>;
 
+    /// Can this stream send files without copying data through user space,
+    /// it is not possible for TLS streams
+    fn can_send_file(&self) -> bool {
+        false
+    }
+
+    /// Send part of the file directly to the stream,
+    /// returns number of bytes sent
+    fn send_file(&mut self, _: &File, _: u64, _: usize) -> Poll<usize, io::Error> {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            "Sending files is not supported",
+        ))
+    }
+
     fn read_available(&mut self, buf: &mut BytesMut) -> Poll<bool, io::Error> {
         let mut read_some = false;
         loop {
@@ -444,6 +495,20 @@ impl IoStream for ::tokio_uds::UnixStream {
     fn set_linger(&mut self, _dur: Option<time::Duration>) -> io::Result<()> {
         Ok(())
     }
+
+    #[cfg(target_os = "linux")]
+    #[inline]
+    fn can_send_file(&self) -> bool {
+        true
+    }
+
+    #[cfg(target_os = "linux")]
+    #[inline]
+    fn send_file(
+        &mut self, file: &File, offset: u64, len: usize,
+    ) -> Poll<usize, io::Error> {
+        sendfile::send_file(self, file, offset, len)
+    }
 }
 
 impl IoStream for TcpStream {
@@ -461,4 +526,18 @@ impl IoStream for TcpStream {
     fn set_linger(&mut self, dur: Option<time::Duration>) -> io::Result<()> {
         TcpStream::set_linger(self, dur)
     }
+
+    #[cfg(target_os = "linux")]
+    #[inline]
+    fn can_send_file(&self) -> bool {
+        true
+    }
+
+    #[cfg(target_os = "linux")]
+    #[inline]
+    fn send_file(
+        &mut self, file: &File, offset: u64, len: usize,
+    ) -> Poll<usize, io::Error> {
+        sendfile::send_file(self, file, offset, len)
+    }
 }
@@ -11,8 +11,11 @@ use flate2::write::{GzEncoder, ZlibEncoder};
 #[cfg(feature = "flate2")]
 use flate2::Compression;
 use http::header::{ACCEPT_ENCODING, CONTENT_LENGTH};
+#[cfg(feature = "zstd")]
+use zstd::stream::write::Encoder as ZstdEncoder;
 use http::Version;
 
+use super::compression::CompressionPolicy;
 use super::message::InnerRequest;
 use body::{Binary, Body};
 use header::ContentEncoding;
@@ -134,6 +137,21 @@ impl Output {
         }
     }
 
+    /// Remaining length of the body, if it is sent as is with known length
+    pub fn plain_length(&self) -> Option<u64> {
+        match self {
+            Output::TE(ref te) => te.remaining(),
+            _ => None,
+        }
+    }
+
+    /// Account body bytes that were sent to the peer bypassing the buffer
+    pub fn advance(&mut self, n: u64) {
+        if let Output::TE(ref mut te) = self {
+            te.advance(n)
+        }
+    }
+
     pub fn write_eof(&mut self) -> Result<bool, io::Error> {
         match self {
             Output::Buffer(_) => Ok(true),
@@ -145,7 +163,7 @@ impl Output {
 
     pub(crate) fn for_server(
         &mut self, info: &mut ResponseInfo, req: &InnerRequest, resp: &mut HttpResponse,
-        response_encoding: ContentEncoding,
+        response_encoding: ContentEncoding, compression: &CompressionPolicy,
     ) {
         let buf = self.take();
         let version = resp.version().unwrap_or_else(|| req.version);
@@ -156,21 +174,24 @@ impl Output {
             &Body::Empty => false,
             &Body::Binary(ref bin) => {
                 len = bin.len();
-                !(response_encoding == ContentEncoding::Auto && len < 96)
+                true
             }
             _ => true,
         };
+        let compress = has_body
+            && (response_encoding != ContentEncoding::Auto
+                || compression.is_compressible(resp));
 
         // Enable content encoding only if response does not contain Content-Encoding
         // header
-        #[cfg(any(feature = "brotli", feature = "flate2"))]
-        let mut encoding = if has_body {
+        #[cfg(any(feature = "brotli", feature = "flate2", feature = "zstd"))]
+        let mut encoding = if compress {
             let encoding = match response_encoding {
                 ContentEncoding::Auto => {
                     // negotiate content-encoding
                     if let Some(val) = req.headers.get(ACCEPT_ENCODING) {
                         if let Ok(enc) = val.to_str() {
-                            AcceptEncoding::parse(enc)
+                            AcceptEncoding::parse(enc, compression.get_preference())
                         } else {
                             ContentEncoding::Identity
                         }
@@ -187,7 +208,7 @@ impl Output {
         } else {
             ContentEncoding::Identity
         };
-        #[cfg(not(any(feature = "brotli", feature = "flate2")))]
+        #[cfg(not(any(feature = "brotli", feature = "flate2", feature = "zstd")))]
         let mut encoding = ContentEncoding::Identity;
 
         #[cfg_attr(feature = "cargo-clippy", allow(match_ref_pats))]
@@ -200,25 +221,30 @@ impl Output {
                 return;
             }
             &Body::Binary(_) => {
-                #[cfg(any(feature = "brotli", feature = "flate2"))]
+                #[cfg(any(feature = "brotli", feature = "flate2", feature = "zstd"))]
                 {
                     if !(encoding == ContentEncoding::Identity
                         || encoding == ContentEncoding::Auto)
                     {
                         let mut tmp = BytesMut::new();
                         let mut transfer = TransferEncoding::eof(tmp);
+                        let level = compression.get_level(encoding);
                         let mut enc = match encoding {
                             #[cfg(feature = "flate2")]
                             ContentEncoding::Deflate => ContentEncoder::Deflate(
-                                ZlibEncoder::new(transfer, Compression::fast()),
+                                ZlibEncoder::new(transfer, Compression::new(level)),
                             ),
                             #[cfg(feature = "flate2")]
                             ContentEncoding::Gzip => ContentEncoder::Gzip(
-                                GzEncoder::new(transfer, Compression::fast()),
+                                GzEncoder::new(transfer, Compression::new(level)),
                             ),
                             #[cfg(feature = "brotli")]
                             ContentEncoding::Br => {
-                                ContentEncoder::Br(BrotliEncoder::new(transfer, 3))
+                                ContentEncoder::Br(BrotliEncoder::new(transfer, level))
+                            }
+                            #[cfg(feature = "zstd")]
+                            ContentEncoding::Zstd => {
+                                ContentEncoder::zstd(transfer, level)
                             }
                             ContentEncoding::Identity | ContentEncoding::Auto => {
                                 unreachable!()
@@ -271,17 +297,23 @@ impl Output {
             return;
         }
 
+        let level = compression.get_level(encoding);
         let enc = match encoding {
             #[cfg(feature = "flate2")]
-            ContentEncoding::Deflate => {
-                ContentEncoder::Deflate(ZlibEncoder::new(transfer, Compression::fast()))
-            }
+            ContentEncoding::Deflate => ContentEncoder::Deflate(ZlibEncoder::new(
+                transfer,
+                Compression::new(level),
+            )),
             #[cfg(feature = "flate2")]
             ContentEncoding::Gzip => {
-                ContentEncoder::Gzip(GzEncoder::new(transfer, Compression::fast()))
+                ContentEncoder::Gzip(GzEncoder::new(transfer, Compression::new(level)))
             }
             #[cfg(feature = "brotli")]
-            ContentEncoding::Br => ContentEncoder::Br(BrotliEncoder::new(transfer, 3)),
+            ContentEncoding::Br => {
+                ContentEncoder::Br(BrotliEncoder::new(transfer, level))
+            }
+            #[cfg(feature = "zstd")]
+            ContentEncoding::Zstd => ContentEncoder::zstd(transfer, level),
             ContentEncoding::Identity | ContentEncoding::Auto => {
                 *self = Output::TE(transfer);
                 return;
@@ -358,6 +390,8 @@ pub(crate) enum ContentEncoder {
     Gzip(GzEncoder<TransferEncoding>),
     #[cfg(feature = "brotli")]
     Br(BrotliEncoder<TransferEncoding>),
+    #[cfg(feature = "zstd")]
+    Zstd(ZstdEncoder<TransferEncoding>),
     Identity(TransferEncoding),
 }
 
@@ -366,6 +400,8 @@ impl fmt::Debug for ContentEncoder {
         match *self {
             #[cfg(feature = "brotli")]
             ContentEncoder::Br(_) => writeln!(f, "ContentEncoder(Brotli)"),
+            #[cfg(feature = "zstd")]
+            ContentEncoder::Zstd(_) => writeln!(f, "ContentEncoder(Zstd)"),
             #[cfg(feature = "flate2")]
             ContentEncoder::Deflate(_) => writeln!(f, "ContentEncoder(Deflate)"),
             #[cfg(feature = "flate2")]
@@ -376,11 +412,20 @@ impl fmt::Debug for ContentEncoder {
 }
 
 impl ContentEncoder {
+    #[cfg(feature = "zstd")]
+    pub(crate) fn zstd(transfer: TransferEncoding, level: u32) -> ContentEncoder {
+        ContentEncoder::Zstd(
+            ZstdEncoder::new(transfer, level as i32).expect("Can not create zstd encoder"),
+        )
+    }
+
     #[inline]
     pub fn len(&self) -> usize {
         match *self {
             #[cfg(feature = "brotli")]
             ContentEncoder::Br(ref encoder) => encoder.get_ref().len(),
+            #[cfg(feature = "zstd")]
+            ContentEncoder::Zstd(ref encoder) => encoder.get_ref().len(),
             #[cfg(feature = "flate2")]
             ContentEncoder::Deflate(ref encoder) => encoder.get_ref().len(),
             #[cfg(feature = "flate2")]
@@ -394,6 +439,8 @@ impl ContentEncoder {
         match *self {
             #[cfg(feature = "brotli")]
             ContentEncoder::Br(ref encoder) => encoder.get_ref().is_empty(),
+            #[cfg(feature = "zstd")]
+            ContentEncoder::Zstd(ref encoder) => encoder.get_ref().is_empty(),
             #[cfg(feature = "flate2")]
             ContentEncoder::Deflate(ref encoder) => encoder.get_ref().is_empty(),
             #[cfg(feature = "flate2")]
@@ -407,6 +454,8 @@ impl ContentEncoder {
         match *self {
             #[cfg(feature = "brotli")]
             ContentEncoder::Br(ref mut encoder) => encoder.get_mut().take(),
+            #[cfg(feature = "zstd")]
+            ContentEncoder::Zstd(ref mut encoder) => encoder.get_mut().take(),
             #[cfg(feature = "flate2")]
             ContentEncoder::Deflate(ref mut encoder) => encoder.get_mut().take(),
             #[cfg(feature = "flate2")]
@@ -420,6 +469,8 @@ impl ContentEncoder {
         match *self {
             #[cfg(feature = "brotli")]
             ContentEncoder::Br(ref mut encoder) => encoder.get_mut().buf_mut(),
+            #[cfg(feature = "zstd")]
+            ContentEncoder::Zstd(ref mut encoder) => encoder.get_mut().buf_mut(),
             #[cfg(feature = "flate2")]
             ContentEncoder::Deflate(ref mut encoder) => encoder.get_mut().buf_mut(),
             #[cfg(feature = "flate2")]
@@ -433,6 +484,8 @@ impl ContentEncoder {
         match *self {
             #[cfg(feature = "brotli")]
             ContentEncoder::Br(ref mut encoder) => encoder.get_mut().buf_ref(),
+            #[cfg(feature = "zstd")]
+            ContentEncoder::Zstd(ref mut encoder) => encoder.get_mut().buf_ref(),
             #[cfg(feature = "flate2")]
             ContentEncoder::Deflate(ref mut encoder) => encoder.get_mut().buf_ref(),
             #[cfg(feature = "flate2")]
@@ -457,6 +510,15 @@ impl ContentEncoder {
                 }
                 Err(err) => Err(err),
             },
+            #[cfg(feature = "zstd")]
+            ContentEncoder::Zstd(encoder) => match encoder.finish() {
+                Ok(mut writer) => {
+                    writer.encode_eof();
+                    *self = ContentEncoder::Identity(writer);
+                    Ok(true)
+                }
+                Err(err) => Err(err),
+            },
             #[cfg(feature = "flate2")]
             ContentEncoder::Gzip(encoder) => match encoder.finish() {
                 Ok(mut writer) => {
@@ -495,6 +557,14 @@ impl ContentEncoder {
                     Err(err)
                 }
             },
+            #[cfg(feature = "zstd")]
+            ContentEncoder::Zstd(ref mut encoder) => match encoder.write_all(data) {
+                Ok(_) => Ok(()),
+                Err(err) => {
+                    trace!("Error decoding zstd encoding: {}", err);
+                    Err(err)
+                }
+            },
             #[cfg(feature = "flate2")]
             ContentEncoder::Gzip(ref mut encoder) => match encoder.write_all(data) {
                 Ok(_) => Ok(()),
@@ -644,6 +714,22 @@ impl TransferEncoding {
         }
     }
 
+    /// Remaining number of bytes for encoder with known length
+    #[inline]
+    fn remaining(&self) -> Option<u64> {
+        match self.kind {
+            TransferEncodingKind::Length(rem) => Some(rem),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn advance(&mut self, n: u64) {
+        if let TransferEncodingKind::Length(ref mut rem) = self.kind {
+            *rem -= cmp::min(*rem, n);
+        }
+    }
+
     /// Encode eof. Return `EOF` state of encoder
     #[inline]
     pub fn encode_eof(&mut self) -> bool {
@@ -677,68 +763,56 @@ impl io::Write for TransferEncoding {
 }
 
 struct AcceptEncoding {
-    encoding: ContentEncoding,
+    /// `None` for `*`
+    encoding: Option<ContentEncoding>,
     quality: f64,
 }
 
-impl Eq for AcceptEncoding {}
-
-impl Ord for AcceptEncoding {
-    fn cmp(&self, other: &AcceptEncoding) -> cmp::Ordering {
-        if self.quality > other.quality {
-            cmp::Ordering::Less
-        } else if self.quality < other.quality {
-            cmp::Ordering::Greater
-        } else {
-            cmp::Ordering::Equal
-        }
-    }
-}
-
-impl PartialOrd for AcceptEncoding {
-    fn partial_cmp(&self, other: &AcceptEncoding) -> Option<cmp::Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl PartialEq for AcceptEncoding {
-    fn eq(&self, other: &AcceptEncoding) -> bool {
-        self.quality == other.quality
-    }
-}
-
 impl AcceptEncoding {
     fn new(tag: &str) -> Option<AcceptEncoding> {
-        let parts: Vec<&str> = tag.split(';').collect();
-        let encoding = match parts.len() {
-            0 => return None,
-            _ => ContentEncoding::from(parts[0]),
-        };
-        let quality = match parts.len() {
-            1 => encoding.quality(),
-            _ => match f64::from_str(parts[1]) {
-                Ok(q) => q,
-                Err(_) => 0.0,
+        let mut parts = tag.split(';');
+        let encoding = match parts.next().map(|s| s.trim()) {
+            None | Some("") => return None,
+            Some("*") => None,
+            Some(name) => match ContentEncoding::from(name) {
+                ContentEncoding::Identity => return None,
+                encoding => Some(encoding),
             },
         };
+        let mut quality = 1.0;
+        for param in parts {
+            let param = param.trim();
+            if param.starts_with("q=") || param.starts_with("Q=") {
+                quality = f64::from_str(&param[2..]).unwrap_or(0.0);
+            }
+        }
         Some(AcceptEncoding { encoding, quality })
     }
 
-    /// Parse a raw Accept-Encoding header value into an ordered list.
-    pub fn parse(raw: &str) -> ContentEncoding {
-        let mut encodings: Vec<_> = raw
-            .replace(' ', "")
-            .split(',')
-            .map(|l| AcceptEncoding::new(l))
-            .collect();
-        encodings.sort();
+    /// Select encoding from a raw Accept-Encoding header value.
+    ///
+    /// Encoding with highest quality is selected, server preference order
+    /// is used for encodings with the same quality.
+    pub fn parse(raw: &str, preference: &[ContentEncoding]) -> ContentEncoding {
+        let encodings: Vec<_> = raw.split(',').filter_map(AcceptEncoding::new).collect();
+        let quality = |encoding: ContentEncoding| {
+            encodings
+                .iter()
+                .find(|enc| enc.encoding == Some(encoding))
+                .or_else(|| encodings.iter().find(|enc| enc.encoding.is_none()))
+                .map_or(0.0, |enc| enc.quality)
+        };
 
-        for enc in encodings {
-            if let Some(enc) = enc {
-                return enc.encoding;
+        let mut selected = ContentEncoding::Identity;
+        let mut max = 0.0;
+        for encoding in preference {
+            let q = quality(*encoding);
+            if q > max {
+                selected = *encoding;
+                max = q;
             }
         }
-        ContentEncoding::Identity
+        selected
     }
 }
 
@@ -760,4 +834,58 @@ mod tests {
             Bytes::from_static(b"4\r\ntest\r\n0\r\n\r\n")
         );
     }
+
+    #[test]
+    fn test_accept_encoding() {
+        let pref = CompressionPolicy::new();
+        let pref = pref.get_preference();
+        assert_eq!(AcceptEncoding::parse("", pref), ContentEncoding::Identity);
+        assert_eq!(
+            AcceptEncoding::parse("identity", pref),
+            ContentEncoding::Identity
+        );
+        assert_eq!(AcceptEncoding::parse("gzip", pref), ContentEncoding::Gzip);
+        assert_eq!(
+            AcceptEncoding::parse("gzip, deflate, br", pref),
+            ContentEncoding::Br
+        );
+        assert_eq!(
+            AcceptEncoding::parse("br;q=0.5, gzip;q=0.8, deflate", pref),
+            ContentEncoding::Deflate
+        );
+        assert_eq!(
+            AcceptEncoding::parse("br;q=0, deflate, *;q=0.5", pref),
+            ContentEncoding::Deflate
+        );
+        #[cfg(not(feature = "zstd"))]
+        assert_eq!(
+            AcceptEncoding::parse("br;q=0, *", pref),
+            ContentEncoding::Gzip
+        );
+        #[cfg(feature = "zstd")]
+        assert_eq!(
+            AcceptEncoding::parse("br;q=0, *", pref),
+            ContentEncoding::Zstd
+        );
+        #[cfg(feature = "zstd")]
+        assert_eq!(
+            AcceptEncoding::parse("gzip, zstd", pref),
+            ContentEncoding::Zstd
+        );
+        assert_eq!(
+            AcceptEncoding::parse("gzip;q=0, deflate;q=0", pref),
+            ContentEncoding::Identity
+        );
+
+        let pref = [ContentEncoding::Gzip, ContentEncoding::Br];
+        assert_eq!(
+            AcceptEncoding::parse("gzip, deflate, br", &pref),
+            ContentEncoding::Gzip
+        );
+        assert_eq!(
+            AcceptEncoding::parse("br, deflate", &pref),
+            ContentEncoding::Br
+        );
+        assert_eq!(AcceptEncoding::parse("deflate", &pref), ContentEncoding::Identity);
+    }
 }
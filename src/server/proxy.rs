@@ -0,0 +1,538 @@
+//! HAProxy PROXY protocol support
+use std::fs::File;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use std::{cmp, io, str};
+
+use bytes::{Buf, Bytes, BytesMut};
+use futures::{Async, Future, Poll};
+use tokio_io::{AsyncRead, AsyncWrite};
+use tokio_timer::Delay;
+
+use extensions::Extensions;
+
+use super::IoStream;
+
+const V1_PREFIX: &[u8] = b"PROXY ";
+const V1_MAX_LENGTH: usize = 107;
+const V2_SIGNATURE: &[u8] = b"\r\n\r\n\0\r\nQUIT\n";
+const V2_HEADER_LENGTH: usize = 16;
+
+/// Neither of the headers could be shorter, so it is safe to read
+/// this much before we know the header version.
+const MIN_LENGTH: usize = 15;
+
+/// PROXY protocol settings of a listener.
+///
+/// Connections from trusted peers must start with PROXY protocol header,
+/// version 1 or 2. Source address of the header is used as peer address
+/// of the connection. Headers are not expected from untrusted peers, so
+/// they can not spoof their address.
+///
+/// ```rust
+/// # extern crate actix_web;
+/// use actix_web::{server, App};
+///
+/// fn main() {
+///     let proxy = server::ProxyProtocol::new()
+///         .trust("10.0.0.0/8")
+///         .trust("192.168.1.10");
+///
+///     let srv = server::new(|| App::new().resource("/", |r| r.f(|_| "Hello")))
+///         .proxy_protocol(proxy)
+///         .bind("127.0.0.1:0")
+///         .unwrap();
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ProxyProtocol {
+    trusted: Vec<IpNet>,
+    timeout: Duration,
+}
+
+impl Default for ProxyProtocol {
+    fn default() -> ProxyProtocol {
+        ProxyProtocol {
+            trusted: Vec::new(),
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+impl ProxyProtocol {
+    /// Create PROXY protocol settings without trusted peers
+    pub fn new() -> ProxyProtocol {
+        ProxyProtocol::default()
+    }
+
+    /// Set timeout for reading PROXY protocol header.
+    ///
+    /// Connection is closed if complete header is not received within
+    /// this time. By default timeout is 5 seconds.
+    pub fn timeout(mut self, timeout: Duration) -> ProxyProtocol {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Trust peers from the network.
+    ///
+    /// Network is an ip address with optional prefix length,
+    /// i.e. `10.0.0.0/8` or `::1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if network can not be parsed.
+    pub fn trust(mut self, net: &str) -> ProxyProtocol {
+        let net = net
+            .parse()
+            .unwrap_or_else(|_| panic!("Can not parse network: {}", net));
+        self.trusted.push(net);
+        self
+    }
+
+    /// Check if PROXY protocol header is expected from the peer
+    pub(crate) fn is_trusted(&self, peer: Option<SocketAddr>) -> bool {
+        match peer {
+            Some(addr) => self.trusted.iter().any(|net| net.contains(&addr.ip())),
+            None => false,
+        }
+    }
+
+    /// Start reading PROXY protocol header from the stream
+    pub(crate) fn read_header<T: AsyncRead>(&self, io: T) -> ReadProxyHeader<T> {
+        ReadProxyHeader::new(io, self.timeout)
+    }
+}
+
+/// Ip network, address with a prefix length
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct IpNet {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl IpNet {
+    pub fn contains(&self, addr: &IpAddr) -> bool {
+        match (self.addr, to_canonical(addr)) {
+            (IpAddr::V4(net), IpAddr::V4(addr)) => {
+                let mask = mask(0xffff_ffff, 32, self.prefix);
+                u128::from(u32::from(net)) & mask == u128::from(u32::from(addr)) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(addr)) => {
+                let mask = mask(!0, 128, self.prefix);
+                u128::from(net) & mask == u128::from(addr) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+fn mask(all: u128, bits: u8, prefix: u8) -> u128 {
+    if prefix == 0 {
+        0
+    } else {
+        all & !((1u128 << (bits - prefix)) - 1)
+    }
+}
+
+/// Ipv4 address mapped to ipv6 is an ipv4 address
+fn to_canonical(addr: &IpAddr) -> IpAddr {
+    if let IpAddr::V6(ref v6) = *addr {
+        if let Some(v4) = v6.to_ipv4() {
+            if v6.segments()[..6] == [0, 0, 0, 0, 0, 0xffff] {
+                return IpAddr::V4(v4);
+            }
+        }
+    }
+    *addr
+}
+
+impl str::FromStr for IpNet {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<IpNet, ()> {
+        let mut parts = s.trim().splitn(2, '/');
+        let addr: IpAddr = parts.next().unwrap().parse().map_err(|_| ())?;
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match parts.next() {
+            Some(prefix) => prefix.parse().map_err(|_| ())?,
+            None => max,
+        };
+        if prefix > max {
+            return Err(());
+        }
+        Ok(IpNet {
+            addr: to_canonical(&addr),
+            prefix,
+        })
+    }
+}
+
+enum Parse {
+    /// Header is not complete, total number of bytes to read
+    Incomplete(usize),
+    /// Header is parsed, source address and length of the header
+    Done(Option<SocketAddr>, usize),
+}
+
+fn invalid() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "Invalid PROXY protocol header")
+}
+
+fn parse(buf: &[u8]) -> Result<Parse, io::Error> {
+    if buf.len() < MIN_LENGTH {
+        Ok(Parse::Incomplete(MIN_LENGTH))
+    } else if buf.starts_with(V1_PREFIX) {
+        parse_v1(buf)
+    } else if buf.starts_with(V2_SIGNATURE) {
+        parse_v2(buf)
+    } else {
+        Err(invalid())
+    }
+}
+
+fn parse_v1(buf: &[u8]) -> Result<Parse, io::Error> {
+    let max = cmp::min(buf.len(), V1_MAX_LENGTH);
+    let len = match buf[..max].windows(2).position(|w| w == b"\r\n") {
+        Some(pos) => pos + 2,
+        None if max < V1_MAX_LENGTH => return Ok(Parse::Incomplete(buf.len() + 1)),
+        None => return Err(invalid()),
+    };
+
+    let line =
+        str::from_utf8(&buf[V1_PREFIX.len()..len - 2]).map_err(|_| invalid())?;
+    let mut parts = line.split(' ');
+    let addr = match parts.next() {
+        Some("UNKNOWN") => None,
+        Some(proto @ "TCP4") | Some(proto @ "TCP6") => {
+            let src: IpAddr = parts
+                .next()
+                .and_then(|s| s.parse().ok())
+                .ok_or_else(invalid)?;
+            let _dst: IpAddr = parts
+                .next()
+                .and_then(|s| s.parse().ok())
+                .ok_or_else(invalid)?;
+            let port: u16 = parts
+                .next()
+                .and_then(|s| s.parse().ok())
+                .ok_or_else(invalid)?;
+            if src.is_ipv4() != (proto == "TCP4") {
+                return Err(invalid());
+            }
+            Some(SocketAddr::new(src, port))
+        }
+        _ => return Err(invalid()),
+    };
+    Ok(Parse::Done(addr, len))
+}
+
+fn parse_v2(buf: &[u8]) -> Result<Parse, io::Error> {
+    if buf.len() < V2_HEADER_LENGTH {
+        return Ok(Parse::Incomplete(V2_HEADER_LENGTH));
+    }
+    let len = V2_HEADER_LENGTH + ((buf[14] as usize) << 8 | buf[15] as usize);
+    if buf.len() < len {
+        return Ok(Parse::Incomplete(len));
+    }
+    if buf[12] >> 4 != 2 {
+        return Err(invalid());
+    }
+
+    let data = &buf[V2_HEADER_LENGTH..len];
+    let addr = match (buf[12] & 0x0f, buf[13] >> 4) {
+        // LOCAL command, connection made by the proxy itself
+        (0, _) => None,
+        // PROXY command, AF_INET
+        (1, 1) => {
+            if data.len() < 12 {
+                return Err(invalid());
+            }
+            let ip = Ipv4Addr::new(data[0], data[1], data[2], data[3]);
+            let port = (data[8] as u16) << 8 | data[9] as u16;
+            Some(SocketAddr::new(IpAddr::V4(ip), port))
+        }
+        // PROXY command, AF_INET6
+        (1, 2) => {
+            if data.len() < 36 {
+                return Err(invalid());
+            }
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(&data[..16]);
+            let port = (data[32] as u16) << 8 | data[33] as u16;
+            Some(SocketAddr::new(IpAddr::V6(Ipv6Addr::from(octets)), port))
+        }
+        // PROXY command, AF_UNSPEC or AF_UNIX
+        (1, _) => None,
+        _ => return Err(invalid()),
+    };
+    Ok(Parse::Done(addr, len))
+}
+
+/// Future that reads PROXY protocol header from the stream.
+///
+/// Data received after the header is not lost, resulting stream
+/// returns it before reading from the underlying io, so it could be
+/// handled by tls acceptor or http channel.
+pub(crate) struct ReadProxyHeader<T> {
+    io: Option<T>,
+    buf: BytesMut,
+    timeout: Delay,
+}
+
+impl<T: AsyncRead> ReadProxyHeader<T> {
+    fn new(io: T, timeout: Duration) -> Self {
+        ReadProxyHeader {
+            io: Some(io),
+            buf: BytesMut::with_capacity(V1_MAX_LENGTH),
+            timeout: Delay::new(Instant::now() + timeout),
+        }
+    }
+}
+
+impl<T: AsyncRead> Future for ReadProxyHeader<T> {
+    type Item = (ProxyStream<T>, Option<SocketAddr>);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        loop {
+            let need = match parse(&self.buf)? {
+                Parse::Incomplete(need) => need,
+                Parse::Done(addr, size) => {
+                    let rest = self.buf.split_off(size).freeze();
+                    let io = self.io.take().expect("Use after resolve");
+                    return Ok(Async::Ready((ProxyStream::with_buf(io, rest), addr)));
+                }
+            };
+
+            let need = cmp::max(need, V1_MAX_LENGTH);
+            if self.buf.capacity() < need {
+                let additional = need - self.buf.len();
+                self.buf.reserve(additional);
+            }
+            let io = self.io.as_mut().unwrap();
+            match AsyncRead::read_buf(io, &mut self.buf) {
+                Ok(Async::Ready(0)) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(Async::Ready(_)) => (),
+                Ok(Async::NotReady) => {
+                    return match self.timeout.poll() {
+                        Ok(Async::NotReady) => Ok(Async::NotReady),
+                        Ok(Async::Ready(_)) => Err(io::Error::new(
+                            io::ErrorKind::TimedOut,
+                            "Timeout while reading PROXY protocol header",
+                        )),
+                        Err(e) => Err(io::Error::new(io::ErrorKind::Other, e)),
+                    };
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+#[doc(hidden)]
+/// Io stream that returns buffered data before reading from the wrapped io.
+///
+/// Connections accepted on tcp listeners are passed to acceptors and http
+/// channels as this type, the buffer holds data received after PROXY
+/// protocol header.
+pub struct ProxyStream<T> {
+    io: T,
+    buf: Bytes,
+}
+
+impl<T> ProxyStream<T> {
+    /// Wrap io stream without buffered data
+    pub fn new(io: T) -> Self {
+        ProxyStream::with_buf(io, Bytes::new())
+    }
+
+    fn with_buf(io: T, buf: Bytes) -> Self {
+        ProxyStream { io, buf }
+    }
+
+    /// Get reference to the wrapped io stream
+    pub fn get_ref(&self) -> &T {
+        &self.io
+    }
+}
+
+impl<T: IoStream> IoStream for ProxyStream<T> {
+    #[inline]
+    fn shutdown(&mut self, how: Shutdown) -> io::Result<()> {
+        IoStream::shutdown(&mut self.io, how)
+    }
+
+    #[inline]
+    fn set_nodelay(&mut self, nodelay: bool) -> io::Result<()> {
+        self.io.set_nodelay(nodelay)
+    }
+
+    #[inline]
+    fn set_linger(&mut self, dur: Option<Duration>) -> io::Result<()> {
+        self.io.set_linger(dur)
+    }
+
+    #[inline]
+    fn can_send_file(&self) -> bool {
+        self.io.can_send_file()
+    }
+
+    #[inline]
+    fn send_file(
+        &mut self, file: &File, offset: u64, len: usize,
+    ) -> Poll<usize, io::Error> {
+        self.io.send_file(file, offset, len)
+    }
+
+    #[inline]
+    fn extensions(&self) -> Option<Rc<Extensions>> {
+        self.io.extensions()
+    }
+}
+
+impl<T: io::Read> io::Read for ProxyStream<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.buf.is_empty() {
+            self.io.read(buf)
+        } else {
+            let n = cmp::min(buf.len(), self.buf.len());
+            buf[..n].copy_from_slice(&self.buf[..n]);
+            self.buf.advance(n);
+            Ok(n)
+        }
+    }
+}
+
+impl<T: io::Write> io::Write for ProxyStream<T> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.io.write(buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.io.flush()
+    }
+}
+
+impl<T: AsyncRead> AsyncRead for ProxyStream<T> {
+    #[inline]
+    unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
+        self.io.prepare_uninitialized_buffer(buf)
+    }
+}
+
+impl<T: AsyncWrite> AsyncWrite for ProxyStream<T> {
+    #[inline]
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        AsyncWrite::shutdown(&mut self.io)
+    }
+
+    #[inline]
+    fn write_buf<B: Buf>(&mut self, buf: &mut B) -> Poll<usize, io::Error> {
+        self.io.write_buf(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_all(buf: &[u8]) -> Result<Option<SocketAddr>, io::Error> {
+        let mut len = 0;
+        loop {
+            match parse(&buf[..len])? {
+                Parse::Incomplete(need) => {
+                    assert!(need <= buf.len());
+                    len = need;
+                }
+                Parse::Done(addr, size) => {
+                    assert_eq!(size, buf.len());
+                    return Ok(addr);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_v1() {
+        let addr = parse_all(b"PROXY TCP4 192.168.0.1 192.168.0.11 56324 443\r\n");
+        assert_eq!(addr.unwrap(), Some("192.168.0.1:56324".parse().unwrap()));
+
+        let addr = parse_all(b"PROXY TCP6 ::1 ::2 1000 80\r\n");
+        assert_eq!(addr.unwrap(), Some("[::1]:1000".parse().unwrap()));
+
+        let addr = parse_all(b"PROXY UNKNOWN\r\n");
+        assert_eq!(addr.unwrap(), None);
+
+        assert!(parse_all(b"PROXY TCP4 ::1 ::2 1000 80\r\n").is_err());
+        assert!(parse_all(b"PROXY TCP4 192.168.0.1\r\n").is_err());
+        assert!(parse_all(b"GET / HTTP/1.1\r\n\r\n").is_err());
+
+        // data after the header is not part of it
+        let buf = b"PROXY UNKNOWN\r\nGET / HTTP/1.1\r\n\r\n";
+        match parse(buf).unwrap() {
+            Parse::Done(None, size) => assert_eq!(size, 15),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_parse_v2() {
+        let mut buf = V2_SIGNATURE.to_vec();
+        buf.extend_from_slice(&[0x21, 0x11, 0, 12]);
+        buf.extend_from_slice(&[10, 0, 0, 1, 10, 0, 0, 2, 0x1f, 0x90, 0, 80]);
+        let addr = parse_all(&buf).unwrap();
+        assert_eq!(addr, Some("10.0.0.1:8080".parse().unwrap()));
+
+        // tlv data is skipped
+        let mut buf = V2_SIGNATURE.to_vec();
+        buf.extend_from_slice(&[0x21, 0x21, 0, 40]);
+        buf.extend_from_slice(&Ipv6Addr::from([1u16, 0, 0, 0, 0, 0, 0, 1]).octets());
+        buf.extend_from_slice(&Ipv6Addr::from([1u16, 0, 0, 0, 0, 0, 0, 2]).octets());
+        buf.extend_from_slice(&[0, 80, 0, 80, 1, 0, 1, 0]);
+        let addr = parse_all(&buf).unwrap();
+        assert_eq!(addr, Some("[1::1]:80".parse().unwrap()));
+
+        // LOCAL command
+        let mut buf = V2_SIGNATURE.to_vec();
+        buf.extend_from_slice(&[0x20, 0x00, 0, 0]);
+        assert_eq!(parse_all(&buf).unwrap(), None);
+
+        // unsupported version
+        let mut buf = V2_SIGNATURE.to_vec();
+        buf.extend_from_slice(&[0x11, 0x11, 0, 0]);
+        assert!(parse_all(&buf).is_err());
+    }
+
+    #[test]
+    fn test_proxy_stream() {
+        use std::io::Read;
+
+        let data = Bytes::from_static(b"GET");
+        let mut stream = ProxyStream::with_buf(io::Cursor::new(b" / HTTP/1.1"), data);
+        let mut buf = [0u8; 2];
+        assert_eq!(stream.read(&mut buf).unwrap(), 2);
+        assert_eq!(&buf, b"GE");
+        let mut rest = String::new();
+        stream.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, "T / HTTP/1.1");
+    }
+
+    #[test]
+    fn test_trusted() {
+        let proxy = ProxyProtocol::new().trust("10.0.0.0/8").trust("::1");
+        assert!(proxy.is_trusted(Some("10.1.2.3:80".parse().unwrap())));
+        assert!(proxy.is_trusted(Some("[::ffff:10.1.2.3]:80".parse().unwrap())));
+        assert!(proxy.is_trusted(Some("[::1]:80".parse().unwrap())));
+        assert!(!proxy.is_trusted(Some("11.0.0.1:80".parse().unwrap())));
+        assert!(!proxy.is_trusted(Some("[::2]:80".parse().unwrap())));
+        assert!(!proxy.is_trusted(None));
+
+        let all = ProxyProtocol::new().trust("0.0.0.0/0");
+        assert!(all.is_trusted(Some("1.2.3.4:80".parse().unwrap())));
+        assert!("10.0.0.0/33".parse::<IpNet>().is_err());
+    }
+}
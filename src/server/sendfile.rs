@@ -0,0 +1,45 @@
+//! Zero-copy file transmission with `sendfile(2)`
+use std::fs::File;
+use std::io::{self, Write};
+use std::os::unix::fs::FileExt;
+use std::os::unix::io::AsRawFd;
+use std::cmp;
+
+use futures::{Async, Poll};
+use libc;
+
+/// Max number of bytes `sendfile` transfers at once
+const MAX_SENDFILE: usize = 0x7fff_f000;
+
+/// Send up to `len` bytes of the file starting at `offset` to the socket.
+pub(crate) fn send_file<T>(
+    io: &mut T, file: &File, offset: u64, len: usize,
+) -> Poll<usize, io::Error>
+where
+    T: AsRawFd + Write,
+{
+    let mut off = offset as libc::off_t;
+    let len = cmp::min(len, MAX_SENDFILE);
+    let res = unsafe { libc::sendfile(io.as_raw_fd(), file.as_raw_fd(), &mut off, len) };
+    if res >= 0 {
+        return Ok(Async::Ready(res as usize));
+    }
+
+    let err = io::Error::last_os_error();
+    if err.kind() != io::ErrorKind::WouldBlock {
+        return Err(err);
+    }
+
+    // `sendfile` bypasses the reactor, so socket write readiness does not
+    // get cleared. Write next byte through the stream instead, it either
+    // succeeds or registers current task for write readiness.
+    let mut buf = [0u8; 1];
+    if file.read_at(&mut buf, offset)? == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    match io.write(&buf) {
+        Ok(n) => Ok(Async::Ready(n)),
+        Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => Ok(Async::NotReady),
+        Err(e) => Err(e),
+    }
+}
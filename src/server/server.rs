@@ -15,6 +15,7 @@ use actix::{
 };
 
 use super::accept::{AcceptLoop, AcceptNotify, Command};
+use super::listener::{StdListener, StdStream};
 use super::worker::{Conn, StopWorker, Worker, WorkerClient};
 use super::{PauseServer, ResumeServer, StopServer, Token};
 
@@ -41,12 +42,10 @@ impl Service for Box<Service> {
 
 #[doc(hidden)]
 /// Describes the way serivce handles incoming
-/// TCP connections.
+/// TCP and unix domain socket connections.
 pub trait ServiceHandler {
     /// Handle incoming stream
-    fn handle(
-        &mut self, token: Token, io: net::TcpStream, peer: Option<net::SocketAddr>,
-    );
+    fn handle(&mut self, token: Token, io: StdStream, peer: Option<net::SocketAddr>);
 
     /// Shutdown open handlers
     fn shutdown(&self, _: bool) {}
@@ -62,7 +61,7 @@ pub struct Server {
     threads: usize,
     workers: Vec<(usize, Addr<Worker>)>,
     services: Vec<Box<Service>>,
-    sockets: Vec<Vec<(Token, net::TcpListener)>>,
+    sockets: Vec<Vec<(Token, StdListener)>>,
     accept: AcceptLoop,
     exit: bool,
     shutdown_timeout: u16,
@@ -70,6 +69,7 @@ pub struct Server {
     no_signals: bool,
     maxconn: usize,
     maxconnrate: usize,
+    total_conns: Arc<AtomicUsize>,
 }
 
 impl Default for Server {
@@ -93,6 +93,7 @@ impl Server {
             no_signals: false,
             maxconn: 102_400,
             maxconnrate: 256,
+            total_conns: Arc::new(AtomicUsize::new(0)),
         }
     }
 
@@ -163,7 +164,7 @@ impl Server {
     /// Add new service to server
     pub fn service<T>(mut self, srv: T) -> Self
     where
-        T: Into<(Box<Service>, Vec<(Token, net::TcpListener)>)>,
+        T: Into<(Box<Service>, Vec<(Token, StdListener)>)>,
     {
         let (srv, sockets) = srv.into();
         self.services.push(srv);
@@ -217,7 +218,7 @@ impl Server {
             // start accept thread
             for sock in &self.sockets {
                 for s in sock.iter() {
-                    info!("Starting server on http://{}", s.1.local_addr().unwrap());
+                    info!("Starting server on {}", s.1.local_addr());
                 }
             }
             let rx = self
@@ -253,8 +254,13 @@ impl Server {
     fn start_worker(
         &self, idx: usize, notify: AcceptNotify,
     ) -> (Addr<Worker>, WorkerClient) {
-        let (tx, rx) = unbounded::<Conn<net::TcpStream>>();
-        let conns = Connections::new(notify, self.maxconn, self.maxconnrate);
+        let (tx, rx) = unbounded::<Conn<StdStream>>();
+        let conns = Connections::new(
+            notify,
+            self.maxconn,
+            self.maxconnrate,
+            Arc::clone(&self.total_conns),
+        );
         let worker = WorkerClient::new(idx, tx, conns.clone());
         let services: Vec<_> = self.services.iter().map(|v| v.clone()).collect();
 
@@ -418,7 +424,10 @@ impl StreamHandler<ServerCommand, ()> for Server {
 pub struct Connections(Arc<ConnectionsInner>);
 
 impl Connections {
-    fn new(notify: AcceptNotify, maxconn: usize, maxconnrate: usize) -> Self {
+    fn new(
+        notify: AcceptNotify, maxconn: usize, maxconnrate: usize,
+        total: Arc<AtomicUsize>,
+    ) -> Self {
         let maxconn_low = if maxconn > 10 { maxconn - 10 } else { 0 };
         let maxconnrate_low = if maxconnrate > 10 {
             maxconnrate - 10
@@ -432,6 +441,7 @@ impl Connections {
             maxconnrate,
             maxconn_low,
             maxconnrate_low,
+            total,
             conn: AtomicUsize::new(0),
             connrate: AtomicUsize::new(0),
         }))
@@ -445,6 +455,11 @@ impl Connections {
         self.0.conn.load(Ordering::Relaxed)
     }
 
+    /// Number of open connections of all workers of the server
+    pub(crate) fn total_connections(&self) -> usize {
+        self.0.total.load(Ordering::Relaxed)
+    }
+
     /// Report opened connection
     pub fn connection(&self) -> ConnectionTag {
         ConnectionTag::new(self.0.clone())
@@ -459,6 +474,8 @@ impl Connections {
 #[derive(Default)]
 struct ConnectionsInner {
     notify: AcceptNotify,
+    /// Connections of all workers, shared between workers of the server
+    total: Arc<AtomicUsize>,
     conn: AtomicUsize,
     connrate: AtomicUsize,
     maxconn: usize,
@@ -497,6 +514,7 @@ pub struct ConnectionTag(Arc<ConnectionsInner>);
 impl ConnectionTag {
     fn new(inner: Arc<ConnectionsInner>) -> Self {
         inner.conn.fetch_add(1, Ordering::Relaxed);
+        inner.total.fetch_add(1, Ordering::Relaxed);
         ConnectionTag(inner)
     }
 }
@@ -504,6 +522,7 @@ impl ConnectionTag {
 impl Drop for ConnectionTag {
     fn drop(&mut self) {
         let conn = self.0.conn.fetch_sub(1, Ordering::Relaxed);
+        self.0.total.fetch_sub(1, Ordering::Relaxed);
         self.0.notify_maxconn(conn);
     }
 }
@@ -17,6 +17,7 @@ use tokio_timer::Interval;
 
 use super::channel::Node;
 use super::message::{Request, RequestPool};
+use super::proxy::IpNet;
 use super::server::{ConnectionRateTag, ConnectionTag, Connections};
 use super::KeepAlive;
 use body::Body;
@@ -49,6 +50,8 @@ pub struct ServerSettings {
     host: String,
     cpu_pool: LazyCell<CpuPool>,
     responses: &'static HttpResponsePool,
+    trusted_proxies: Option<Vec<IpNet>>,
+    conns: Option<Connections>,
 }
 
 impl Clone for ServerSettings {
@@ -59,6 +62,8 @@ impl Clone for ServerSettings {
             host: self.host.clone(),
             cpu_pool: LazyCell::new(),
             responses: HttpResponsePool::get_pool(),
+            trusted_proxies: self.trusted_proxies.clone(),
+            conns: self.conns.clone(),
         }
     }
 }
@@ -71,6 +76,8 @@ impl Default for ServerSettings {
             host: "localhost:8080".to_owned(),
             responses: HttpResponsePool::get_pool(),
             cpu_pool: LazyCell::new(),
+            trusted_proxies: None,
+            conns: None,
         }
     }
 }
@@ -95,9 +102,28 @@ impl ServerSettings {
             host,
             cpu_pool,
             responses,
+            trusted_proxies: None,
+            conns: None,
         }
     }
 
+    pub(crate) fn with_trusted_proxies(mut self, nets: Option<Vec<IpNet>>) -> Self {
+        self.trusted_proxies = nets;
+        self
+    }
+
+    pub(crate) fn with_connections(mut self, conns: Connections) -> Self {
+        self.conns = Some(conns);
+        self
+    }
+
+    /// Returns the number of open connections of the server.
+    ///
+    /// Connections of all workers are counted.
+    pub fn connections(&self) -> Option<usize> {
+        self.conns.as_ref().map(|conns| conns.total_connections())
+    }
+
     /// Returns the socket address of the local half of this TCP connection
     pub fn local_addr(&self) -> Option<net::SocketAddr> {
         self.addr
@@ -113,6 +139,32 @@ impl ServerSettings {
         &self.host
     }
 
+    /// Returns true if forwarded headers from the peer could be trusted.
+    ///
+    /// Peers are trusted if trusted proxies are not configured.
+    pub fn is_trusted_proxy(&self, peer: Option<net::SocketAddr>) -> bool {
+        match self.trusted_proxies {
+            Some(ref nets) => match peer {
+                Some(addr) => nets.iter().any(|net| net.contains(&addr.ip())),
+                None => false,
+            },
+            None => true,
+        }
+    }
+
+    /// Returns true if forwarding proxies are configured
+    pub(crate) fn has_trusted_proxies(&self) -> bool {
+        self.trusted_proxies.is_some()
+    }
+
+    /// Returns true if address belongs to one of trusted proxies
+    pub(crate) fn is_trusted_addr(&self, addr: &net::IpAddr) -> bool {
+        match self.trusted_proxies {
+            Some(ref nets) => nets.iter().any(|net| net.contains(addr)),
+            None => false,
+        }
+    }
+
     /// Returns default `CpuPool` for server
     pub fn cpu_pool(&self) -> &CpuPool {
         self.cpu_pool.borrow_with(|| DEFAULT_CPUPOOL.lock().clone())
@@ -180,7 +232,7 @@ impl<H> WorkerSettings<H> {
         WorkerSettings {
             h,
             bytes: Rc::new(SharedBytesPool::new()),
-            messages: RequestPool::pool(settings),
+            messages: RequestPool::pool(settings.with_connections(conns.clone())),
             node: RefCell::new(Node::head()),
             date: UnsafeCell::new(Date::new()),
             keep_alive,
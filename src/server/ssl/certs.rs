@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, SystemTime};
+use std::{fs, io, thread};
+
+use parking_lot::{Mutex, RwLock};
+
+/// Certificate chain and private key files
+#[derive(Clone)]
+struct CertFiles {
+    cert: PathBuf,
+    key: PathBuf,
+}
+
+impl CertFiles {
+    /// Last modification time of the files
+    fn modified(
+        &self, mtime: fn(&Path) -> io::Result<SystemTime>,
+    ) -> Option<SystemTime> {
+        let cert = mtime(&self.cert).ok();
+        let key = mtime(&self.key).ok();
+        cert.into_iter().chain(key).max()
+    }
+}
+
+fn file_mtime(path: &Path) -> io::Result<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified())
+}
+
+struct Entry<T> {
+    files: CertFiles,
+    cert: T,
+}
+
+struct Certificates<T> {
+    default: Option<Entry<T>>,
+    hosts: HashMap<String, Entry<T>>,
+}
+
+/// Certificates selected by SNI hostname, shared by tls acceptor
+/// and its resolver handles
+pub(crate) struct CertStore<T> {
+    certs: RwLock<Certificates<T>>,
+    load: fn(&Path, &Path) -> io::Result<T>,
+    /// Modification time source of certificate files
+    mtime: fn(&Path) -> io::Result<SystemTime>,
+    /// Interval of the watcher thread, if it is started
+    watch: Mutex<Option<Duration>>,
+}
+
+impl<T: Clone + Send + Sync + 'static> CertStore<T> {
+    pub fn new(load: fn(&Path, &Path) -> io::Result<T>) -> Arc<CertStore<T>> {
+        Arc::new(CertStore {
+            load,
+            mtime: file_mtime,
+            watch: Mutex::new(None),
+            certs: RwLock::new(Certificates {
+                default: None,
+                hosts: HashMap::new(),
+            }),
+        })
+    }
+
+    fn load(&self, cert: &Path, key: &Path) -> io::Result<Entry<T>> {
+        Ok(Entry {
+            cert: (self.load)(cert, key)?,
+            files: CertFiles {
+                cert: cert.to_owned(),
+                key: key.to_owned(),
+            },
+        })
+    }
+
+    pub fn set_default(&self, cert: &Path, key: &Path) -> io::Result<()> {
+        let entry = self.load(cert, key)?;
+        self.certs.write().default = Some(entry);
+        Ok(())
+    }
+
+    pub fn add(&self, host: &str, cert: &Path, key: &Path) -> io::Result<()> {
+        let entry = self.load(cert, key)?;
+        self.certs.write().hosts.insert(host.to_lowercase(), entry);
+        Ok(())
+    }
+
+    pub fn remove(&self, host: &str) {
+        self.certs.write().hosts.remove(&host.to_lowercase());
+    }
+
+    /// Load all certificates from their files.
+    ///
+    /// Certificates are replaced only if all of them are loaded.
+    pub fn reload(&self) -> io::Result<()> {
+        let files: Vec<_> = {
+            let certs = self.certs.read();
+            certs
+                .hosts
+                .iter()
+                .map(|(host, entry)| (Some(host.clone()), entry.files.clone()))
+                .chain(certs.default.iter().map(|entry| (None, entry.files.clone())))
+                .collect()
+        };
+
+        let mut certs = Certificates {
+            default: None,
+            hosts: HashMap::new(),
+        };
+        for (host, files) in files {
+            let entry = self.load(&files.cert, &files.key)?;
+            match host {
+                Some(host) => {
+                    certs.hosts.insert(host, entry);
+                }
+                None => certs.default = Some(entry),
+            }
+        }
+        *self.certs.write() = certs;
+        Ok(())
+    }
+
+    /// Find certificate for the hostname.
+    ///
+    /// Exact hostname is checked first, then wildcard name
+    /// (`*.example.com`), then default certificate.
+    pub fn get(&self, host: Option<&str>) -> Option<T> {
+        let certs = self.certs.read();
+        if let Some(host) = host {
+            let host = host.to_lowercase();
+            if let Some(entry) = certs.hosts.get(&host) {
+                return Some(entry.cert.clone());
+            }
+            if let Some(pos) = host.find('.') {
+                let wildcard = format!("*{}", &host[pos..]);
+                if let Some(entry) = certs.hosts.get(&wildcard) {
+                    return Some(entry.cert.clone());
+                }
+            }
+        }
+        certs.default.as_ref().map(|entry| entry.cert.clone())
+    }
+
+    /// Last modification time of certificate files
+    fn modified(&self) -> Option<SystemTime> {
+        let certs = self.certs.read();
+        certs
+            .hosts
+            .values()
+            .chain(certs.default.iter())
+            .filter_map(|entry| entry.files.modified(self.mtime))
+            .max()
+    }
+
+    /// Reload certificates if their files changed since `modified`.
+    ///
+    /// Returns `true` if certificates are reloaded.
+    fn reload_modified(&self, modified: &mut Option<SystemTime>) -> bool {
+        let m = self.modified();
+        if m == *modified {
+            return false;
+        }
+        match self.reload() {
+            Ok(()) => {
+                *modified = m;
+                true
+            }
+            Err(err) => {
+                error!("Can not reload tls certificates: {}", err);
+                false
+            }
+        }
+    }
+
+    /// Reload certificates when their files change.
+    ///
+    /// Files are checked in a background thread, the thread stops
+    /// once the store is dropped. Only one thread is started per store,
+    /// subsequent calls change the interval after current one elapses.
+    pub fn watch(store: &Arc<CertStore<T>>, interval: Duration) {
+        {
+            let mut watch = store.watch.lock();
+            let started = watch.is_some();
+            *watch = Some(interval);
+            if started {
+                return;
+            }
+        }
+        let mut modified = store.modified();
+        let store: Weak<CertStore<T>> = Arc::downgrade(store);
+
+        let _ = thread::Builder::new()
+            .name("actix-web tls watcher".to_owned())
+            .spawn(move || loop {
+                let interval = match store.upgrade() {
+                    Some(store) => store.watch.lock().unwrap_or(interval),
+                    None => return,
+                };
+                thread::sleep(interval);
+                let store = match store.upgrade() {
+                    Some(store) => store,
+                    None => return,
+                };
+                store.reload_modified(&mut modified);
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static MTIME: AtomicUsize = AtomicUsize::new(0);
+
+    fn load(cert: &Path, _: &Path) -> io::Result<String> {
+        fs::read_to_string(cert)
+    }
+
+    fn mtime(_: &Path) -> io::Result<SystemTime> {
+        let secs = MTIME.load(Ordering::SeqCst) as u64;
+        Ok(SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+    }
+
+    #[test]
+    fn test_cert_store() {
+        let dir = ::std::env::temp_dir()
+            .join(format!("actix-web-certs-{}", ::std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let write = |name: &str, content: &str| {
+            let path = dir.join(name);
+            fs::File::create(&path)
+                .unwrap()
+                .write_all(content.as_bytes())
+                .unwrap();
+            path
+        };
+        let default = write("default.pem", "default");
+        let example = write("example.pem", "example");
+        let wildcard = write("wildcard.pem", "wildcard");
+
+        let mut store = CertStore::new(load);
+        Arc::get_mut(&mut store).unwrap().mtime = mtime;
+        assert_eq!(store.get(Some("example.com")), None);
+
+        store.set_default(&default, &default).unwrap();
+        store.add("Example.com", &example, &example).unwrap();
+        store.add("*.example.com", &wildcard, &wildcard).unwrap();
+        let missing = dir.join("missing.pem");
+        assert!(store.add("rust-lang.org", &missing, &example).is_err());
+
+        assert_eq!(store.get(None), Some("default".to_owned()));
+        assert_eq!(store.get(Some("rust-lang.org")), Some("default".to_owned()));
+        assert_eq!(store.get(Some("EXAMPLE.com")), Some("example".to_owned()));
+        assert_eq!(store.get(Some("www.example.com")), Some("wildcard".to_owned()));
+        assert_eq!(store.get(Some("a.www.example.com")), Some("default".to_owned()));
+
+        write("example.pem", "updated");
+        store.reload().unwrap();
+        assert_eq!(store.get(Some("example.com")), Some("updated".to_owned()));
+
+        // certificates are not replaced if any of them can not be loaded
+        fs::remove_file(&wildcard).unwrap();
+        write("example.pem", "failed");
+        assert!(store.reload().is_err());
+        assert_eq!(store.get(Some("example.com")), Some("updated".to_owned()));
+
+        store.remove("example.com");
+        assert_eq!(store.get(Some("example.com")), Some("default".to_owned()));
+
+        // only changed files are reloaded
+        store.remove("*.example.com");
+        let mut modified = store.modified();
+        write("default.pem", "watched");
+        assert!(!store.reload_modified(&mut modified));
+        assert_eq!(store.get(None), Some("default".to_owned()));
+        MTIME.fetch_add(1, Ordering::SeqCst);
+        assert!(store.reload_modified(&mut modified));
+        assert_eq!(store.get(None), Some("watched".to_owned()));
+        assert!(!store.reload_modified(&mut modified));
+
+        CertStore::watch(&store, Duration::from_secs(20));
+        CertStore::watch(&store, Duration::from_secs(10));
+        assert_eq!(*store.watch.lock(), Some(Duration::from_secs(10)));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
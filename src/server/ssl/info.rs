@@ -0,0 +1,478 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use error::{Error, ErrorForbidden};
+use handler::FromRequest;
+use httprequest::HttpRequest;
+
+/// Details of established tls connection.
+///
+/// Tls acceptors put `TlsInfo` to the io stream extensions, it is
+/// available for every request of the connection.
+///
+/// ```rust
+/// # extern crate actix_web;
+/// use actix_web::{server::TlsInfo, HttpRequest};
+///
+/// fn index(req: &HttpRequest) -> String {
+///     if let Some(info) = req.stream_extensions().and_then(|e| e.get::<TlsInfo>()) {
+///         format!("Negotiated protocol: {:?}", info.version())
+///     } else {
+///         "Plain text connection".to_owned()
+///     }
+/// }
+/// # fn main() {}
+/// ```
+#[derive(Clone, Debug)]
+pub struct TlsInfo {
+    version: Option<String>,
+    cipher: Option<String>,
+    alpn: Option<Vec<u8>>,
+    peer_certificates: Vec<PeerCertificate>,
+}
+
+impl TlsInfo {
+    #[cfg(any(feature = "alpn", feature = "tls", feature = "rust-tls"))]
+    pub(crate) fn new(
+        version: Option<String>, cipher: Option<String>, alpn: Option<Vec<u8>>,
+        peer_certificates: Vec<PeerCertificate>,
+    ) -> TlsInfo {
+        TlsInfo {
+            version,
+            cipher,
+            alpn,
+            peer_certificates,
+        }
+    }
+
+    /// Negotiated protocol version, i.e. `TLSv1.2`.
+    ///
+    /// `NativeTlsAcceptor` does not provide protocol version.
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_ref().map(|s| s.as_ref())
+    }
+
+    /// Name of negotiated cipher suite.
+    ///
+    /// `NativeTlsAcceptor` does not provide cipher suite.
+    pub fn cipher(&self) -> Option<&str> {
+        self.cipher.as_ref().map(|s| s.as_ref())
+    }
+
+    /// Protocol selected with ALPN, i.e. `h2`
+    pub fn alpn_protocol(&self) -> Option<&[u8]> {
+        self.alpn.as_ref().map(|p| p.as_ref())
+    }
+
+    /// Certificate of the client, if it was requested and sent.
+    ///
+    /// Acceptor verifies the certificate according to its configuration,
+    /// i.e. `SslAcceptorBuilder::set_verify()` for openssl.
+    pub fn peer_certificate(&self) -> Option<&PeerCertificate> {
+        self.peer_certificates.first()
+    }
+
+    /// Certificate chain of the client, starting with client's certificate.
+    ///
+    /// `NativeTlsAcceptor` provides client's certificate only.
+    pub fn peer_certificates(&self) -> &[PeerCertificate] {
+        &self.peer_certificates
+    }
+}
+
+/// Subject alternative name of the certificate
+#[derive(Clone, Debug, PartialEq)]
+pub enum SubjectAltName {
+    /// Dns name
+    Dns(String),
+    /// Email address
+    Email(String),
+    /// Uniform resource identifier
+    Uri(String),
+    /// Ip address
+    Ip(IpAddr),
+}
+
+/// X.509 certificate of the tls peer.
+///
+/// Subject and alternative names are parsed from DER data by actix-web
+/// itself, so they are available with every tls backend.
+///
+/// `PeerCertificate` could be used as an extractor, request is rejected
+/// with `403 Forbidden` response if client did not send a certificate.
+///
+/// ```rust
+/// # extern crate actix_web;
+/// use actix_web::server::PeerCertificate;
+///
+/// fn index(cert: PeerCertificate) -> String {
+///     format!("Welcome {}!", cert.common_name().unwrap_or("unknown"))
+/// }
+/// # fn main() {}
+/// ```
+#[derive(Clone, Debug)]
+pub struct PeerCertificate {
+    der: Vec<u8>,
+    subject: String,
+    common_name: Option<String>,
+    alt_names: Vec<SubjectAltName>,
+}
+
+impl PeerCertificate {
+    /// Create certificate from DER encoded data.
+    ///
+    /// Subject and alternative names are empty if data can not be parsed.
+    pub fn from_der(der: Vec<u8>) -> PeerCertificate {
+        let mut cert = PeerCertificate {
+            der,
+            subject: String::new(),
+            common_name: None,
+            alt_names: Vec::new(),
+        };
+        if let Some((subject, alt_names)) = parse_certificate(&cert.der) {
+            cert.common_name = subject
+                .iter()
+                .rev()
+                .find(|attr| attr.0 == "CN")
+                .map(|attr| attr.1.clone());
+            cert.subject = subject
+                .iter()
+                .map(|attr| format!("{}={}", attr.0, attr.1))
+                .collect::<Vec<_>>()
+                .join(", ");
+            cert.alt_names = alt_names;
+        }
+        cert
+    }
+
+    /// DER encoded certificate
+    pub fn der(&self) -> &[u8] {
+        &self.der
+    }
+
+    /// Subject of the certificate, i.e. `C=US, O=Company, CN=www.example.com`
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// Common name of the certificate's subject
+    pub fn common_name(&self) -> Option<&str> {
+        self.common_name.as_ref().map(|s| s.as_ref())
+    }
+
+    /// Subject alternative names of the certificate
+    pub fn alt_names(&self) -> &[SubjectAltName] {
+        &self.alt_names
+    }
+}
+
+impl<S> FromRequest<S> for PeerCertificate {
+    type Config = ();
+    type Result = Result<Self, Error>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest<S>, _: &Self::Config) -> Self::Result {
+        req.stream_extensions()
+            .and_then(|ext| ext.get::<TlsInfo>())
+            .and_then(|info| info.peer_certificate())
+            .cloned()
+            .ok_or_else(|| ErrorForbidden("Client certificate is required"))
+    }
+}
+
+const OID_SUBJECT_ALT_NAME: &[u8] = &[0x55, 0x1d, 0x11];
+
+const TAG_OID: u8 = 0x06;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_VERSION: u8 = 0xa0;
+const TAG_EXTENSIONS: u8 = 0xa3;
+const TAG_BMP_STRING: u8 = 0x1e;
+
+/// Attributes of distinguished name
+type Name = Vec<(String, String)>;
+
+/// Read DER encoded value, returns tag, content and rest of the data
+fn read_tlv(buf: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    if buf.len() < 2 {
+        return None;
+    }
+    let (len, offset) = match buf[1] {
+        n if n < 0x80 => (n as usize, 2),
+        n @ 0x81..=0x84 => {
+            let size = (n & 0x7f) as usize;
+            if buf.len() < 2 + size {
+                return None;
+            }
+            let len = buf[2..2 + size]
+                .iter()
+                .fold(0usize, |len, b| len << 8 | *b as usize);
+            (len, 2 + size)
+        }
+        _ => return None,
+    };
+    if buf.len() - offset < len {
+        return None;
+    }
+    Some((buf[0], &buf[offset..offset + len], &buf[offset + len..]))
+}
+
+/// Read DER encoded value with expected tag
+fn expect_tlv(buf: &[u8], tag: u8) -> Option<(&[u8], &[u8])> {
+    match read_tlv(buf) {
+        Some((t, content, rest)) if t == tag => Some((content, rest)),
+        _ => None,
+    }
+}
+
+/// Parse subject and subject alternative names of X.509 certificate
+fn parse_certificate(der: &[u8]) -> Option<(Name, Vec<SubjectAltName>)> {
+    let (cert, _) = expect_tlv(der, TAG_SEQUENCE)?;
+    let (tbs, _) = expect_tlv(cert, TAG_SEQUENCE)?;
+
+    let mut rest = tbs;
+    if let Some((_, r)) = expect_tlv(rest, TAG_VERSION) {
+        rest = r;
+    }
+    // serial number, signature, issuer, validity
+    for _ in 0..4 {
+        rest = read_tlv(rest)?.2;
+    }
+    let (subject, mut rest) = expect_tlv(rest, TAG_SEQUENCE)?;
+    let subject = parse_name(subject)?;
+
+    // subject public key info, issuer and subject unique ids
+    let mut alt_names = Vec::new();
+    rest = read_tlv(rest)?.2;
+    while let Some((tag, content, r)) = read_tlv(rest) {
+        rest = r;
+        if tag == TAG_EXTENSIONS {
+            let (mut exts, _) = expect_tlv(content, TAG_SEQUENCE)?;
+            while let Some((ext, r)) = expect_tlv(exts, TAG_SEQUENCE) {
+                exts = r;
+                let (oid, ext) = expect_tlv(ext, TAG_OID)?;
+                if oid != OID_SUBJECT_ALT_NAME {
+                    continue;
+                }
+                // skip critical flag
+                let (tag, mut value, r) = read_tlv(ext)?;
+                if tag != 0x04 {
+                    value = expect_tlv(r, 0x04)?.0;
+                }
+                alt_names = parse_alt_names(value)?;
+            }
+        }
+    }
+    Some((subject, alt_names))
+}
+
+/// Parse distinguished name, attributes are returned in certificate order
+fn parse_name(mut buf: &[u8]) -> Option<Name> {
+    let mut attrs = Vec::new();
+    while !buf.is_empty() {
+        let (mut set, rest) = expect_tlv(buf, 0x31)?;
+        buf = rest;
+        while !set.is_empty() {
+            let (attr, rest) = expect_tlv(set, TAG_SEQUENCE)?;
+            set = rest;
+            let (oid, attr) = expect_tlv(attr, TAG_OID)?;
+            let (tag, value, _) = read_tlv(attr)?;
+            attrs.push((attribute_name(oid), decode_string(tag, value)));
+        }
+    }
+    Some(attrs)
+}
+
+fn parse_alt_names(buf: &[u8]) -> Option<Vec<SubjectAltName>> {
+    let (mut buf, _) = expect_tlv(buf, TAG_SEQUENCE)?;
+    let mut names = Vec::new();
+    while let Some((tag, value, rest)) = read_tlv(buf) {
+        buf = rest;
+        let text = || String::from_utf8_lossy(value).into_owned();
+        match tag {
+            0x81 => names.push(SubjectAltName::Email(text())),
+            0x82 => names.push(SubjectAltName::Dns(text())),
+            0x86 => names.push(SubjectAltName::Uri(text())),
+            0x87 => match value.len() {
+                4 => names.push(SubjectAltName::Ip(IpAddr::V4(Ipv4Addr::new(
+                    value[0], value[1], value[2], value[3],
+                )))),
+                16 => {
+                    let mut octets = [0u8; 16];
+                    octets.copy_from_slice(value);
+                    names.push(SubjectAltName::Ip(IpAddr::V6(Ipv6Addr::from(octets))));
+                }
+                _ => (),
+            },
+            _ => (),
+        }
+    }
+    Some(names)
+}
+
+/// Short name of known attribute, dotted object identifier otherwise
+fn attribute_name(oid: &[u8]) -> String {
+    let name = match oid {
+        [0x55, 0x04, 0x03] => "CN",
+        [0x55, 0x04, 0x05] => "serialNumber",
+        [0x55, 0x04, 0x06] => "C",
+        [0x55, 0x04, 0x07] => "L",
+        [0x55, 0x04, 0x08] => "ST",
+        [0x55, 0x04, 0x09] => "street",
+        [0x55, 0x04, 0x0a] => "O",
+        [0x55, 0x04, 0x0b] => "OU",
+        [0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x01] => "emailAddress",
+        [0x09, 0x92, 0x26, 0x89, 0x93, 0xf2, 0x2c, 0x64, 0x01, 0x19] => "DC",
+        [0x09, 0x92, 0x26, 0x89, 0x93, 0xf2, 0x2c, 0x64, 0x01, 0x01] => "UID",
+        _ => return format_oid(oid),
+    };
+    name.to_owned()
+}
+
+fn format_oid(oid: &[u8]) -> String {
+    let mut parts = Vec::new();
+    let mut value = 0u64;
+    for b in oid {
+        value = value << 7 | u64::from(b & 0x7f);
+        if b & 0x80 == 0 {
+            if parts.is_empty() {
+                let first = if value < 80 { value / 40 } else { 2 };
+                parts.push(first);
+                parts.push(value - first * 40);
+            } else {
+                parts.push(value);
+            }
+            value = 0;
+        }
+    }
+    parts
+        .iter()
+        .map(|p| p.to_string())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+fn decode_string(tag: u8, value: &[u8]) -> String {
+    if tag == TAG_BMP_STRING {
+        let chars: Vec<u16> = value
+            .chunks(2)
+            .filter(|c| c.len() == 2)
+            .map(|c| u16::from(c[0]) << 8 | u16::from(c[1]))
+            .collect();
+        String::from_utf16_lossy(&chars)
+    } else {
+        String::from_utf8_lossy(value).into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pem_to_der(pem: &str) -> Vec<u8> {
+        let data: String = pem.lines().filter(|l| !l.starts_with("-----")).collect();
+        ::base64::decode(&data).unwrap()
+    }
+
+    #[test]
+    fn test_peer_certificate() {
+        let der = pem_to_der(include_str!("../../../tests/cert.pem"));
+        let cert = PeerCertificate::from_der(der.clone());
+        assert_eq!(cert.der(), &der[..]);
+        assert_eq!(cert.common_name(), Some("www.example.com"));
+        assert_eq!(
+            cert.subject(),
+            "C=US, ST=CF, L=SF, O=Company, OU=Org, CN=www.example.com"
+        );
+        assert_eq!(
+            cert.alt_names(),
+            &[SubjectAltName::Dns("localhost".to_owned())]
+        );
+
+        let cert = PeerCertificate::from_der(b"invalid".to_vec());
+        assert_eq!(cert.common_name(), None);
+        assert_eq!(cert.subject(), "");
+    }
+
+    #[test]
+    fn test_critical_alt_names() {
+        // ec certificate with critical subject alternative name extension
+        let pem = concat!(
+        "MIICGjCCAcGgAwIBAgIUAlIfI/5KoD3Lyh+TQjRlfO3a4IUwCgYIKoZIzj0EAwIw",
+        "LTELMAkGA1UEBhMCREUxDTALBgNVBAoMBEFjbWUxDzANBgNVBAMMBmNsaWVudDAe",
+        "Fw0yNjEwMTkwNDA5MDJaFw0zNjEwMTYwNDA5MDJaMC0xCzAJBgNVBAYTAkRFMQ0w",
+        "CwYDVQQKDARBY21lMQ8wDQYDVQQDDAZjbGllbnQwWTATBgcqhkjOPQIBBggqhkjO",
+        "PQMBBwNCAAQY27v95Q6SGlzyR3YKV4uWN9mnhzUEDiQhn2D97fQT7qQ4VMnr+xJr",
+        "UOHr7CU/RpAs4hmlHUDJghHHAgilwwHXo4G+MIG7MB0GA1UdDgQWBBTOKGvi5c65",
+        "UHYpihqaizpAGTgqATAfBgNVHSMEGDAWgBTOKGvi5c65UHYpihqaizpAGTgqATAP",
+        "BgNVHRMBAf8EBTADAQH/MGgGA1UdEQEB/wReMFyCEmNsaWVudC5leGFtcGxlLmNv",
+        "bYESY2xpZW50QGV4YW1wbGUuY29thhpodHRwczovL2V4YW1wbGUuY29tL2NsaWVu",
+        "dIcECgAAAYcQAAAAAAAAAAAAAAAAAAAAATAKBggqhkjOPQQDAgNHADBEAiBnkO9N",
+        "x6zPsvIN9FovhOrCTwWX4wBaaL50eRo5y6ty6wIgYJLqc7TlXP7JKWfR6F2aAhxz",
+        "/HY1QrJOmfPP+CD88XU="
+        );
+        let cert = PeerCertificate::from_der(::base64::decode(pem).unwrap());
+        assert_eq!(cert.subject(), "C=DE, O=Acme, CN=client");
+        assert_eq!(cert.common_name(), Some("client"));
+        assert_eq!(
+            cert.alt_names(),
+            &[
+                SubjectAltName::Dns("client.example.com".to_owned()),
+                SubjectAltName::Email("client@example.com".to_owned()),
+                SubjectAltName::Uri("https://example.com/client".to_owned()),
+                SubjectAltName::Ip("10.0.0.1".parse().unwrap()),
+                SubjectAltName::Ip("::1".parse().unwrap()),
+            ]
+        );
+
+        // truncated data
+        let der = ::base64::decode(pem).unwrap();
+        let cert = PeerCertificate::from_der(der[..der.len() / 2].to_vec());
+        assert_eq!(cert.subject(), "");
+        assert!(cert.alt_names().is_empty());
+    }
+
+    #[test]
+    fn test_bmp_string() {
+        assert_eq!(decode_string(TAG_BMP_STRING, &[0, b'A', 0x04, 0x1f]), "A\u{41f}");
+        assert_eq!(decode_string(0x0c, "\u{e9}t\u{e9}".as_bytes()), "\u{e9}t\u{e9}");
+    }
+
+    #[test]
+    fn test_alt_names() {
+        let mut value = vec![0x30, 28];
+        value.extend_from_slice(&[0x82, 11]);
+        value.extend_from_slice(b"example.com");
+        value.extend_from_slice(&[0x87, 4, 127, 0, 0, 1]);
+        value.extend_from_slice(&[0x81, 7]);
+        value.extend_from_slice(b"a@b.com");
+        assert_eq!(
+            parse_alt_names(&value).unwrap(),
+            vec![
+                SubjectAltName::Dns("example.com".to_owned()),
+                SubjectAltName::Ip("127.0.0.1".parse().unwrap()),
+                SubjectAltName::Email("a@b.com".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_oid() {
+        assert_eq!(format_oid(&[0x55, 0x04, 0x03]), "2.5.4.3");
+        assert_eq!(
+            format_oid(&[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x01]),
+            "1.2.840.113549.1.9.1"
+        );
+        assert_eq!(attribute_name(&[0x55, 0x04, 0x2a]), "2.5.4.42");
+    }
+
+    #[test]
+    fn test_extractor() {
+        use test::TestRequest;
+
+        let req = TestRequest::default().finish();
+        let err = PeerCertificate::extract(&req).unwrap_err();
+        assert_eq!(
+            err.as_response_error().error_response().status(),
+            ::http::StatusCode::FORBIDDEN
+        );
+    }
+}
@@ -1,7 +1,13 @@
+mod info;
+pub use self::info::{PeerCertificate, SubjectAltName, TlsInfo};
+
+#[cfg(any(feature = "alpn", feature = "rust-tls"))]
+mod certs;
+
 #[cfg(feature = "alpn")]
 mod openssl;
 #[cfg(feature = "alpn")]
-pub use self::openssl::OpensslAcceptor;
+pub use self::openssl::{OpensslAcceptor, OpensslCertResolver};
 
 #[cfg(feature = "tls")]
 mod nativetls;
@@ -11,4 +17,4 @@ pub use self::nativetls::{NativeTlsAcceptor, TlsStream};
 #[cfg(feature = "rust-tls")]
 mod rustls;
 #[cfg(feature = "rust-tls")]
-pub use self::rustls::RustlsAcceptor;
+pub use self::rustls::{RustlsAcceptor, RustlsCertResolver};
@@ -1,10 +1,13 @@
 use std::net::Shutdown;
+use std::rc::Rc;
 use std::{io, time};
 
 use futures::{Async, Future, Poll};
 use native_tls::{self, HandshakeError, TlsAcceptor};
 use tokio_io::{AsyncRead, AsyncWrite};
 
+use super::{PeerCertificate, TlsInfo};
+use extensions::Extensions;
 use server::{AcceptorService, IoStream};
 
 #[derive(Clone)]
@@ -73,6 +76,22 @@ impl<Io: IoStream> IoStream for TlsStream<Io> {
     fn set_linger(&mut self, dur: Option<time::Duration>) -> io::Result<()> {
         self.get_mut().get_mut().set_linger(dur)
     }
+
+    fn extensions(&self) -> Option<Rc<Extensions>> {
+        let cert = match self.get_ref().peer_certificate() {
+            Ok(Some(cert)) => cert.to_der().ok(),
+            _ => None,
+        };
+
+        let mut ext = Extensions::new();
+        ext.insert(TlsInfo::new(
+            None,
+            None,
+            None,
+            cert.into_iter().map(PeerCertificate::from_der).collect(),
+        ));
+        Some(Rc::new(ext))
+    }
 }
 
 impl<Io: IoStream> Future for Accept<Io> {
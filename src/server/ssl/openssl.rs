@@ -1,10 +1,19 @@
 use std::net::Shutdown;
-use std::{io, time};
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::{fs, io, time};
 
 use futures::{Future, Poll};
-use openssl::ssl::{AlpnError, SslAcceptor, SslAcceptorBuilder};
+use openssl::error::ErrorStack;
+use openssl::pkey::{PKey, Private};
+use openssl::ssl::{AlpnError, NameType, SniError, SslAcceptor, SslAcceptorBuilder};
+use openssl::x509::X509;
 use tokio_openssl::{AcceptAsync, SslAcceptorExt, SslStream};
 
+use super::certs::CertStore;
+use super::{PeerCertificate, TlsInfo};
+use extensions::Extensions;
 use server::{AcceptorService, IoStream, ServerFlags};
 
 #[derive(Clone)]
@@ -51,6 +60,145 @@ impl OpensslAcceptor {
     }
 }
 
+/// Certificates of `OpensslAcceptor` selected by SNI hostname.
+///
+/// Certificates could be added, replaced or reloaded from their files
+/// at runtime, new connections use new certificates while established
+/// connections are not affected. Clones of the resolver share
+/// the same certificates.
+///
+/// If no certificate matches the hostname, certificate of
+/// `SslAcceptorBuilder` is used.
+///
+/// ```rust,no_run
+/// # extern crate actix_web;
+/// # extern crate openssl;
+/// use actix_web::server::OpensslCertResolver;
+/// use actix_web::{server, App};
+/// use openssl::ssl::{SslAcceptor, SslMethod};
+/// use std::time::Duration;
+///
+/// fn main() {
+///     let resolver = OpensslCertResolver::new();
+///     resolver.set_default("cert.pem", "key.pem").unwrap();
+///     resolver.add("www.rust-lang.org", "rust.pem", "rust-key.pem").unwrap();
+///     // reload certificates once they are renewed
+///     resolver.watch(Duration::from_secs(60));
+///
+///     let mut builder = SslAcceptor::mozilla_intermediate(SslMethod::tls()).unwrap();
+///     resolver.configure(&mut builder);
+///
+///     server::new(|| App::new().resource("/", |r| r.f(|_| "Hello")))
+///         .bind_ssl("127.0.0.1:8443", builder)
+///         .unwrap()
+///         .run();
+/// }
+/// ```
+#[derive(Clone)]
+pub struct OpensslCertResolver {
+    store: Arc<CertStore<OpensslCert>>,
+}
+
+#[derive(Clone)]
+struct OpensslCert {
+    cert: X509,
+    chain: Vec<X509>,
+    key: PKey<Private>,
+}
+
+fn load_cert(cert: &Path, key: &Path) -> io::Result<OpensslCert> {
+    fn err(e: ErrorStack) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, e)
+    }
+
+    let mut chain = X509::stack_from_pem(&fs::read(cert)?).map_err(err)?;
+    if chain.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Certificate file is empty",
+        ));
+    }
+    let cert = chain.remove(0);
+    let key = PKey::private_key_from_pem(&fs::read(key)?).map_err(err)?;
+    if !cert.public_key().map_err(err)?.public_eq(&key) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Private key does not match certificate",
+        ));
+    }
+    Ok(OpensslCert { cert, chain, key })
+}
+
+impl Default for OpensslCertResolver {
+    fn default() -> Self {
+        OpensslCertResolver {
+            store: CertStore::new(load_cert),
+        }
+    }
+}
+
+impl OpensslCertResolver {
+    /// Create resolver without certificates
+    pub fn new() -> Self {
+        OpensslCertResolver::default()
+    }
+
+    /// Load certificate used if no other certificate matches the hostname.
+    ///
+    /// Certificate file should contain certificate chain in PEM format,
+    /// private key must match the certificate.
+    pub fn set_default<P: AsRef<Path>>(&self, cert: P, key: P) -> io::Result<()> {
+        self.store.set_default(cert.as_ref(), key.as_ref())
+    }
+
+    /// Load certificate for the hostname.
+    ///
+    /// Wildcard hostnames, i.e. `*.rust-lang.org`, match single label.
+    pub fn add<P: AsRef<Path>>(&self, host: &str, cert: P, key: P) -> io::Result<()> {
+        self.store.add(host, cert.as_ref(), key.as_ref())
+    }
+
+    /// Remove certificate of the hostname
+    pub fn remove(&self, host: &str) {
+        self.store.remove(host)
+    }
+
+    /// Reload all certificates from their files.
+    ///
+    /// Certificates are replaced only if all of them could be loaded
+    /// and their private keys match.
+    pub fn reload(&self) -> io::Result<()> {
+        self.store.reload()
+    }
+
+    /// Reload certificates when their files change.
+    ///
+    /// Files are checked with the interval in a background thread,
+    /// subsequent calls change the interval of the same thread.
+    pub fn watch(&self, interval: time::Duration) {
+        CertStore::watch(&self.store, interval)
+    }
+
+    /// Use resolver for connections of the acceptor
+    pub fn configure(&self, builder: &mut SslAcceptorBuilder) {
+        let store = self.store.clone();
+        builder.set_servername_callback(move |ssl, _| {
+            let cert = store.get(ssl.servername(NameType::HOST_NAME));
+            if let Some(cert) = cert {
+                ssl.set_certificate(&cert.cert)
+                    .and_then(|_| ssl.set_private_key(&cert.key))
+                    .and_then(|_| {
+                        for c in cert.chain {
+                            ssl.add_chain_cert(c)?;
+                        }
+                        Ok(())
+                    }).map_err(|_| SniError::ALERT_FATAL)?;
+            }
+            Ok(())
+        });
+    }
+}
+
 pub struct AcceptorFut<Io>(AcceptAsync<Io>);
 
 impl<Io: IoStream> Future for AcceptorFut<Io> {
@@ -93,4 +241,32 @@ impl<T: IoStream> IoStream for SslStream<T> {
     fn set_linger(&mut self, dur: Option<time::Duration>) -> io::Result<()> {
         self.get_mut().get_mut().set_linger(dur)
     }
+
+    fn extensions(&self) -> Option<Rc<Extensions>> {
+        let ssl = self.get_ref().ssl();
+
+        // server side chain does not include peer's certificate
+        let mut certs = Vec::new();
+        if let Some(cert) = ssl.peer_certificate() {
+            certs.extend(cert.to_der().ok());
+        }
+        if let Some(chain) = ssl.peer_cert_chain() {
+            for cert in chain {
+                if let Ok(der) = cert.to_der() {
+                    if certs.first() != Some(&der) {
+                        certs.push(der);
+                    }
+                }
+            }
+        }
+
+        let mut ext = Extensions::new();
+        ext.insert(TlsInfo::new(
+            Some(ssl.version_str().to_owned()),
+            ssl.current_cipher().map(|c| c.name().to_owned()),
+            ssl.selected_alpn_protocol().map(|p| p.to_vec()),
+            certs.into_iter().map(PeerCertificate::from_der).collect(),
+        ));
+        Some(Rc::new(ext))
+    }
 }
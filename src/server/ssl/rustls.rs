@@ -1,11 +1,25 @@
+use std::fs::File;
+use std::io::BufReader;
 use std::net::Shutdown;
+use std::path::Path;
+use std::rc::Rc;
 use std::sync::Arc;
 use std::{io, time};
 
-use rustls::{ClientSession, ServerConfig, ServerSession};
+use rustls::internal::pemfile;
+use rustls::sign::{self, CertifiedKey};
+use rustls::{
+    ClientSession, ProtocolVersion, ResolvesServerCert, ServerConfig, ServerSession,
+    Session, SignatureScheme,
+};
 use tokio_io::AsyncWrite;
 use tokio_rustls::{AcceptAsync, ServerConfigExt, TlsStream};
+use untrusted::Input;
+use webpki;
 
+use super::certs::CertStore;
+use super::{PeerCertificate, TlsInfo};
+use extensions::Extensions;
 use server::{AcceptorService, IoStream, ServerFlags};
 
 #[derive(Clone)]
@@ -41,6 +55,154 @@ impl RustlsAcceptor {
     }
 }
 
+/// Certificates of `RustlsAcceptor` selected by SNI hostname.
+///
+/// Certificates could be added, replaced or reloaded from their files
+/// at runtime, new connections use new certificates while established
+/// connections are not affected. Clones of the resolver share
+/// the same certificates.
+///
+/// ```rust,ignore
+/// # extern crate actix_web;
+/// # extern crate rustls;
+/// use actix_web::server::RustlsCertResolver;
+/// use actix_web::{server, App};
+/// use rustls::{NoClientAuth, ServerConfig};
+/// use std::time::Duration;
+///
+/// fn main() {
+///     let resolver = RustlsCertResolver::new();
+///     resolver.set_default("cert.pem", "key.pem").unwrap();
+///     resolver.add("www.rust-lang.org", "rust.pem", "rust-key.pem").unwrap();
+///     // reload certificates once they are renewed
+///     resolver.watch(Duration::from_secs(60));
+///
+///     let mut config = ServerConfig::new(NoClientAuth::new());
+///     resolver.configure(&mut config);
+///
+///     server::new(|| App::new().resource("/", |r| r.f(|_| "Hello")))
+///         .bind_rustls("127.0.0.1:8443", config)
+///         .unwrap()
+///         .run();
+/// }
+/// ```
+#[derive(Clone)]
+pub struct RustlsCertResolver {
+    store: Arc<CertStore<CertifiedKey>>,
+}
+
+fn load_cert(cert: &Path, key: &Path) -> io::Result<CertifiedKey> {
+    fn err(msg: &str) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, msg)
+    }
+
+    let certs = pemfile::certs(&mut BufReader::new(File::open(cert)?))
+        .map_err(|_| err("Can not parse certificate file"))?;
+    if certs.is_empty() {
+        return Err(err("Certificate file is empty"));
+    }
+
+    let mut keys = pemfile::pkcs8_private_keys(&mut BufReader::new(File::open(key)?))
+        .map_err(|_| err("Can not parse private key file"))?;
+    if keys.is_empty() {
+        keys = pemfile::rsa_private_keys(&mut BufReader::new(File::open(key)?))
+            .map_err(|_| err("Can not parse private key file"))?;
+    }
+    let key = keys
+        .first()
+        .ok_or_else(|| err("Private key file is empty"))
+        .and_then(|key| {
+            sign::any_supported_type(key).map_err(|_| err("Unsupported private key"))
+        })?;
+
+    // sign test message with the key and verify it with the certificate
+    const MESSAGE: &[u8] = b"actix-web private key check";
+    let signer = key
+        .choose_scheme(&[
+            SignatureScheme::RSA_PKCS1_SHA256,
+            SignatureScheme::ECDSA_NISTP256_SHA256,
+            SignatureScheme::ECDSA_NISTP384_SHA384,
+        ]).ok_or_else(|| err("Unsupported private key"))?;
+    let alg: &webpki::SignatureAlgorithm = match signer.get_scheme() {
+        SignatureScheme::ECDSA_NISTP256_SHA256 => &webpki::ECDSA_P256_SHA256,
+        SignatureScheme::ECDSA_NISTP384_SHA384 => &webpki::ECDSA_P384_SHA384,
+        _ => &webpki::RSA_PKCS1_2048_8192_SHA256,
+    };
+    let signature = signer
+        .sign(MESSAGE)
+        .map_err(|_| err("Can not sign with private key"))?;
+    webpki::EndEntityCert::from(Input::from(&certs[0].0))
+        .and_then(|cert| {
+            cert.verify_signature(alg, Input::from(MESSAGE), Input::from(&signature))
+        }).map_err(|_| err("Private key does not match certificate"))?;
+
+    Ok(CertifiedKey::new(certs, Arc::new(key)))
+}
+
+impl Default for RustlsCertResolver {
+    fn default() -> Self {
+        RustlsCertResolver {
+            store: CertStore::new(load_cert),
+        }
+    }
+}
+
+impl RustlsCertResolver {
+    /// Create resolver without certificates
+    pub fn new() -> Self {
+        RustlsCertResolver::default()
+    }
+
+    /// Load certificate used if no other certificate matches the hostname.
+    ///
+    /// Certificate file should contain certificate chain in PEM format,
+    /// private key must match the certificate.
+    pub fn set_default<P: AsRef<Path>>(&self, cert: P, key: P) -> io::Result<()> {
+        self.store.set_default(cert.as_ref(), key.as_ref())
+    }
+
+    /// Load certificate for the hostname.
+    ///
+    /// Wildcard hostnames, i.e. `*.rust-lang.org`, match single label.
+    pub fn add<P: AsRef<Path>>(&self, host: &str, cert: P, key: P) -> io::Result<()> {
+        self.store.add(host, cert.as_ref(), key.as_ref())
+    }
+
+    /// Remove certificate of the hostname
+    pub fn remove(&self, host: &str) {
+        self.store.remove(host)
+    }
+
+    /// Reload all certificates from their files.
+    ///
+    /// Certificates are replaced only if all of them could be loaded
+    /// and their private keys match.
+    pub fn reload(&self) -> io::Result<()> {
+        self.store.reload()
+    }
+
+    /// Reload certificates when their files change.
+    ///
+    /// Files are checked with the interval in a background thread,
+    /// subsequent calls change the interval of the same thread.
+    pub fn watch(&self, interval: time::Duration) {
+        CertStore::watch(&self.store, interval)
+    }
+
+    /// Use resolver for connections of the server config
+    pub fn configure(&self, config: &mut ServerConfig) {
+        config.cert_resolver = Arc::new(self.clone());
+    }
+}
+
+impl ResolvesServerCert for RustlsCertResolver {
+    fn resolve(
+        &self, server_name: Option<webpki::DNSNameRef>, _: &[SignatureScheme],
+    ) -> Option<CertifiedKey> {
+        self.store.get(server_name.map(|name| name.into()))
+    }
+}
+
 impl<Io: IoStream> AcceptorService<Io> for RustlsAcceptor {
     type Accepted = TlsStream<Io, ServerSession>;
     type Future = AcceptAsync<Io>;
@@ -88,4 +250,30 @@ impl<Io: IoStream> IoStream for TlsStream<Io, ServerSession> {
     fn set_linger(&mut self, dur: Option<time::Duration>) -> io::Result<()> {
         self.get_mut().0.set_linger(dur)
     }
+
+    fn extensions(&self) -> Option<Rc<Extensions>> {
+        let session = self.get_ref().1;
+        let version = session.get_protocol_version().map(|v| match v {
+            ProtocolVersion::TLSv1_0 => "TLSv1".to_owned(),
+            ProtocolVersion::TLSv1_1 => "TLSv1.1".to_owned(),
+            ProtocolVersion::TLSv1_2 => "TLSv1.2".to_owned(),
+            ProtocolVersion::TLSv1_3 => "TLSv1.3".to_owned(),
+            v => format!("{:?}", v),
+        });
+        let certs = session.get_peer_certificates().unwrap_or_default();
+
+        let mut ext = Extensions::new();
+        ext.insert(TlsInfo::new(
+            version,
+            session
+                .get_negotiated_ciphersuite()
+                .map(|s| format!("{:?}", s.suite)),
+            session.get_alpn_protocol().map(|p| p.as_bytes().to_vec()),
+            certs
+                .into_iter()
+                .map(|cert| PeerCertificate::from_der(cert.0))
+                .collect(),
+        ));
+        Some(Rc::new(ext))
+    }
 }
@@ -7,6 +7,7 @@ use futures::Future;
 use actix::msgs::StopArbiter;
 use actix::{Actor, Arbiter, AsyncContext, Context, Handler, Message, Response};
 
+use super::listener::{ListenerAddr, StdListener, StdStream};
 use super::server::{Connections, ServiceHandler};
 use super::Token;
 
@@ -19,28 +20,28 @@ pub(crate) struct Conn<T> {
 }
 
 pub(crate) struct Socket {
-    pub lst: net::TcpListener,
-    pub addr: net::SocketAddr,
+    pub lst: StdListener,
+    pub addr: ListenerAddr,
     pub token: Token,
 }
 
 #[derive(Clone)]
 pub(crate) struct WorkerClient {
     pub idx: usize,
-    tx: UnboundedSender<Conn<net::TcpStream>>,
+    tx: UnboundedSender<Conn<StdStream>>,
     conns: Connections,
 }
 
 impl WorkerClient {
     pub fn new(
-        idx: usize, tx: UnboundedSender<Conn<net::TcpStream>>, conns: Connections,
+        idx: usize, tx: UnboundedSender<Conn<StdStream>>, conns: Connections,
     ) -> Self {
         WorkerClient { idx, tx, conns }
     }
 
     pub fn send(
-        &self, msg: Conn<net::TcpStream>,
-    ) -> Result<(), SendError<Conn<net::TcpStream>>> {
+        &self, msg: Conn<StdStream>,
+    ) -> Result<(), SendError<Conn<StdStream>>> {
         self.tx.unbounded_send(msg)
     }
 
@@ -102,10 +103,10 @@ impl Worker {
     }
 }
 
-impl Handler<Conn<net::TcpStream>> for Worker {
+impl Handler<Conn<StdStream>> for Worker {
     type Result = ();
 
-    fn handle(&mut self, msg: Conn<net::TcpStream>, _: &mut Context<Self>) {
+    fn handle(&mut self, msg: Conn<StdStream>, _: &mut Context<Self>) {
         self.handlers[msg.handler.0].handle(msg.token, msg.io, msg.peer)
     }
 }
@@ -4,6 +4,11 @@ use std::str::FromStr;
 use std::sync::mpsc;
 use std::{net, thread};
 
+#[cfg(all(unix, feature = "uds"))]
+use std::path::PathBuf;
+#[cfg(all(unix, feature = "uds"))]
+use std::{env, fs, process};
+
 use actix_inner::{Actor, Addr, System};
 
 use cookie::Cookie;
@@ -70,6 +75,8 @@ pub struct TestServer {
     ssl: bool,
     conn: Addr<ClientConnector>,
     rt: Runtime,
+    #[cfg(all(unix, feature = "uds"))]
+    uds: Option<PathBuf>,
 }
 
 impl TestServer {
@@ -134,17 +141,23 @@ impl TestServer {
             conn,
             ssl: false,
             rt: Runtime::new().unwrap(),
+            #[cfg(all(unix, feature = "uds"))]
+            uds: None,
         }
     }
 
     fn get_conn() -> Addr<ClientConnector> {
+        TestServer::connector().start()
+    }
+
+    fn connector() -> ClientConnector {
         #[cfg(feature = "alpn")]
         {
             use openssl::ssl::{SslConnector, SslMethod, SslVerifyMode};
 
             let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
             builder.set_verify(SslVerifyMode::NONE);
-            ClientConnector::with_connector(builder.build()).start()
+            ClientConnector::with_connector(builder.build())
         }
         #[cfg(all(feature = "rust-tls", not(feature = "alpn")))]
         {
@@ -154,11 +167,11 @@ impl TestServer {
             let mut config = ClientConfig::new();
             let pem_file = &mut BufReader::new(File::open("tests/cert.pem").unwrap());
             config.root_store.add_pem_file(pem_file).unwrap();
-            ClientConnector::with_connector(config).start()
+            ClientConnector::with_connector(config)
         }
         #[cfg(not(any(feature = "alpn", feature = "rust-tls")))]
         {
-            ClientConnector::default().start()
+            ClientConnector::default()
         }
     }
 
@@ -177,6 +190,12 @@ impl TestServer {
         self.addr
     }
 
+    #[cfg(all(unix, feature = "uds"))]
+    /// Path of the unix domain socket, if test server listens on it
+    pub fn uds_path(&self) -> Option<&PathBuf> {
+        self.uds.as_ref()
+    }
+
     /// Construct test server url
     pub fn url(&self, uri: &str) -> String {
         if uri.starts_with('/') {
@@ -199,6 +218,13 @@ impl TestServer {
     /// Stop http server
     fn stop(&mut self) {
         System::current().stop();
+
+        #[cfg(all(unix, feature = "uds"))]
+        {
+            if let Some(ref path) = self.uds {
+                let _ = fs::remove_file(path);
+            }
+        }
     }
 
     /// Execute future on current core
@@ -227,17 +253,17 @@ impl TestServer {
 
     /// Create `GET` request
     pub fn get(&self) -> ClientRequestBuilder {
-        ClientRequest::get(self.url("/").as_str())
+        self.client(Method::GET, "/")
     }
 
     /// Create `POST` request
     pub fn post(&self) -> ClientRequestBuilder {
-        ClientRequest::post(self.url("/").as_str())
+        self.client(Method::POST, "/")
     }
 
     /// Create `HEAD` request
     pub fn head(&self) -> ClientRequestBuilder {
-        ClientRequest::head(self.url("/").as_str())
+        self.client(Method::HEAD, "/")
     }
 
     /// Connect to test http server
@@ -266,6 +292,8 @@ pub struct TestServerBuilder<S> {
     ssl: Option<SslAcceptorBuilder>,
     #[cfg(feature = "rust-tls")]
     rust_ssl: Option<ServerConfig>,
+    #[cfg(all(unix, feature = "uds"))]
+    uds: bool,
 }
 
 impl<S: 'static> TestServerBuilder<S> {
@@ -280,6 +308,8 @@ impl<S: 'static> TestServerBuilder<S> {
             ssl: None,
             #[cfg(feature = "rust-tls")]
             rust_ssl: None,
+            #[cfg(all(unix, feature = "uds"))]
+            uds: false,
         }
     }
 
@@ -297,6 +327,15 @@ impl<S: 'static> TestServerBuilder<S> {
         self
     }
 
+    #[cfg(all(unix, feature = "uds"))]
+    /// Listen on unix domain socket in temporary directory
+    ///
+    /// Requests made with `TestServer` methods are sent over this socket.
+    pub fn uds(mut self) -> Self {
+        self.uds = true;
+        self
+    }
+
     #[allow(unused_mut)]
     /// Configure test application and run test server
     pub fn start<F>(mut self, config: F) -> TestServer
@@ -307,6 +346,17 @@ impl<S: 'static> TestServerBuilder<S> {
 
         let mut has_ssl = false;
 
+        #[cfg(all(unix, feature = "uds"))]
+        let uds = if self.uds {
+            let port = TestServer::unused_addr().port();
+            let name = format!("actix-test-{}-{}.sock", process::id(), port);
+            Some(env::temp_dir().join(name))
+        } else {
+            None
+        };
+        #[cfg(all(unix, feature = "uds"))]
+        let uds_path = uds.clone();
+
         #[cfg(feature = "alpn")]
         {
             has_ssl = has_ssl || self.ssl.is_some();
@@ -330,8 +380,19 @@ impl<S: 'static> TestServerBuilder<S> {
             }).workers(1)
             .disable_signals();
 
-            tx.send((System::current(), addr, TestServer::get_conn()))
-                .unwrap();
+            let mut conn = TestServer::connector();
+            let mut listening = has_ssl;
+
+            #[cfg(all(unix, feature = "uds"))]
+            {
+                if let Some(path) = uds_path {
+                    conn = conn.uds_host("localhost", path.clone());
+                    srv = srv.bind_uds(path).unwrap();
+                    listening = true;
+                }
+            }
+
+            tx.send((System::current(), addr, conn.start())).unwrap();
 
             #[cfg(feature = "alpn")]
             {
@@ -349,7 +410,7 @@ impl<S: 'static> TestServerBuilder<S> {
                     srv = srv.listen_with(tcp, RustlsAcceptor::new(ssl));
                 }
             }
-            if !has_ssl {
+            if !listening {
                 let tcp = net::TcpListener::bind(addr).unwrap();
                 srv = srv.listen(tcp);
             }
@@ -365,6 +426,8 @@ impl<S: 'static> TestServerBuilder<S> {
             conn,
             ssl: has_ssl,
             rt: Runtime::new().unwrap(),
+            #[cfg(all(unix, feature = "uds"))]
+            uds,
         }
     }
 }
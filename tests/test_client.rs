@@ -86,7 +86,12 @@ fn test_no_decompress() {
     let mut srv =
         test::TestServer::new(|app| app.handler(|_| HttpResponse::Ok().body(STR)));
 
-    let request = srv.get().disable_decompress().finish().unwrap();
+    let request = srv
+        .get()
+        .header("accept-encoding", "gzip")
+        .disable_decompress()
+        .finish()
+        .unwrap();
     let response = srv.execute(request.send()).unwrap();
     assert!(response.status().is_success());
 
@@ -99,7 +104,12 @@ fn test_no_decompress() {
     assert_eq!(Bytes::from(dec), Bytes::from_static(STR.as_ref()));
 
     // POST
-    let request = srv.post().disable_decompress().finish().unwrap();
+    let request = srv
+        .post()
+        .header("accept-encoding", "gzip")
+        .disable_decompress()
+        .finish()
+        .unwrap();
     let response = srv.execute(request.send()).unwrap();
 
     let bytes = srv.execute(response.body()).unwrap();
@@ -136,6 +146,38 @@ fn test_client_gzip_encoding() {
     assert_eq!(bytes, Bytes::from_static(STR.as_ref()));
 }
 
+#[cfg(feature = "zstd")]
+#[test]
+fn test_client_zstd_encoding() {
+    let mut srv = test::TestServer::new(|app| {
+        app.handler(|req: &HttpRequest| {
+            req.body()
+                .and_then(|bytes: Bytes| {
+                    Ok(HttpResponse::Ok()
+                        .content_encoding(http::ContentEncoding::Zstd)
+                        .body(bytes))
+                }).responder()
+        })
+    });
+
+    // client request
+    let request = srv
+        .post()
+        .content_encoding(http::ContentEncoding::Zstd)
+        .body(STR)
+        .unwrap();
+    let response = srv.execute(request.send()).unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(
+        response.headers().get(http::header::CONTENT_ENCODING).unwrap(),
+        "zstd"
+    );
+
+    // read response
+    let bytes = srv.execute(response.body()).unwrap();
+    assert_eq!(bytes, Bytes::from_static(STR.as_ref()));
+}
+
 #[test]
 fn test_client_gzip_encoding_large() {
     let data = STR.repeat(10);
@@ -437,7 +479,10 @@ fn test_default_headers() {
 
     let request = srv.get().finish().unwrap();
     let repr = format!("{:?}", request);
+    #[cfg(not(feature = "zstd"))]
     assert!(repr.contains("\"accept-encoding\": \"gzip, deflate\""));
+    #[cfg(feature = "zstd")]
+    assert!(repr.contains("\"accept-encoding\": \"zstd, gzip, deflate\""));
     assert!(repr.contains(concat!(
         "\"user-agent\": \"actix-web/",
         env!("CARGO_PKG_VERSION"),
@@ -126,6 +126,48 @@ fn test_resource_middleware() {
     assert_eq!(num3.load(Ordering::Relaxed), 1);
 }
 
+#[test]
+fn test_resource_middleware_method_not_allowed() {
+    let num1 = Arc::new(AtomicUsize::new(0));
+    let num2 = Arc::new(AtomicUsize::new(0));
+    let num3 = Arc::new(AtomicUsize::new(0));
+
+    let act_num1 = Arc::clone(&num1);
+    let act_num2 = Arc::clone(&num2);
+    let act_num3 = Arc::clone(&num3);
+
+    let mut srv = test::TestServer::with_factory(move || {
+        let mw = MiddlewareTest {
+            start: Arc::clone(&act_num1),
+            response: Arc::clone(&act_num2),
+            finish: Arc::clone(&act_num3),
+        };
+        App::new().resource("/test", move |r| {
+            r.middleware(mw);
+            r.get().f(|_| HttpResponse::Ok());
+        })
+    });
+
+    let request = srv.post().uri(srv.url("/test")).finish().unwrap();
+    let response = srv.execute(request.send()).unwrap();
+    assert_eq!(response.status(), http::StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(
+        response.headers().get(http::header::ALLOW).unwrap(),
+        "GET, OPTIONS"
+    );
+
+    let request = srv
+        .client(http::Method::OPTIONS, "/test")
+        .finish()
+        .unwrap();
+    let response = srv.execute(request.send()).unwrap();
+    assert!(response.status().is_success());
+
+    assert_eq!(num1.load(Ordering::Relaxed), 2);
+    assert_eq!(num2.load(Ordering::Relaxed), 2);
+    assert_eq!(num3.load(Ordering::Relaxed), 2);
+}
+
 #[test]
 fn test_resource_middleware_multiple() {
     let num1 = Arc::new(AtomicUsize::new(0));
@@ -1053,3 +1095,205 @@ fn test_session_storage_middleware() {
 
     srv.execute(request.send()).unwrap();
 }
+
+#[test]
+fn test_timeout_middleware() {
+    let mut srv = test::TestServer::with_factory(|| {
+        App::new()
+            .middleware(middleware::Timeout::new(Duration::from_millis(50)))
+            .resource("/fast", |r| {
+                r.route().a(|_| {
+                    Delay::new(Instant::now() + Duration::from_millis(10))
+                        .and_then(|_| Ok(HttpResponse::Ok()))
+                })
+            }).resource("/slow", |r| {
+                r.route().a(|_| {
+                    Delay::new(Instant::now() + Duration::from_secs(10))
+                        .and_then(|_| Ok(HttpResponse::Ok()))
+                })
+            }).scope("/scope", |scope| {
+                scope
+                    .middleware(
+                        middleware::Timeout::new(Duration::from_millis(10))
+                            .status(http::StatusCode::SERVICE_UNAVAILABLE),
+                    ).resource("/slow", |r| {
+                        r.route().a(|_| {
+                            Delay::new(Instant::now() + Duration::from_secs(10))
+                                .and_then(|_| Ok(HttpResponse::Ok()))
+                        })
+                    })
+            })
+    });
+
+    let request = srv.get().uri(srv.url("/fast")).finish().unwrap();
+    let response = srv.execute(request.send()).unwrap();
+    assert!(response.status().is_success());
+
+    let request = srv.get().uri(srv.url("/slow")).finish().unwrap();
+    let response = srv.execute(request.send()).unwrap();
+    assert_eq!(response.status(), http::StatusCode::GATEWAY_TIMEOUT);
+
+    let request = srv.get().uri(srv.url("/scope/slow")).finish().unwrap();
+    let response = srv.execute(request.send()).unwrap();
+    assert_eq!(response.status(), http::StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[test]
+fn test_rate_limit_middleware() {
+    use actix_web::middleware::ratelimit::{MemoryStore, Quota, RateLimit};
+
+    let store = Arc::new(MemoryStore::new());
+    let mut srv = test::TestServer::with_factory(move || {
+        App::new()
+            .middleware(RateLimit::with_store(
+                Quota::sliding_window(2, Duration::from_secs(60)),
+                store.clone(),
+            )).resource("/", |r| r.f(|_| HttpResponse::Ok()))
+    });
+
+    for remaining in &["1", "0"] {
+        let request = srv.get().finish().unwrap();
+        let response = srv.execute(request.send()).unwrap();
+        assert!(response.status().is_success());
+        assert_eq!(
+            response.headers().get("ratelimit-remaining").unwrap(),
+            remaining
+        );
+    }
+
+    let request = srv.get().finish().unwrap();
+    let response = srv.execute(request.send()).unwrap();
+    assert_eq!(response.status(), http::StatusCode::TOO_MANY_REQUESTS);
+    assert!(response.headers().contains_key(http::header::RETRY_AFTER));
+}
+
+#[test]
+fn test_request_id_middleware() {
+    use actix_web::middleware::request_id::{RequestId, RequestIdService};
+
+    let mut srv = test::TestServer::with_factory(|| {
+        App::new()
+            .middleware(RequestIdService::new())
+            .resource("/", |r| {
+                r.f(|req| {
+                    let upstream = client::get("http://localhost/")
+                        .forward_request_id(req)
+                        .finish()
+                        .unwrap();
+                    let id = RequestId::extract(req).unwrap();
+                    assert_eq!(
+                        upstream.headers().get("x-request-id").unwrap(),
+                        id.as_str()
+                    );
+                    HttpResponse::Ok()
+                })
+            })
+    });
+
+    let request = srv.get().header("x-request-id", "abc").finish().unwrap();
+    let response = srv.execute(request.send()).unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(response.headers().get("x-request-id").unwrap(), "abc");
+
+    let request = srv.get().finish().unwrap();
+    let response = srv.execute(request.send()).unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(response.headers().get("x-request-id").unwrap().len(), 36);
+}
+
+#[test]
+fn test_metrics_middleware() {
+    use actix_web::middleware::metrics::Metrics;
+
+    let metrics = Metrics::new();
+    let mut srv = test::TestServer::with_factory(move || {
+        let handler = metrics.clone();
+        App::new()
+            .middleware(metrics.clone())
+            .resource("/metrics", move |r| r.h(handler))
+            .resource("/user/{name}", |r| r.f(|_| HttpResponse::Ok()))
+            .scope("/api", |scope| {
+                scope.nested("/v1", |scope| {
+                    scope.resource("/items/{id}", |r| r.f(|_| HttpResponse::Ok()))
+                })
+            })
+    });
+
+    let request = srv.get().uri(srv.url("/user/john")).finish().unwrap();
+    let response = srv.execute(request.send()).unwrap();
+    assert!(response.status().is_success());
+    let request = srv.get().uri(srv.url("/api/v1/items/1")).finish().unwrap();
+    let response = srv.execute(request.send()).unwrap();
+    assert!(response.status().is_success());
+    let request = srv.get().uri(srv.url("/unknown")).finish().unwrap();
+    let response = srv.execute(request.send()).unwrap();
+    assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+
+    let request = srv.get().uri(srv.url("/metrics")).finish().unwrap();
+    let response = srv.execute(request.send()).unwrap();
+    assert!(response.status().is_success());
+    let bytes = srv.execute(response.body()).unwrap();
+    let body = String::from_utf8(bytes.to_vec()).unwrap();
+    assert!(body.contains(
+        "http_requests_total{method=\"GET\",path=\"/user/{name}\",status=\"200\"} 1"
+    ));
+    assert!(body.contains(
+        "http_requests_total{method=\"GET\",path=\"/api/v1/items/{id}\",\
+         status=\"200\"} 1"
+    ));
+    assert!(
+        body.contains("http_requests_total{method=\"GET\",path=\"\",status=\"404\"} 1")
+    );
+    assert!(body.contains("http_requests_in_flight{method=\"GET\"} 1"));
+    assert!(body.contains("# TYPE http_server_connections gauge"));
+}
+
+#[test]
+fn test_json_logger_scope() {
+    use actix_web::middleware::LogField;
+    use std::sync::Mutex;
+
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&lines);
+    let mut srv = test::TestServer::with_factory(move || {
+        let sink = Arc::clone(&sink);
+        let logger = middleware::Logger::json()
+            .field("pattern", LogField::Pattern)
+            .sink(move |line| sink.lock().unwrap().push(line.to_owned()));
+        App::new().middleware(logger).scope("/api", |scope| {
+            scope.resource("/items/{id}", |r| {
+                r.name("item");
+                r.f(|_| HttpResponse::Ok())
+            })
+        })
+    });
+
+    let request = srv.get().uri(srv.url("/api/items/1")).finish().unwrap();
+    let response = srv.execute(request.send()).unwrap();
+    assert!(response.status().is_success());
+    let lines = lines.lock().unwrap();
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].contains("\"route\":\"item\""));
+    assert!(lines[0].contains("\"pattern\":\"/api/items/{id}\""));
+}
+
+#[test]
+fn test_logger_scope_pattern() {
+    use std::sync::Mutex;
+
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&lines);
+    let mut srv = test::TestServer::with_factory(move || {
+        let sink = Arc::clone(&sink);
+        let logger = middleware::Logger::new("%R")
+            .sink(move |line| sink.lock().unwrap().push(line.to_owned()));
+        App::new().middleware(logger).scope("/api", |scope| {
+            scope.resource("/items/{id}", |r| r.f(|_| HttpResponse::Ok()))
+        })
+    });
+
+    let request = srv.get().uri(srv.url("/api/items/1")).finish().unwrap();
+    let response = srv.execute(request.send()).unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(*lines.lock().unwrap(), vec!["/api/items/{id}".to_owned()]);
+}
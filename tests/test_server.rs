@@ -7,10 +7,14 @@ extern crate flate2;
 extern crate futures;
 extern crate h2;
 extern crate http as modhttp;
+#[cfg(feature = "alpn")]
+extern crate openssl;
 extern crate rand;
 extern crate tokio;
 extern crate tokio_reactor;
 extern crate tokio_tcp;
+#[cfg(feature = "zstd")]
+extern crate zstd;
 
 use std::io::{Read, Write};
 use std::sync::Arc;
@@ -217,6 +221,323 @@ fn test_panic() {
     let _ = sys.stop();
 }
 
+#[test]
+#[cfg(all(unix, feature = "uds"))]
+fn test_uds() {
+    use actix::{Actor, System};
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::UnixListener;
+    use std::sync::mpsc;
+    use std::{env, fs, io, process};
+
+    let path = env::temp_dir().join(format!("actix-test-uds-{}.sock", process::id()));
+    let _ = fs::remove_file(&path);
+
+    // stale socket file
+    drop(UnixListener::bind(&path).unwrap());
+
+    let (tx, rx) = mpsc::channel();
+    let p = path.clone();
+    thread::spawn(move || {
+        System::run(move || {
+            let srv = server::new(|| {
+                App::new().resource("/", |r| r.f(|_| HttpResponse::Ok().body(STR)))
+            }).uds_permissions(0o600);
+
+            let srv = srv.bind_uds(&p).unwrap();
+            assert!(srv.addrs().is_empty());
+            srv.start();
+
+            let conn = client::ClientConnector::default()
+                .uds_host("backend", p)
+                .start();
+            let _ = tx.send((conn, System::current()));
+        });
+    });
+    let (conn, sys) = rx.recv().unwrap();
+    System::set_current(sys.clone());
+
+    let mode = fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+
+    // socket is in use
+    let res = server::new(|| App::new()).bind_uds(&path);
+    assert_eq!(res.err().unwrap().kind(), io::ErrorKind::AddrInUse);
+
+    let mut rt = Runtime::new().unwrap();
+    {
+        let req = client::ClientRequest::get("http://backend/")
+            .with_connector(conn)
+            .finish()
+            .unwrap();
+        let response = rt.block_on(req.send()).unwrap();
+        assert!(response.status().is_success());
+        let bytes = rt.block_on(response.body()).unwrap();
+        assert_eq!(bytes, Bytes::from_static(STR.as_ref()));
+    }
+
+    let _ = sys.stop();
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+#[cfg(all(unix, feature = "uds"))]
+fn test_uds_test_server() {
+    let mut srv = test::TestServer::build()
+        .uds()
+        .start(|app| app.handler(|_| HttpResponse::Ok().body(STR)));
+    assert!(srv.uds_path().unwrap().exists());
+
+    let request = srv.get().finish().unwrap();
+    let response = srv.execute(request.send()).unwrap();
+    assert!(response.status().is_success());
+
+    let bytes = srv.execute(response.body()).unwrap();
+    assert_eq!(bytes, Bytes::from_static(STR.as_ref()));
+}
+
+#[test]
+fn test_proxy_protocol() {
+    use actix::System;
+    use std::net;
+    use std::sync::mpsc;
+
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        System::run(move || {
+            let app = || {
+                App::new().resource("/", |r| {
+                    r.f(|req| {
+                        let peer = req.peer_addr().map(|a| a.to_string());
+                        let info = req.connection_info();
+                        let remote = info.remote().map(|r| r.to_owned());
+                        format!("{:?} {:?}", peer, remote)
+                    })
+                })
+            };
+            let trusted = server::new(app)
+                .proxy_protocol(server::ProxyProtocol::new().trust("127.0.0.0/8"))
+                .bind("127.0.0.1:0")
+                .unwrap();
+            let untrusted = server::new(app)
+                .proxy_protocol(server::ProxyProtocol::new().trust("10.0.0.0/8"))
+                .bind("127.0.0.1:0")
+                .unwrap();
+            let proxy = server::ProxyProtocol::new()
+                .trust("127.0.0.0/8")
+                .timeout(time::Duration::from_millis(100));
+            let slow = server::new(app)
+                .proxy_protocol(proxy)
+                .bind("127.0.0.1:0")
+                .unwrap();
+            let addrs = (trusted.addrs()[0], untrusted.addrs()[0], slow.addrs()[0]);
+            trusted.start();
+            untrusted.start();
+            slow.start();
+            let _ = tx.send((addrs, System::current()));
+        });
+    });
+    let ((trusted, untrusted, slow), sys) = rx.recv().unwrap();
+
+    let request = |addr: net::SocketAddr, data: &[u8]| {
+        let mut stream = net::TcpStream::connect(addr).unwrap();
+        stream.write_all(data).unwrap();
+        let mut resp = String::new();
+        let _ = stream.read_to_string(&mut resp);
+        resp
+    };
+
+    // version 1
+    let resp = request(
+        trusted,
+        b"PROXY TCP4 192.168.0.1 192.168.0.11 56324 443\r\n\
+          GET / HTTP/1.1\r\nConnection: close\r\n\r\n",
+    );
+    assert!(resp.starts_with("HTTP/1.1 200"));
+    assert!(resp.ends_with("Some(\"192.168.0.1:56324\") Some(\"192.168.0.1:56324\")"));
+
+    // version 2
+    let mut data = b"\r\n\r\n\0\r\nQUIT\n\x21\x11\x00\x0c".to_vec();
+    data.extend_from_slice(&[10, 0, 0, 1, 10, 0, 0, 2, 0x1f, 0x90, 0, 80]);
+    data.extend_from_slice(b"GET / HTTP/1.1\r\nConnection: close\r\n\r\n");
+    let resp = request(trusted, &data);
+    assert!(resp.starts_with("HTTP/1.1 200"));
+    assert!(resp.ends_with("Some(\"10.0.0.1:8080\") Some(\"10.0.0.1:8080\")"));
+
+    // header is required from trusted peer
+    let resp = request(trusted, b"GET / HTTP/1.1\r\nConnection: close\r\n\r\n");
+    assert!(resp.is_empty());
+
+    // untrusted peer can not spoof address
+    let resp = request(
+        untrusted,
+        b"PROXY TCP4 192.168.0.1 192.168.0.11 56324 443\r\n\
+          GET / HTTP/1.1\r\nConnection: close\r\n\r\n",
+    );
+    assert!(!resp.contains("192.168.0.1"));
+    let resp = request(untrusted, b"GET / HTTP/1.1\r\nConnection: close\r\n\r\n");
+    assert!(resp.starts_with("HTTP/1.1 200"));
+    assert!(resp.contains("127.0.0.1"));
+
+    // incomplete header is dropped after timeout
+    let mut stream = net::TcpStream::connect(slow).unwrap();
+    stream.write_all(b"PROXY TCP4 192.168.0.1").unwrap();
+    let mut resp = String::new();
+    let _ = stream.read_to_string(&mut resp);
+    assert!(resp.is_empty());
+
+    let _ = sys.stop();
+}
+
+#[test]
+#[cfg(feature = "alpn")]
+fn test_tls_client_certificate() {
+    use actix_web::server::{PeerCertificate, TlsInfo};
+    use openssl::ssl::{
+        SslAcceptor, SslConnector, SslFiletype, SslMethod, SslVerifyMode,
+    };
+
+    let mut builder = SslAcceptor::mozilla_intermediate(SslMethod::tls()).unwrap();
+    builder
+        .set_private_key_file("tests/key.pem", SslFiletype::PEM)
+        .unwrap();
+    builder
+        .set_certificate_chain_file("tests/cert.pem")
+        .unwrap();
+    // test certificate is self-signed
+    builder.set_verify_callback(SslVerifyMode::PEER, |_, _| true);
+
+    let srv = test::TestServer::build().ssl(builder).start(|app| {
+        app.resource("/", |r| {
+            r.with(|(req, cert): (HttpRequest, PeerCertificate)| {
+                let ext = req.stream_extensions().unwrap();
+                let info = ext.get::<TlsInfo>().unwrap();
+                format!(
+                    "{} {} {}",
+                    cert.common_name().unwrap(),
+                    info.version().unwrap().starts_with("TLS"),
+                    info.cipher().is_some()
+                )
+            })
+        });
+    });
+
+    let request = |with_cert: bool| {
+        let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
+        builder.set_verify(SslVerifyMode::NONE);
+        if with_cert {
+            builder
+                .set_private_key_file("tests/key.pem", SslFiletype::PEM)
+                .unwrap();
+            builder
+                .set_certificate_file("tests/cert.pem", SslFiletype::PEM)
+                .unwrap();
+        }
+        let tcp = std::net::TcpStream::connect(srv.addr()).unwrap();
+        let mut stream = builder.build().connect("localhost", tcp).unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nconnection: close\r\n\r\n")
+            .unwrap();
+        let mut data = String::new();
+        let _ = stream.read_to_string(&mut data);
+        data
+    };
+
+    let data = request(true);
+    assert!(data.starts_with("HTTP/1.1 200 OK"));
+    assert!(data.ends_with("www.example.com true true"));
+
+    let data = request(false);
+    assert!(data.starts_with("HTTP/1.1 403 Forbidden"));
+}
+
+#[test]
+#[cfg(feature = "alpn")]
+fn test_tls_cert_resolver() {
+    use actix_web::server::OpensslCertResolver;
+    use openssl::asn1::Asn1Time;
+    use openssl::hash::MessageDigest;
+    use openssl::nid::Nid;
+    use openssl::pkey::PKey;
+    use openssl::rsa::Rsa;
+    use openssl::ssl::{SslAcceptor, SslConnector, SslMethod, SslVerifyMode};
+    use openssl::x509::{X509NameBuilder, X509};
+    use std::fs;
+
+    let dir = std::env::temp_dir().join(format!("actix-web-sni-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    // self-signed certificate with the common name
+    let write_cert = |file: &str, name: &str| {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let mut subject = X509NameBuilder::new().unwrap();
+        subject.append_entry_by_text("CN", name).unwrap();
+        let subject = subject.build();
+        let mut cert = X509::builder().unwrap();
+        cert.set_version(2).unwrap();
+        cert.set_subject_name(&subject).unwrap();
+        cert.set_issuer_name(&subject).unwrap();
+        cert.set_pubkey(&key).unwrap();
+        cert.set_not_before(&Asn1Time::days_from_now(0).unwrap()).unwrap();
+        cert.set_not_after(&Asn1Time::days_from_now(1).unwrap()).unwrap();
+        cert.sign(&key, MessageDigest::sha256()).unwrap();
+
+        let cert_file = dir.join(format!("{}.pem", file));
+        let key_file = dir.join(format!("{}-key.pem", file));
+        fs::write(&cert_file, cert.build().to_pem().unwrap()).unwrap();
+        fs::write(&key_file, key.private_key_to_pem_pkcs8().unwrap()).unwrap();
+        (cert_file, key_file)
+    };
+
+    let resolver = OpensslCertResolver::new();
+    let (cert, key) = write_cert("default", "default");
+    resolver.set_default(&cert, &key).unwrap();
+    let (cert, key) = write_cert("wildcard", "wildcard");
+    resolver.add("*.example.com", &cert, &key).unwrap();
+
+    let mut builder = SslAcceptor::mozilla_intermediate(SslMethod::tls()).unwrap();
+    resolver.configure(&mut builder);
+    let srv = test::TestServer::build()
+        .ssl(builder)
+        .start(|app| app.handler(|_| HttpResponse::Ok()));
+
+    let common_name = |host: Option<&str>| {
+        let mut builder = SslConnector::builder(SslMethod::tls()).unwrap();
+        builder.set_verify(SslVerifyMode::NONE);
+        let mut config = builder.build().configure().unwrap();
+        config.set_use_server_name_indication(host.is_some());
+        config.set_verify_hostname(false);
+
+        let tcp = std::net::TcpStream::connect(srv.addr()).unwrap();
+        let stream = config.connect(host.unwrap_or(""), tcp).unwrap();
+        let cert = stream.ssl().peer_certificate().unwrap();
+        let name = cert.subject_name().entries_by_nid(Nid::COMMONNAME).next();
+        let name = name.unwrap().data().as_utf8().unwrap().to_string();
+        name
+    };
+
+    assert_eq!(common_name(Some("www.example.com")), "wildcard");
+    assert_eq!(common_name(Some("localhost")), "default");
+    assert_eq!(common_name(None), "default");
+
+    // new connections use renewed certificate
+    write_cert("default", "renewed");
+    resolver.reload().unwrap();
+    assert_eq!(common_name(Some("localhost")), "renewed");
+    assert_eq!(common_name(Some("www.example.com")), "wildcard");
+
+    // certificate is not replaced until its private key is updated too
+    let (other, _) = write_cert("other", "other");
+    fs::copy(&other, dir.join("default.pem")).unwrap();
+    assert!(resolver.reload().is_err());
+    assert_eq!(common_name(Some("localhost")), "renewed");
+
+    resolver.remove("*.example.com");
+    assert_eq!(common_name(Some("www.example.com")), "renewed");
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
 #[test]
 fn test_simple() {
     let mut srv = test::TestServer::new(|app| app.handler(|_| HttpResponse::Ok()));
@@ -618,6 +939,39 @@ fn test_body_brotli() {
     assert_eq!(Bytes::from(dec), Bytes::from_static(STR.as_ref()));
 }
 
+#[cfg(feature = "zstd")]
+#[test]
+fn test_zstd_encoding() {
+    let mut srv = test::TestServer::new(|app| {
+        app.handler(|req: &HttpRequest| {
+            req.body()
+                .and_then(|bytes: Bytes| Ok(HttpResponse::Ok().body(bytes)))
+                .responder()
+        })
+    });
+
+    // client request
+    let enc = zstd::encode_all(STR.as_bytes(), 3).unwrap();
+    let request = srv
+        .post()
+        .header(http::header::CONTENT_ENCODING, "zstd")
+        .header(http::header::ACCEPT_ENCODING, "gzip, zstd;q=1.0, br;q=0.8")
+        .disable_decompress()
+        .body(enc)
+        .unwrap();
+    let response = srv.execute(request.send()).unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(
+        response.headers().get(http::header::CONTENT_ENCODING).unwrap(),
+        "zstd"
+    );
+
+    // read response
+    let bytes = srv.execute(response.body()).unwrap();
+    let dec = zstd::decode_all(&bytes[..]).unwrap();
+    assert_eq!(Bytes::from(dec), Bytes::from_static(STR.as_ref()));
+}
+
 #[test]
 fn test_gzip_encoding() {
     let mut srv = test::TestServer::new(|app| {
@@ -932,6 +1286,166 @@ fn test_application() {
     assert!(response.status().is_success());
 }
 
+#[test]
+fn test_compression_policy() {
+    use actix_web::http::ContentEncoding;
+
+    let mut srv = test::TestServer::with_factory(|| {
+        App::new()
+            .compression(
+                server::CompressionPolicy::new()
+                    .level(ContentEncoding::Gzip, 9)
+                    .min_size(1024)
+                    .include("text/*")
+                    .preference(&[ContentEncoding::Gzip, ContentEncoding::Br]),
+            ).resource("/text", |r| {
+                r.f(|_| HttpResponse::Ok().content_type("text/plain").body(STR))
+            }).resource("/small", |r| {
+                r.f(|_| HttpResponse::Ok().content_type("text/plain").body("small"))
+            }).resource("/json", |r| {
+                r.f(|_| HttpResponse::Ok().content_type("application/json").body(STR))
+            })
+    });
+
+    // gzip is preferred by server
+    let request = srv
+        .get()
+        .uri(srv.url("/text"))
+        .header("accept-encoding", "br, gzip")
+        .disable_decompress()
+        .finish()
+        .unwrap();
+    let response = srv.execute(request.send()).unwrap();
+    assert_eq!(
+        response.headers().get(http::header::CONTENT_ENCODING).unwrap(),
+        "gzip"
+    );
+    let bytes = srv.execute(response.body()).unwrap();
+    let mut e = GzDecoder::new(&bytes[..]);
+    let mut dec = Vec::new();
+    e.read_to_end(&mut dec).unwrap();
+    assert_eq!(Bytes::from(dec), Bytes::from_static(STR.as_ref()));
+
+    // client quality wins over server preference
+    let request = srv
+        .get()
+        .uri(srv.url("/text"))
+        .header("accept-encoding", "br, gzip;q=0.5")
+        .disable_decompress()
+        .finish()
+        .unwrap();
+    let response = srv.execute(request.send()).unwrap();
+    assert_eq!(
+        response.headers().get(http::header::CONTENT_ENCODING).unwrap(),
+        "br"
+    );
+
+    for path in &["/small", "/json"] {
+        let request = srv
+            .get()
+            .uri(srv.url(path))
+            .header("accept-encoding", "gzip")
+            .disable_decompress()
+            .finish()
+            .unwrap();
+        let response = srv.execute(request.send()).unwrap();
+        assert!(response.headers().get(http::header::CONTENT_ENCODING).is_none());
+    }
+}
+
+#[test]
+fn test_decompression_limits() {
+    let mut srv = test::TestServer::with_factory(|| {
+        App::new()
+            .max_decoded_size(100_000)
+            .resource("/", |r| {
+                r.f(|req| {
+                    req.body()
+                        .limit(10_000_000)
+                        .and_then(|body: Bytes| Ok(HttpResponse::Ok().body(body)))
+                        .responder()
+                })
+            }).resource("/raw", |r| {
+                r.decompress(false);
+                r.f(|req| {
+                    req.body()
+                        .and_then(|body: Bytes| Ok(HttpResponse::Ok().body(body)))
+                        .responder()
+                })
+            })
+    });
+
+    let mut e = GzEncoder::new(Vec::new(), Compression::default());
+    e.write_all(&[b'a'; 1_000_000]).unwrap();
+    let enc = e.finish().unwrap();
+
+    let request = srv
+        .post()
+        .uri(srv.url("/"))
+        .header(http::header::CONTENT_ENCODING, "gzip")
+        .body(enc.clone())
+        .unwrap();
+    let response = srv.execute(request.send()).unwrap();
+    assert_eq!(response.status(), http::StatusCode::PAYLOAD_TOO_LARGE);
+
+    // payload is passed as is
+    let request = srv
+        .post()
+        .uri(srv.url("/raw"))
+        .header(http::header::CONTENT_ENCODING, "gzip")
+        .body(enc.clone())
+        .unwrap();
+    let response = srv.execute(request.send()).unwrap();
+    assert!(response.status().is_success());
+    let bytes = srv.execute(response.body()).unwrap();
+    assert_eq!(bytes, Bytes::from(enc));
+}
+
+#[test]
+fn test_route_decompression() {
+    let mut srv = test::TestServer::with_factory(|| {
+        App::new().resource("/", |r| {
+            r.post().decompress(false).f(|req| {
+                req.body()
+                    .and_then(|body: Bytes| Ok(HttpResponse::Ok().body(body)))
+                    .responder()
+            });
+            r.put().f(|req| {
+                req.body()
+                    .and_then(|body: Bytes| Ok(HttpResponse::Ok().body(body)))
+                    .responder()
+            })
+        })
+    });
+
+    let mut e = GzEncoder::new(Vec::new(), Compression::default());
+    e.write_all(STR.as_ref()).unwrap();
+    let enc = e.finish().unwrap();
+
+    // payload is passed as is
+    let request = srv
+        .post()
+        .uri(srv.url("/"))
+        .header(http::header::CONTENT_ENCODING, "gzip")
+        .body(enc.clone())
+        .unwrap();
+    let response = srv.execute(request.send()).unwrap();
+    assert!(response.status().is_success());
+    let bytes = srv.execute(response.body()).unwrap();
+    assert_eq!(bytes, Bytes::from(enc.clone()));
+
+    // payload is decompressed
+    let request = srv
+        .client(http::Method::PUT, "/")
+        .header(http::header::CONTENT_ENCODING, "gzip")
+        .body(enc)
+        .unwrap();
+    let response = srv.execute(request.send()).unwrap();
+    assert!(response.status().is_success());
+    let bytes = srv.execute(response.body()).unwrap();
+    assert_eq!(bytes, Bytes::from_static(STR.as_ref()));
+}
+
 #[test]
 fn test_server_cookies() {
     use actix_web::http;